@@ -3,7 +3,14 @@
 import { useState, useEffect } from "react";
 import { useWallet, useConnection, useAnchorWallet } from "@solana/wallet-adapter-react";
 import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, ComputeBudgetProgram } from "@solana/web3.js";
-import { ForgeClient, createForgeClient } from "@/lib/forgeClient";
+import { BN } from "@coral-xyz/anchor";
+import {
+  ForgeClient,
+  REPLAY_POLICY_UNLIMITED,
+  ReplayPolicyValue,
+  createForgeClient,
+  replayPolicyTag,
+} from "@/lib/forgeClient";
 import { useParams } from "next/navigation";
 import { useWalletModal } from "@solana/wallet-adapter-react-ui";
 
@@ -55,6 +62,7 @@ type Recipe = {
   minted?: { toString(): string } | number;
   supplyCap?: { toString(): string };
   ingredientConstraints?: Constraint[];
+  replayPolicy?: ReplayPolicyValue;
 };
 
 type ForgeConfigType = Record<string, unknown>;
@@ -425,15 +433,29 @@ export default function MintPage() {
       const mintAta = deriveAta(publicKey, mintKeypair.publicKey);
       const metadata = deriveMetadataPda(mintKeypair.publicKey);
       const masterEdition = deriveMasterEditionPda(mintKeypair.publicKey);
+      const [forgedAsset] = client.deriveForgedAssetPDA(mintKeypair.publicKey);
 
-      // Build ingredient hash chunks and compute input hash AFTER mint generation
-      // For 0-ingredient recipes, the hash includes both forger and mint pubkeys
-      // This allows each wallet to forge multiple times while preserving security
+      // Build ingredient hash chunks and compute the input hash under the
+      // recipe's replay policy. Unlimited recipes get a fresh random nonce so
+      // every attempt lands on a new RecipeUse PDA.
       const constraintsForHash = (recipe.ingredientConstraints || []) as Parameters<
         typeof client.buildIngredientHashChunks
       >[0];
-      const ingredientChunks = client.buildIngredientHashChunks(constraintsForHash, publicKey);
-      const inputHash = await client.computeInputHash(ingredientChunks, publicKey, mintKeypair.publicKey);
+      const { chunks, inputs } = client.buildIngredientHashChunks(constraintsForHash, publicKey);
+      const replayPolicy = recipe.replayPolicy ?? "unlimited";
+      const nonceBytes = crypto.getRandomValues(new Uint8Array(8));
+      const nonce =
+        replayPolicyTag(replayPolicy) === REPLAY_POLICY_UNLIMITED
+          ? new DataView(nonceBytes.buffer).getBigUint64(0, true)
+          : BigInt(0);
+      const inputHash = await client.computeInputHash({
+        recipe: recipePDA,
+        replayPolicy,
+        forger: publicKey,
+        nonce,
+        chunks,
+        inputs,
+      });
       
       // Validate input hash is 32 bytes
       if (inputHash.length !== 32) {
@@ -444,19 +466,17 @@ export default function MintPage() {
       // Use Uint8Array directly to match the working script pattern
       const inputHashForArgs = inputHash;
       
-      // Derive RecipeUse PDA with the new hash (includes mint pubkey for 0-ingredient recipes)
       const [recipeUsePDA] = client.deriveRecipeUsePDA(recipePDA, inputHash);
       
       // Check if RecipeUse already exists (anti-replay protection)
-      // With mint pubkey in hash, each mint attempt gets a unique RecipeUse PDA
       console.log("Checking if RecipeUse already exists at:", recipeUsePDA.toBase58());
       const accountInfo = await connection.getAccountInfo(recipeUsePDA);
       
       if (accountInfo && accountInfo.data.length > 0) {
-        console.log("RecipeUse account EXISTS - this mint has already been used");
+        console.log("RecipeUse account EXISTS - this forge has already been used");
         throw new Error(
-          `This specific mint has already been used to forge an NFT. ` +
-          `Each mint can only be used once. Please try again (a new mint will be generated).`
+          `This recipe has already been forged with this wallet or these ingredients, ` +
+          `and its replay policy does not allow forging it again.`
         );
       } else {
         console.log("RecipeUse account does not exist - can proceed with forging");
@@ -477,8 +497,8 @@ export default function MintPage() {
       console.log("Input hash type:", inputHash.constructor.name);
       console.log("Input hash length:", inputHash.length);
       
-      // Build args exactly like the working script: { inputHash: Uint8Array }
-      const args = { inputHash: inputHashForArgs };
+      // Build args like the working script: { inputHash: Uint8Array, nonce: BN }
+      const args = { inputHash: inputHashForArgs, nonce: new BN(nonce.toString()) };
       console.log("Transaction args:", { inputHashLength: args.inputHash.length });
       
       // Add compute budget instructions to prevent CU exhaustion
@@ -500,6 +520,7 @@ export default function MintPage() {
           forger: publicKey,
           mint: mintKeypair.publicKey,
           mintAta,
+          forgedAsset,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           metadata,
           masterEdition,
@@ -525,6 +546,7 @@ export default function MintPage() {
         mintAta: mintAta.toBase58(),
         metadata: metadata.toBase58(),
         masterEdition: masterEdition.toBase58(),
+        forgedAsset: forgedAsset.toBase58(),
       });
       
       // Build transaction first to inspect it
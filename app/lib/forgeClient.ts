@@ -8,6 +8,35 @@ import { Umi, createUmi } from "@metaplex-foundation/umi";
 // IDL type - will be loaded dynamically
 type ForgeIDL = anchor.Idl & { address?: string };
 
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/** Domain separator and version of the on-chain input hash schema. */
+export const INPUT_HASH_DOMAIN = "forge-input-hash";
+export const INPUT_HASH_VERSION = 1;
+
+/** A recipe's replay policy as decoded by Anchor, or its camelCase name. */
+export type ReplayPolicyValue =
+  | "oncePerWallet"
+  | "oncePerInputSet"
+  | "unlimited"
+  | { oncePerWallet: unknown }
+  | { oncePerInputSet: unknown }
+  | { unlimited: unknown };
+
+/** Tag of the replay policy whose forges each carry a fresh nonce. */
+export const REPLAY_POLICY_UNLIMITED = 2;
+
+/** Variant tag of a replay policy (matches the Borsh variant index). */
+export function replayPolicyTag(policy: ReplayPolicyValue): number {
+  const name = typeof policy === "string" ? policy : Object.keys(policy)[0];
+  const tag = ["oncePerWallet", "oncePerInputSet", "unlimited"].indexOf(name);
+  if (tag < 0) {
+    throw new Error(`Unknown replay policy: ${name}`);
+  }
+  return tag;
+}
+
 export interface ForgeClientConfig {
   connection: Connection;
   programId: PublicKey;
@@ -116,6 +145,16 @@ export class ForgeClient {
     );
   }
 
+  /**
+   * Derives the ForgedAsset provenance PDA for a forged mint
+   */
+  deriveForgedAssetPDA(mint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("forged-asset"), mint.toBuffer()],
+      this.programId
+    );
+  }
+
   /**
    * Fetches the ForgeConfig account
    */
@@ -177,48 +216,72 @@ export class ForgeClient {
   }
 
   /**
-   * Computes input hash from ingredient constraints
-   * This matches the on-chain hash computation using SHA256 (hashv)
-   * 
-   * On-chain uses: solana_program::hash::hashv(&hash_inputs)
-   * This is SHA256 of concatenated inputs
-   * 
-   * For recipes with no ingredient constraints, includes both the forger's pubkey
-   * and the mint pubkey to allow each wallet to forge multiple times while preserving security.
+   * Fetches the ForgedAsset provenance record of a forged mint
    */
-  async computeInputHash(ingredientChunks: Uint8Array[], forgerPubkey?: PublicKey, mintPubkey?: PublicKey): Promise<Uint8Array> {
-    if (ingredientChunks.length === 0) {
-      // For recipes with no ingredient constraints, include both the forger's pubkey
-      // and the mint pubkey in the hash. This allows each wallet to forge multiple times
-      // (each mint is unique) while preserving security (prevents replay attacks).
-      // This matches on-chain: hashv(&[forger_pubkey, mint_pubkey])
-      if (!forgerPubkey) {
-        throw new Error("forgerPubkey is required when computing hash for recipes with no ingredient constraints");
-      }
-      if (!mintPubkey) {
-        throw new Error("mintPubkey is required when computing hash for recipes with no ingredient constraints");
-      }
-      const forgerBytes = forgerPubkey.toBytes();
-      const mintBytes = mintPubkey.toBytes();
-      // Concatenate forger + mint pubkeys (matching hashv behavior)
-      const combined = new Uint8Array(forgerBytes.length + mintBytes.length);
-      combined.set(forgerBytes, 0);
-      combined.set(mintBytes, forgerBytes.length);
-      const hashBuffer = await crypto.subtle.digest("SHA-256", combined);
-      return new Uint8Array(hashBuffer).slice(0, 32);
+  async fetchForgedAsset(mint: PublicKey) {
+    const [forgedAssetPDA] = this.deriveForgedAssetPDA(mint);
+    try {
+      const accounts = this.program.account as unknown as {
+        forgedAsset: { fetch: (pk: PublicKey) => Promise<unknown> };
+      };
+      return await accounts.forgedAsset.fetch(forgedAssetPDA);
+    } catch {
+      return null; // Mint was not forged by this program
+    }
+  }
+
+  /**
+   * Computes the v1 input hash that keys a forge's RecipeUse PDA.
+   * Matches `input_hash_v1` on-chain (see docs/recipes.md, "Input Hash Schema"):
+   *
+   *   sha256("forge-input-hash" || 1 || policy tag || recipe || body)
+   *
+   * where the body is `forger` (oncePerWallet), `ingredients` (oncePerInputSet)
+   * or `forger || nonce (u64 LE) || ingredients` (unlimited), and `ingredients`
+   * is the u16-LE-counted, u16-LE-length-prefixed chunks followed by the
+   * u16-LE-counted concrete inputs. Set `authorizedNonce` when a signed
+   * authorization covered the nonce; oncePerWallet then hashes `forger || nonce`.
+   */
+  async computeInputHash(params: {
+    recipe: PublicKey;
+    replayPolicy: ReplayPolicyValue;
+    forger: PublicKey;
+    nonce?: bigint;
+    authorizedNonce?: boolean;
+    chunks: Uint8Array[];
+    inputs: PublicKey[];
+  }): Promise<Uint8Array> {
+    const policy = replayPolicyTag(params.replayPolicy);
+    const u16 = (value: number) => new Uint8Array([value & 0xff, (value >> 8) & 0xff]);
+    const parts: Uint8Array[] = [
+      new TextEncoder().encode(INPUT_HASH_DOMAIN),
+      new Uint8Array([INPUT_HASH_VERSION, policy]),
+      params.recipe.toBytes(),
+    ];
+    const ingredients = (): Uint8Array[] => [
+      u16(params.chunks.length),
+      ...params.chunks.flatMap((chunk) => [u16(chunk.length), chunk]),
+      u16(params.inputs.length),
+      ...params.inputs.map((input) => input.toBytes()),
+    ];
+    const nonceBytes = new Uint8Array(8);
+    new DataView(nonceBytes.buffer).setBigUint64(0, params.nonce ?? BigInt(0), true);
+    if (policy === 0) {
+      parts.push(params.forger.toBytes());
+      if (params.authorizedNonce) parts.push(nonceBytes);
+    } else if (policy === 1) {
+      parts.push(...ingredients());
+    } else {
+      parts.push(params.forger.toBytes(), nonceBytes, ...ingredients());
     }
 
-    // Concatenate all chunks (matching hashv behavior)
-    const totalLength = ingredientChunks.reduce((sum, chunk) => sum + chunk.length, 0);
-    const concatenated = new Uint8Array(totalLength);
+    const preimage = new Uint8Array(parts.reduce((sum, part) => sum + part.length, 0));
     let offset = 0;
-    for (const chunk of ingredientChunks) {
-      concatenated.set(chunk, offset);
-      offset += chunk.length;
+    for (const part of parts) {
+      preimage.set(part, offset);
+      offset += part.length;
     }
-    
-    // SHA256 hash
-    const hashBuffer = await crypto.subtle.digest("SHA-256", concatenated);
+    const hashBuffer = await crypto.subtle.digest("SHA-256", preimage);
     return new Uint8Array(hashBuffer).slice(0, 32);
   }
 
@@ -247,23 +310,33 @@ export class ForgeClient {
   }
 
   /**
-   * Builds ingredient hash chunks from recipe constraints
-   * Matches on-chain logic in forge_asset instruction
-   * Handles both PublicKey objects (from Anchor deserialization) and strings
+   * Builds ingredient hash chunks and the concrete inputs each leaf is
+   * satisfied with, matching the on-chain IngredientVerifier.
+   * Handles both PublicKey objects (from Anchor deserialization) and strings.
+   *
+   * TokenMint inputs default to the forger's ATA. CollectionNft and Allowlist
+   * inputs depend on what the forger presents, so `resolved` must supply the
+   * NFT mint or the allowlist leaf.
    */
   buildIngredientHashChunks(
     constraints: Array<
       | { Signer: { authority: PublicKey | string } }
-      | { CustomSeeds: { seeds: Uint8Array | ArrayBuffer | number[] } }
+      | {
+          CustomSeeds: {
+            programId: PublicKey | string;
+            seeds: Uint8Array | ArrayBuffer | number[];
+            includeForger: boolean;
+          };
+        }
       | { TokenMint: { mint: PublicKey | string; amount: number | string | bigint } }
       | { CollectionNft: { collectionMint: PublicKey | string } }
       | { Allowlist: { merkleRoot: Uint8Array | ArrayBuffer | number[] } }
     >,
-    _forgerPubkey: PublicKey
-  ): Uint8Array[] {
+    forgerPubkey: PublicKey,
+    resolved: { collectionNftMint?: PublicKey; allowlistLeaf?: Uint8Array } = {}
+  ): { chunks: Uint8Array[]; inputs: PublicKey[] } {
     const chunks: Uint8Array[] = [];
-    // Avoid unused param lint; reserved for future per-forger hashing adjustments
-    void _forgerPubkey;
+    const inputs: PublicKey[] = [];
 
     for (const constraint of constraints) {
       if ("Signer" in constraint) {
@@ -276,16 +349,26 @@ export class ForgeClient {
         chunk[0] = 0;
         chunk.set(authorityPubkey.toBytes(), 1);
         chunks.push(chunk);
+        inputs.push(authorityPubkey);
       } else if ("CustomSeeds" in constraint) {
-        // Variant tag 1 + seed bytes
-        if (!constraint.CustomSeeds?.seeds) {
-          throw new Error("CustomSeeds constraint missing seeds field");
+        // Variant tag 1 + program_id (32) + include_forger (1) + derived PDA (32)
+        if (!constraint.CustomSeeds?.programId || !constraint.CustomSeeds.seeds) {
+          throw new Error("CustomSeeds constraint missing programId or seeds field");
         }
+        const programId = this.normalizePublicKey(constraint.CustomSeeds.programId);
+        const includeForger = Boolean(constraint.CustomSeeds.includeForger);
         const seedBytes = new Uint8Array(constraint.CustomSeeds.seeds);
-        const chunk = new Uint8Array(1 + seedBytes.length);
+        const seeds: Buffer[] = [];
+        if (seedBytes.length > 0) seeds.push(Buffer.from(seedBytes));
+        if (includeForger) seeds.push(forgerPubkey.toBuffer());
+        const [pda] = PublicKey.findProgramAddressSync(seeds, programId);
+        const chunk = new Uint8Array(66);
         chunk[0] = 1;
-        chunk.set(seedBytes, 1);
+        chunk.set(programId.toBytes(), 1);
+        chunk[33] = includeForger ? 1 : 0;
+        chunk.set(pda.toBytes(), 34);
         chunks.push(chunk);
+        inputs.push(pda);
       } else if ("TokenMint" in constraint) {
         // Variant tag 2 + mint (32 bytes) + amount (8 bytes)
         if (!constraint.TokenMint?.mint) {
@@ -305,21 +388,33 @@ export class ForgeClient {
         view.setBigUint64(0, amount, true); // true = little endian
         chunk.set(amountBytes, 33);
         chunks.push(chunk);
+        const [forgerAta] = PublicKey.findProgramAddressSync(
+          [forgerPubkey.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+          ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+        inputs.push(forgerAta);
       } else if ("CollectionNft" in constraint) {
         // Variant tag 3 + collection_mint (32 bytes)
         if (!constraint.CollectionNft?.collectionMint) {
           throw new Error("CollectionNft constraint missing collectionMint field");
         }
+        if (!resolved.collectionNftMint) {
+          throw new Error("CollectionNft constraint needs the NFT mint being presented");
+        }
         const collectionMint = this.normalizePublicKey(constraint.CollectionNft.collectionMint);
         const chunk = new Uint8Array(33);
         chunk[0] = 3;
         chunk.set(collectionMint.toBytes(), 1);
         chunks.push(chunk);
+        inputs.push(resolved.collectionNftMint);
       } else if ("Allowlist" in constraint) {
         // Variant tag 4 + merkle_root (32 bytes)
         if (!constraint.Allowlist?.merkleRoot) {
           throw new Error("Allowlist constraint missing merkleRoot field");
         }
+        if (!resolved.allowlistLeaf || resolved.allowlistLeaf.length !== 32) {
+          throw new Error("Allowlist constraint needs the 32-byte leaf being proven");
+        }
         const merkleRoot = new Uint8Array(constraint.Allowlist.merkleRoot);
         if (merkleRoot.length !== 32) {
           throw new Error(`Allowlist merkleRoot must be 32 bytes, got ${merkleRoot.length}`);
@@ -328,10 +423,17 @@ export class ForgeClient {
         chunk[0] = 4;
         chunk.set(merkleRoot, 1);
         chunks.push(chunk);
+        inputs.push(new PublicKey(resolved.allowlistLeaf));
+      } else {
+        // Composite (AllOf/AnyOf/AtLeast) chunks depend on which branches the
+        // forger satisfies, so they cannot be derived from the recipe alone.
+        throw new Error(
+          `Unsupported ingredient constraint for client-side hashing: ${Object.keys(constraint)[0]}`
+        );
       }
     }
 
-    return chunks;
+    return { chunks, inputs };
   }
 }
 
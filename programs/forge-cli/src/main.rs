@@ -0,0 +1,762 @@
+//! `forge-cli`: manages forge configs and recipes, forges assets and
+//! inspects program accounts.
+//!
+//! Every command prints the addresses it derives. With `--dry-run` it prints
+//! the instructions it would send instead of sending them.
+
+mod manifest;
+mod output;
+mod plan;
+
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program},
+    AccountDeserialize, Discriminator,
+};
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use forge_client::{
+    accounts,
+    forge::{
+        eligibility::{check_eligibility, AccountSnapshot, SnapshotAccount},
+        instructions::{InitializeForgeArgs, SetForgeConfigArgs, SetRecipeStatusArgs},
+        state::{
+            ClosedRecipe, ForgeCommit, ForgeConfig, ForgeEscrow, ForgedAsset, OutputKind, Recipe,
+            RecipeMode, RecipeStatus, RecipeUse,
+        },
+    },
+    ingredients::{self, IngredientChoices},
+    instructions, pda,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::{
+    manifest::RecipeManifest,
+    plan::{Action, Plan},
+};
+
+/// Compute units requested for forges; minting with metadata needs more than
+/// the default 200,000.
+const FORGE_COMPUTE_UNITS: u32 = 400_000;
+
+#[derive(Parser)]
+#[command(
+    name = "forge-cli",
+    version,
+    about = "Manage Forge configs and recipes"
+)]
+struct Cli {
+    /// RPC endpoint of the cluster.
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "SOLANA_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+    /// Keypair that pays for and signs transactions [default:
+    /// ~/.config/solana/id.json].
+    #[arg(long, short = 'k', global = true, env = "WALLET_PATH")]
+    keypair: Option<PathBuf>,
+    /// Print the derived addresses and instructions instead of sending them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the forge config of the keypair.
+    Init(InitArgs),
+    /// Manage the forge config.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Manage recipes.
+    #[command(subcommand)]
+    Recipe(RecipeCommand),
+    /// Forge an asset from a recipe.
+    Forge(ForgeArgs),
+    /// Decode and print a Forge program account.
+    Inspect { address: Pubkey },
+}
+
+#[derive(Args)]
+struct InitArgs {
+    /// Collection mint newly forged assets belong to.
+    #[arg(long)]
+    collection: Option<Pubkey>,
+    /// Freeze authority assigned to minted assets.
+    #[arg(long)]
+    freeze_authority: Option<Pubkey>,
+    /// Default royalty in basis points (0-10000).
+    #[arg(long, default_value_t = 500)]
+    royalty_bps: u16,
+    /// Create the forge with recipe creation disabled.
+    #[arg(long)]
+    disable_recipe_creation: bool,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Change forge config fields; omitted fields are left unchanged.
+    Set(ConfigSetArgs),
+    /// Upgrade a forge config created before event sequence numbers.
+    Migrate,
+}
+
+#[derive(Args)]
+struct ConfigSetArgs {
+    /// Collection mint, or `none` to clear it.
+    #[arg(long)]
+    collection: Option<MaybePubkey>,
+    /// Freeze authority, or `none` to clear it.
+    #[arg(long)]
+    freeze_authority: Option<MaybePubkey>,
+    /// Default royalty in basis points (0-10000).
+    #[arg(long)]
+    royalty_bps: Option<u16>,
+    /// Whether new recipes can be created.
+    #[arg(long)]
+    recipe_creation_enabled: Option<bool>,
+}
+
+#[derive(Subcommand)]
+enum RecipeCommand {
+    /// Create a recipe from a TOML or JSON manifest.
+    Create { file: PathBuf },
+    /// Update a recipe to match its manifest, sending only the fields that
+    /// changed.
+    Update { file: PathBuf },
+    /// Check manifests offline, reporting every error.
+    Validate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Show what applying a directory of manifests would change, field by
+    /// field.
+    Plan {
+        dir: PathBuf,
+        /// Forge authority [default: the keypair].
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Print the plan for a directory of manifests, then apply it.
+    Apply { dir: PathBuf },
+    /// Move a recipe to another lifecycle status.
+    Status {
+        slug: String,
+        #[arg(long)]
+        version: u16,
+        #[arg(value_enum)]
+        status: StatusArg,
+    },
+    /// Upgrade a recipe created before use records were counted.
+    Migrate {
+        slug: String,
+        #[arg(long)]
+        version: u16,
+    },
+    /// List every recipe of a forge.
+    List {
+        /// Forge authority [default: the keypair].
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+}
+
+#[derive(Args)]
+struct ForgeArgs {
+    slug: String,
+    #[arg(long)]
+    version: u16,
+    /// Forge authority [default: the keypair].
+    #[arg(long)]
+    authority: Option<Pubkey>,
+    /// Nonce hashed by unlimited recipes [default: the current time in
+    /// nanoseconds].
+    #[arg(long)]
+    nonce: Option<u64>,
+    /// Leaf and proof accounts of an allowlist ingredient, in order.
+    #[arg(long = "allowlist-account")]
+    allowlist_accounts: Vec<Pubkey>,
+    /// NFT mint presented for a collection ingredient.
+    #[arg(long)]
+    collection_nft: Option<Pubkey>,
+    /// Forged asset mint presented for a forged-output ingredient.
+    #[arg(long = "forged-asset")]
+    forged_assets: Vec<Pubkey>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatusArg {
+    Draft,
+    Active,
+    Paused,
+    Retired,
+}
+
+impl From<StatusArg> for RecipeStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Draft => Self::Draft,
+            StatusArg::Active => Self::Active,
+            StatusArg::Paused => Self::Paused,
+            StatusArg::Retired => Self::Retired,
+        }
+    }
+}
+
+/// An address, or `none` to clear an optional one.
+#[derive(Clone, Copy)]
+struct MaybePubkey(Option<Pubkey>);
+
+impl FromStr for MaybePubkey {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        match text {
+            "none" => Ok(Self(None)),
+            _ => Ok(Self(Some(Pubkey::from_str(text)?))),
+        }
+    }
+}
+
+/// Cluster connection and signer shared by every command.
+struct Runner {
+    rpc: RpcClient,
+    payer: Keypair,
+    dry_run: bool,
+}
+
+impl Runner {
+    /// Prints `addresses`, then sends `instructions` in one transaction
+    /// signed by the payer and `signers`, or prints them on a dry run.
+    fn submit(
+        &self,
+        addresses: &[(&str, Pubkey)],
+        instructions: Vec<Instruction>,
+        signers: &[&Keypair],
+    ) -> anyhow::Result<()> {
+        output::print_addresses(addresses);
+        if self.dry_run {
+            for (index, instruction) in instructions.iter().enumerate() {
+                output::print_instruction(index, instruction);
+            }
+            return Ok(());
+        }
+
+        let mut all_signers: Vec<&dyn Signer> = vec![&self.payer];
+        all_signers.extend(signers.iter().map(|signer| *signer as &dyn Signer));
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.rpc.get_latest_blockhash()?,
+        );
+        let signature = self.rpc.send_and_confirm_transaction(&transaction)?;
+        println!("Signature: {signature}");
+        Ok(())
+    }
+
+    fn authority(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // Validation runs offline and signs nothing.
+    if let Command::Recipe(RecipeCommand::Validate { files }) = &cli.command {
+        return validate_manifests(files);
+    }
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair_path()?,
+    };
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow!("reading keypair {}: {err}", keypair_path.display()))?;
+    let runner = Runner {
+        rpc: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+        dry_run: cli.dry_run,
+    };
+
+    match cli.command {
+        Command::Init(args) => init(&runner, args),
+        Command::Config(ConfigCommand::Set(args)) => set_config(&runner, args),
+        Command::Config(ConfigCommand::Migrate) => migrate_config(&runner),
+        Command::Recipe(RecipeCommand::Create { file }) => create_recipe(&runner, &file),
+        Command::Recipe(RecipeCommand::Update { file }) => update_recipe(&runner, &file),
+        Command::Recipe(RecipeCommand::Validate { .. }) => unreachable!(),
+        Command::Recipe(RecipeCommand::Plan { dir, authority }) => {
+            let plan = plan_recipes(
+                &runner,
+                &dir,
+                &authority.unwrap_or_else(|| runner.authority()),
+            )?;
+            println!("{plan}");
+            Ok(())
+        }
+        Command::Recipe(RecipeCommand::Apply { dir }) => apply_recipes(&runner, &dir),
+        Command::Recipe(RecipeCommand::Status {
+            slug,
+            version,
+            status,
+        }) => set_recipe_status(&runner, &slug, version, status.into()),
+        Command::Recipe(RecipeCommand::Migrate { slug, version }) => {
+            migrate_recipe(&runner, &slug, version)
+        }
+        Command::Recipe(RecipeCommand::List { authority }) => {
+            list_recipes(&runner, &authority.unwrap_or_else(|| runner.authority()))
+        }
+        Command::Forge(args) => forge(&runner, args),
+        Command::Inspect { address } => inspect(&runner, &address),
+    }
+}
+
+fn default_keypair_path() -> anyhow::Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .context("no home directory; pass --keypair")?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+fn init(runner: &Runner, args: InitArgs) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let instruction = instructions::InitializeForge::new(
+        authority,
+        InitializeForgeArgs {
+            collection_mint: args.collection,
+            freeze_authority: args.freeze_authority,
+            default_royalty_bps: args.royalty_bps,
+            recipe_creation_enabled: !args.disable_recipe_creation,
+        },
+    )
+    .instruction();
+    runner.submit(
+        &[("forge_config", pda::forge_config(&authority).0)],
+        vec![instruction],
+        &[],
+    )
+}
+
+fn set_config(runner: &Runner, args: ConfigSetArgs) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let instruction = instructions::SetForgeConfig::new(
+        authority,
+        SetForgeConfigArgs {
+            collection_mint: args.collection.map(|value| value.0),
+            freeze_authority: args.freeze_authority.map(|value| value.0),
+            default_royalty_bps: args.royalty_bps,
+            recipe_creation_enabled: args.recipe_creation_enabled,
+        },
+    )
+    .instruction();
+    runner.submit(
+        &[("forge_config", pda::forge_config(&authority).0)],
+        vec![instruction],
+        &[],
+    )
+}
+
+fn migrate_config(runner: &Runner) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let instruction = instructions::MigrateForgeConfig::new(authority).instruction();
+    runner.submit(
+        &[("forge_config", pda::forge_config(&authority).0)],
+        vec![instruction],
+        &[],
+    )
+}
+
+/// Accounts `create_recipe`, `update_recipe` and `set_recipe_status` read
+/// when the recipe ends up active.
+fn activation_accounts(output_kind: &OutputKind) -> Vec<AccountMeta> {
+    match output_kind {
+        OutputKind::Edition { parent_mint } => vec![AccountMeta::new_readonly(*parent_mint, false)],
+        _ => Vec::new(),
+    }
+}
+
+fn create_recipe(runner: &Runner, file: &Path) -> anyhow::Result<()> {
+    let manifest = RecipeManifest::load(file)?;
+    let authority = runner.authority();
+    let forge_config = pda::forge_config(&authority).0;
+    let args = manifest.create_args(&forge_config)?;
+    let remaining_accounts = activation_accounts(&args.output_kind);
+    let builder =
+        instructions::CreateRecipe::new(authority, args).remaining_accounts(remaining_accounts);
+    runner.submit(
+        &[("forge_config", forge_config), ("recipe", builder.recipe())],
+        vec![builder.instruction()],
+        &[],
+    )
+}
+
+fn update_recipe(runner: &Runner, file: &Path) -> anyhow::Result<()> {
+    let manifest = RecipeManifest::load(file)?;
+    let authority = runner.authority();
+    let forge_config = pda::forge_config(&authority).0;
+    let recipe_key = pda::recipe(&forge_config, &manifest.slug, manifest.version).0;
+    let recipe = accounts::fetch_recipe(&runner.rpc, &recipe_key)?;
+    let args = manifest.update_args(&recipe)?;
+    let output_kind = args.output_kind.unwrap_or(recipe.output_kind);
+    let instruction = instructions::UpdateRecipe::new(authority, recipe_key, args)
+        .remaining_accounts(activation_accounts(&output_kind))
+        .instruction();
+    runner.submit(
+        &[("forge_config", forge_config), ("recipe", recipe_key)],
+        vec![instruction],
+        &[],
+    )
+}
+
+fn validate_manifests(files: &[PathBuf]) -> anyhow::Result<()> {
+    let mut invalid = 0;
+    for file in files {
+        // Addresses depend on the forge, so any forge config will do here.
+        let result = RecipeManifest::load(file)
+            .and_then(|manifest| Ok(manifest.validate(&Pubkey::default())?));
+        match result {
+            Ok(_) => println!("{}: ok", file.display()),
+            Err(err) => {
+                invalid += 1;
+                println!("{}: {err:#}", file.display());
+            }
+        }
+    }
+    if invalid > 0 {
+        bail!("{invalid} of {} manifest(s) are invalid", files.len());
+    }
+    Ok(())
+}
+
+fn plan_recipes(runner: &Runner, dir: &Path, authority: &Pubkey) -> anyhow::Result<Plan> {
+    let manifests = plan::load_dir(dir)?;
+    let forge_config = pda::forge_config(authority).0;
+    let recipes = accounts::fetch_recipes_of(&runner.rpc, &forge_config)?;
+    Ok(plan::plan(&forge_config, &manifests, &recipes))
+}
+
+fn apply_recipes(runner: &Runner, dir: &Path) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let plan = plan_recipes(runner, dir, &authority)?;
+    println!("{plan}");
+    if plan.invalid() > 0 {
+        bail!("fix the invalid manifests before applying");
+    }
+
+    for step in plan.steps {
+        let (Some(recipe), Some(output_kind)) = (step.recipe, step.output_kind) else {
+            continue;
+        };
+        let remaining_accounts = activation_accounts(&output_kind);
+        let transaction = match step.action {
+            Action::Create(args) => vec![instructions::CreateRecipe::new(authority, args)
+                .remaining_accounts(remaining_accounts)
+                .instruction()],
+            Action::Fork {
+                previous_version,
+                args,
+            } => vec![instructions::ForkRecipeVersion::new(
+                authority,
+                &step.slug,
+                previous_version,
+                args,
+            )
+            .remaining_accounts(remaining_accounts)
+            .instruction()],
+            Action::Update { args, status } => {
+                let mut transaction = Vec::new();
+                if let Some(args) = args {
+                    transaction.push(
+                        instructions::UpdateRecipe::new(authority, recipe, args)
+                            .remaining_accounts(remaining_accounts.clone())
+                            .instruction(),
+                    );
+                }
+                if let Some(status) = status {
+                    transaction.push(
+                        instructions::SetRecipeStatus::new(
+                            authority,
+                            recipe,
+                            SetRecipeStatusArgs { status },
+                        )
+                        .remaining_accounts(remaining_accounts)
+                        .instruction(),
+                    );
+                }
+                transaction
+            }
+            Action::Unchanged | Action::Invalid(_) => continue,
+        };
+        println!("Applying {} v{}:", step.slug, step.version);
+        runner.submit(&[("recipe", recipe)], transaction, &[])?;
+    }
+    Ok(())
+}
+
+fn set_recipe_status(
+    runner: &Runner,
+    slug: &str,
+    version: u16,
+    status: RecipeStatus,
+) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let forge_config = pda::forge_config(&authority).0;
+    let recipe_key = pda::recipe(&forge_config, slug, version).0;
+    let recipe = accounts::fetch_recipe(&runner.rpc, &recipe_key)?;
+    let instruction =
+        instructions::SetRecipeStatus::new(authority, recipe_key, SetRecipeStatusArgs { status })
+            .remaining_accounts(activation_accounts(&recipe.output_kind))
+            .instruction();
+    runner.submit(
+        &[("forge_config", forge_config), ("recipe", recipe_key)],
+        vec![instruction],
+        &[],
+    )
+}
+
+fn migrate_recipe(runner: &Runner, slug: &str, version: u16) -> anyhow::Result<()> {
+    let authority = runner.authority();
+    let forge_config = pda::forge_config(&authority).0;
+    let recipe = pda::recipe(&forge_config, slug, version).0;
+    let instruction = instructions::MigrateRecipe::new(authority, recipe).instruction();
+    runner.submit(
+        &[("forge_config", forge_config), ("recipe", recipe)],
+        vec![instruction],
+        &[],
+    )
+}
+
+fn list_recipes(runner: &Runner, authority: &Pubkey) -> anyhow::Result<()> {
+    let forge_config = pda::forge_config(authority).0;
+    let mut recipes = accounts::fetch_recipes_of(&runner.rpc, &forge_config)?;
+    recipes.sort_by(|(_, a), (_, b)| (&a.slug, a.version).cmp(&(&b.slug, b.version)));
+    println!("Recipes of forge config {forge_config}:");
+    for (key, recipe) in recipes {
+        let supply = match recipe.supply_cap {
+            Some(cap) => format!("{}/{cap}", recipe.minted),
+            None => recipe.minted.to_string(),
+        };
+        println!(
+            "  {key}  {} v{}  {:?}  minted {supply}",
+            recipe.slug, recipe.version, recipe.status
+        );
+    }
+    Ok(())
+}
+
+fn forge(runner: &Runner, args: ForgeArgs) -> anyhow::Result<()> {
+    let forger = runner.payer.pubkey();
+    let forge_config = pda::forge_config(&args.authority.unwrap_or(forger)).0;
+    let recipe_key = pda::recipe(&forge_config, &args.slug, args.version).0;
+    let recipe = accounts::fetch_recipe(&runner.rpc, &recipe_key)?;
+    if recipe.mode != RecipeMode::Mint {
+        bail!(
+            "{} v{} evolves existing assets",
+            recipe.slug,
+            recipe.version
+        );
+    }
+
+    let choices = IngredientChoices {
+        allowlist_accounts: args.allowlist_accounts,
+        collection_nft: args.collection_nft,
+        forged_assets: args.forged_assets,
+    };
+    let ingredient_metas = ingredients::ingredient_accounts(&recipe, &forger, &choices);
+    if !runner.dry_run {
+        if let Some(cosigner) = ingredient_metas.iter().find(|meta| meta.is_signer) {
+            bail!("the recipe needs {} to co-sign the forge", cosigner.pubkey);
+        }
+    }
+    let output_metas = ingredients::output_accounts(&recipe_key, &recipe, &forger, &[])
+        .context("only semi-fungible extra outputs are supported")?;
+    if !recipe.trait_table.is_empty()
+        && accounts::fetch_forge_commit(&runner.rpc, &pda::forge_commit(&recipe_key, &forger).0)?
+            .is_none()
+    {
+        bail!("recipes with a trait table need a forge commit first");
+    }
+
+    let remaining_metas: Vec<AccountMeta> = ingredient_metas
+        .iter()
+        .chain(&output_metas)
+        .cloned()
+        .collect();
+    let snapshot = snapshot(runner, &remaining_metas)?;
+    let eligibility = check_eligibility(&recipe, &forger, &snapshot);
+    if !eligibility.is_eligible() {
+        let mut report = String::from("cannot forge:");
+        if let Err(err) = &eligibility.recipe {
+            report.push_str(&format!("\n  recipe: {err}"));
+        }
+        for (index, result) in eligibility.constraints.iter().enumerate() {
+            if let Err(err) = result {
+                report.push_str(&format!("\n  ingredient #{index}: {err}"));
+            }
+        }
+        bail!(report);
+    }
+    let nonce = args.nonce.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let input_hash =
+        ingredients::forge_input_hash(&recipe_key, &recipe, &forger, nonce, &snapshot)?;
+
+    let mint = Keypair::new();
+    let mut transaction = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        FORGE_COMPUTE_UNITS,
+    )];
+    // Semi-fungible extra outputs mint into the forger's token account for
+    // the shared output mint.
+    for (index, output) in recipe.extra_outputs.iter().enumerate() {
+        if output.output_kind == OutputKind::SemiFungible {
+            transaction.push(create_associated_token_account_idempotent(
+                &forger,
+                &forger,
+                &pda::output_mint(&recipe_key, index as u8).0,
+                &anchor_spl::token::ID,
+            ));
+        }
+    }
+    transaction.push(
+        instructions::ForgeAssetV2::new(
+            recipe_key,
+            &recipe,
+            forger,
+            mint.pubkey(),
+            input_hash,
+            nonce,
+        )
+        .remaining_accounts(ingredient_metas)
+        .remaining_accounts(output_metas)
+        .instruction(),
+    );
+
+    println!("Input hash: {} (nonce {nonce})", hex::encode(input_hash));
+    runner.submit(
+        &[
+            ("forge_config", forge_config),
+            ("recipe", recipe_key),
+            ("recipe_use", pda::recipe_use(&recipe_key, &input_hash).0),
+            ("mint", mint.pubkey()),
+            ("forged_asset", pda::forged_asset(&mint.pubkey()).0),
+        ],
+        transaction,
+        &[&mint],
+    )
+}
+
+/// Current state of the accounts in `metas`, as the program would see them
+/// if the forge landed now.
+fn snapshot(runner: &Runner, metas: &[AccountMeta]) -> anyhow::Result<AccountSnapshot> {
+    let keys: Vec<Pubkey> = metas.iter().map(|meta| meta.pubkey).collect();
+    let fetched = runner.rpc.get_multiple_accounts(&keys)?;
+    let accounts = metas
+        .iter()
+        .zip(fetched)
+        .map(|(meta, account)| {
+            let account = account.unwrap_or_default();
+            SnapshotAccount {
+                key: meta.pubkey,
+                owner: if account.lamports == 0 {
+                    system_program::ID
+                } else {
+                    account.owner
+                },
+                lamports: account.lamports,
+                data: account.data,
+                is_signer: meta.is_signer,
+            }
+        })
+        .collect();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    Ok(AccountSnapshot { now, accounts })
+}
+
+fn inspect(runner: &Runner, address: &Pubkey) -> anyhow::Result<()> {
+    let account = runner.rpc.get_account(address)?;
+    if account.owner != forge_client::forge::ID {
+        bail!(
+            "{address} is owned by {}, not the Forge program",
+            account.owner
+        );
+    }
+    println!("{address} ({} lamports)", account.lamports);
+    let data = &account.data;
+    let discriminator = data.get(..8).unwrap_or_default();
+    if discriminator == ForgeConfig::DISCRIMINATOR {
+        print_account::<ForgeConfig>(address, data)
+    } else if discriminator == Recipe::DISCRIMINATOR {
+        print_account::<Recipe>(address, data)
+    } else if discriminator == ClosedRecipe::DISCRIMINATOR {
+        print_account::<ClosedRecipe>(address, data)
+    } else if discriminator == RecipeUse::DISCRIMINATOR {
+        print_account::<RecipeUse>(address, data)
+    } else if discriminator == ForgedAsset::DISCRIMINATOR {
+        print_account::<ForgedAsset>(address, data)
+    } else if discriminator == ForgeCommit::DISCRIMINATOR {
+        print_account::<ForgeCommit>(address, data)
+    } else if discriminator == ForgeEscrow::DISCRIMINATOR {
+        print_account::<ForgeEscrow>(address, data)
+    } else {
+        bail!("{address} is not a known Forge account")
+    }
+}
+
+fn print_account<T: AccountDeserialize + Debug>(
+    address: &Pubkey,
+    data: &[u8],
+) -> anyhow::Result<()> {
+    let account: T = accounts::decode(address, data)?;
+    println!("{account:#?}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn parses_commands() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from([
+            "forge-cli",
+            "--dry-run",
+            "config",
+            "set",
+            "--collection",
+            "none",
+            "--royalty-bps",
+            "250",
+        ])
+        .unwrap();
+        assert!(cli.dry_run);
+        let Command::Config(ConfigCommand::Set(args)) = cli.command else {
+            panic!("expected config set");
+        };
+        assert_eq!(args.collection.map(|value| value.0), Some(None));
+        assert_eq!(args.royalty_bps, Some(250));
+        assert_eq!(args.freeze_authority.map(|value| value.0), None);
+    }
+}
@@ -0,0 +1,983 @@
+//! Recipe manifests.
+//!
+//! A manifest declares one recipe version in TOML or JSON (picked by the
+//! file extension). Every manifest starts with the `schema_version` it was
+//! written against so the format can evolve; this tool reads
+//! [`SCHEMA_VERSION`]. Addresses are base58 strings and byte strings are hex:
+//!
+//! ```toml
+//! schema_version = 1
+//! slug = "sword"
+//! version = 1
+//!
+//! [output]
+//! kind = "one-of-one"
+//! metadata_uri = "https://example.com/sword.json"
+//!
+//! [[ingredients]]
+//! type = "token-mint"
+//! mint = "So11111111111111111111111111111111111111112"
+//! amount = 3
+//!
+//! [schedule]
+//! go_live = 1767225600
+//! ```
+//!
+//! [`RecipeManifest::validate`], [`RecipeManifest::create_args`] and
+//! [`RecipeManifest::update_args`] check the manifest with the program's own
+//! validators and the account size limit, and report every problem found
+//! rather than stopping at the first.
+
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use anchor_lang::{prelude::Pubkey, solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE};
+use anyhow::{bail, Context};
+use forge_client::{
+    forge::{
+        errors::ForgeError,
+        instructions::{CreateRecipeArgs, UpdateRecipeArgs},
+        state::{
+            constants::{
+                MAX_EXTRA_OUTPUTS, MAX_INGREDIENTS, MAX_INGREDIENT_NODES, MAX_RECIPE_SLUG_LENGTH,
+                MAX_TRAIT_TIERS,
+            },
+            CreatorShare, IngredientConstraint, OutputKind, Recipe, RecipeMode, RecipeOutput,
+            RecipeStatus, ReplayPolicy, TraitTier,
+        },
+    },
+    pda,
+};
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+/// Manifest schema version this tool reads.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// One recipe version as declared in a manifest.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeManifest {
+    /// Checked against [`SCHEMA_VERSION`] before the rest is parsed.
+    #[allow(dead_code)]
+    pub schema_version: u32,
+    pub slug: String,
+    pub version: u16,
+    /// Version of the same slug this recipe supersedes.
+    #[serde(default)]
+    pub previous_version: Option<u16>,
+    /// Status the recipe is created in.
+    #[serde(default)]
+    pub status: StatusManifest,
+    #[serde(default)]
+    pub mode: ModeManifest,
+    #[serde(default)]
+    pub reversible: bool,
+    #[serde(default)]
+    pub replay_policy: ReplayPolicyManifest,
+    pub output: OutputManifest,
+    #[serde(default)]
+    pub creators: Vec<CreatorManifest>,
+    #[serde(default)]
+    pub ingredients: Vec<IngredientManifest>,
+    #[serde(default)]
+    pub schedule: ScheduleManifest,
+}
+
+/// What a forge mints.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputManifest {
+    pub kind: OutputKindManifest,
+    /// Master edition printed from; only for `edition` outputs.
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub parent_mint: Option<Pubkey>,
+    /// Bubblegum tree minted into; only for `compressed` outputs.
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub merkle_tree: Option<Pubkey>,
+    pub metadata_uri: String,
+    #[serde(default)]
+    pub supply_cap: Option<u64>,
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub collection_mint: Option<Pubkey>,
+    /// Weighted rarity tiers the primary output rolls into.
+    #[serde(default)]
+    pub traits: Vec<TraitManifest>,
+    /// Outputs minted alongside the primary one.
+    #[serde(default)]
+    pub extra: Vec<ExtraOutputManifest>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputKindManifest {
+    OneOfOne,
+    Edition,
+    SemiFungible,
+    Compressed,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreatorManifest {
+    #[serde(deserialize_with = "pubkey")]
+    pub address: Pubkey,
+    pub share: u8,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum IngredientManifest {
+    TokenMint {
+        #[serde(deserialize_with = "pubkey")]
+        mint: Pubkey,
+        amount: u64,
+    },
+    CollectionNft {
+        #[serde(deserialize_with = "pubkey")]
+        collection_mint: Pubkey,
+    },
+    Allowlist {
+        #[serde(deserialize_with = "hash")]
+        merkle_root: [u8; 32],
+    },
+    Signer {
+        #[serde(deserialize_with = "pubkey")]
+        authority: Pubkey,
+    },
+    CustomSeeds {
+        #[serde(deserialize_with = "pubkey")]
+        program_id: Pubkey,
+        #[serde(default, deserialize_with = "bytes")]
+        seeds: Vec<u8>,
+        #[serde(default)]
+        include_forger: bool,
+    },
+    AllOf {
+        constraints: Vec<IngredientManifest>,
+    },
+    AnyOf {
+        constraints: Vec<IngredientManifest>,
+    },
+    AtLeast {
+        threshold: u8,
+        constraints: Vec<IngredientManifest>,
+    },
+    ForgedOutput {
+        #[serde(deserialize_with = "pubkey")]
+        recipe: Pubkey,
+        #[serde(default)]
+        burn: bool,
+    },
+    SignedAuthorization {
+        #[serde(deserialize_with = "pubkey")]
+        authority: Pubkey,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraitManifest {
+    pub name: String,
+    pub weight: u16,
+    pub metadata_uri: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtraOutputManifest {
+    pub kind: ExtraOutputKind,
+    pub metadata_uri: String,
+    #[serde(default = "one")]
+    pub quantity: u64,
+    #[serde(default)]
+    pub supply_cap: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExtraOutputKind {
+    OneOfOne,
+    SemiFungible,
+}
+
+/// Unix timestamps (seconds) of the recipe's scheduled transitions.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleManifest {
+    #[serde(default)]
+    pub go_live: Option<i64>,
+    #[serde(default)]
+    pub pause_at: Option<i64>,
+    #[serde(default)]
+    pub retire_at: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum ModeManifest {
+    #[default]
+    Mint,
+    Evolve {
+        #[serde(default, deserialize_with = "optional_pubkey")]
+        source_recipe: Option<Pubkey>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplayPolicyManifest {
+    OncePerWallet,
+    OncePerInputSet,
+    #[default]
+    Unlimited,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatusManifest {
+    #[default]
+    Draft,
+    Active,
+    Paused,
+    Retired,
+}
+
+/// A problem with one manifest field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestError {
+    /// Dotted path of the field, e.g. `output.traits[1]`.
+    pub path: String,
+    pub message: String,
+}
+
+/// Every problem found in a manifest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ManifestErrors(pub Vec<ManifestError>);
+
+impl fmt::Display for ManifestErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} manifest error(s)", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  {}: {}", error.path, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ManifestErrors {}
+
+impl ManifestErrors {
+    pub fn fail(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ManifestError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Records the error of a program validator, if any.
+    fn check(&mut self, path: impl Into<String>, result: anchor_lang::Result<()>) {
+        if let Err(err) = result {
+            self.fail(path, error_message(err));
+        }
+    }
+
+    fn into_result<T>(self, value: T) -> Result<T, ManifestErrors> {
+        if self.0.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// The message of a program error, without Anchor's code and origin.
+fn error_message(err: anchor_lang::error::Error) -> String {
+    match err {
+        anchor_lang::error::Error::AnchorError(err) => err.error_msg,
+        err => err.to_string(),
+    }
+}
+
+/// Leading fields read before the rest of a manifest.
+#[derive(Deserialize)]
+struct Header {
+    schema_version: Option<u32>,
+}
+
+impl Header {
+    fn check(self) -> anyhow::Result<()> {
+        match self.schema_version {
+            Some(SCHEMA_VERSION) => Ok(()),
+            Some(version) => {
+                bail!("unsupported schema_version {version}; this tool reads {SCHEMA_VERSION}")
+            }
+            None => bail!("missing schema_version (this tool reads {SCHEMA_VERSION})"),
+        }
+    }
+}
+
+impl RecipeManifest {
+    /// Reads a manifest from a `.toml` or `.json` file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("json") => Self::from_json(&text),
+            _ => bail!("{}: expected a .toml or .json file", path.display()),
+        }
+        .with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn from_toml(text: &str) -> anyhow::Result<Self> {
+        toml::from_str::<Header>(text)?.check()?;
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn from_json(text: &str) -> anyhow::Result<Self> {
+        serde_json::from_str::<Header>(text)?.check()?;
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Validated `create_recipe` arguments of this recipe under
+    /// `forge_config`.
+    pub fn create_args(&self, forge_config: &Pubkey) -> Result<CreateRecipeArgs, ManifestErrors> {
+        let args = self.validate(forge_config)?;
+        let mut errors = ManifestErrors::default();
+        if !args.status.is_valid_initial() {
+            errors.fail("status", "recipes are created as draft or active");
+        }
+        errors.into_result(args)
+    }
+
+    /// Checks every field of this recipe under `forge_config`, returning the
+    /// recipe as `create_recipe` arguments with whatever status the manifest
+    /// asks for.
+    pub fn validate(&self, forge_config: &Pubkey) -> Result<CreateRecipeArgs, ManifestErrors> {
+        let mut errors = ManifestErrors::default();
+        let output = &self.output;
+
+        if self.slug.is_empty() {
+            errors.fail("slug", "must not be empty");
+        }
+        errors.check("slug", Recipe::validate_lengths(&self.slug, "", &[]));
+        errors.check(
+            "output.metadata_uri",
+            Recipe::validate_lengths("", &output.metadata_uri, &[]),
+        );
+
+        let output_kind = match (output.kind, output.parent_mint) {
+            (OutputKindManifest::Edition, Some(parent_mint)) => OutputKind::Edition { parent_mint },
+            (OutputKindManifest::Edition, None) => {
+                errors.fail("output.parent_mint", "required for edition outputs");
+                OutputKind::Edition {
+                    parent_mint: Pubkey::default(),
+                }
+            }
+            (_, Some(_)) => {
+                errors.fail("output.parent_mint", "only edition outputs have a parent");
+                OutputKind::OneOfOne
+            }
+            (OutputKindManifest::OneOfOne, None) => OutputKind::OneOfOne,
+            (OutputKindManifest::SemiFungible, None) => OutputKind::SemiFungible,
+            (OutputKindManifest::Compressed, None) => OutputKind::Compressed {
+                merkle_tree: output.merkle_tree.unwrap_or_else(|| {
+                    errors.fail("output.merkle_tree", "required for compressed outputs");
+                    Pubkey::default()
+                }),
+            },
+        };
+        if output.merkle_tree.is_some() && output.kind != OutputKindManifest::Compressed {
+            errors.fail(
+                "output.merkle_tree",
+                "only compressed outputs have a merkle tree",
+            );
+        }
+        if output.supply_cap == Some(0) {
+            errors.fail("output.supply_cap", "must be greater than zero");
+        }
+
+        let creators: Vec<CreatorShare> = self.creators.iter().map(Into::into).collect();
+        errors.check("creators", Recipe::validate_lengths("", "", &creators));
+        let shares: u16 = creators.iter().map(|c| u16::from(c.share)).sum();
+        if !creators.is_empty() && shares != 100 {
+            errors.check("creators", Err(ForgeError::InvalidCreatorShares.into()));
+        }
+
+        let ingredients: Vec<IngredientConstraint> =
+            self.ingredients.iter().map(Into::into).collect();
+        if ingredients.len() > MAX_INGREDIENTS {
+            errors.check("ingredients", Err(ForgeError::TooManyIngredients.into()));
+        }
+        let mut nodes = 0;
+        for (index, constraint) in ingredients.iter().enumerate() {
+            match constraint.validate(1) {
+                Ok(subtree) => nodes += subtree,
+                Err(err) => errors.fail(format!("ingredients[{index}]"), error_message(err)),
+            }
+        }
+        if nodes > MAX_INGREDIENT_NODES {
+            errors.fail(
+                "ingredients",
+                format!("{nodes} constraints in total, more than {MAX_INGREDIENT_NODES}"),
+            );
+        }
+
+        let trait_table: Vec<TraitTier> = output.traits.iter().map(Into::into).collect();
+        if trait_table.len() > MAX_TRAIT_TIERS {
+            errors.check("output.traits", Err(ForgeError::InvalidTraitTable.into()));
+        }
+        for (index, tier) in trait_table.iter().enumerate() {
+            errors.check(
+                format!("output.traits[{index}]"),
+                Recipe::validate_trait_table(std::slice::from_ref(tier)),
+            );
+        }
+
+        let extra_outputs: Vec<RecipeOutput> = output.extra.iter().map(Into::into).collect();
+        if extra_outputs.len() > MAX_EXTRA_OUTPUTS {
+            errors.check("output.extra", Err(ForgeError::InvalidRecipeOutputs.into()));
+        }
+        for (index, extra) in extra_outputs.iter().enumerate() {
+            errors.check(
+                format!("output.extra[{index}]"),
+                Recipe::validate_extra_outputs(std::slice::from_ref(extra)),
+            );
+        }
+
+        let mode = self.mode.into();
+        let replay_policy = self.replay_policy.into();
+        errors.check(
+            "mode",
+            Recipe::validate_mode(&mode, &trait_table, &extra_outputs),
+        );
+        errors.check(
+            "reversible",
+            Recipe::validate_reversible(
+                self.reversible,
+                &mode,
+                &ingredients,
+                &trait_table,
+                &extra_outputs,
+            ),
+        );
+        errors.check(
+            "replay_policy",
+            Recipe::validate_replay_policy(replay_policy, &mode, &ingredients),
+        );
+        errors.check(
+            "schedule",
+            Recipe::validate_schedule(self.schedule.pause_at, self.schedule.retire_at),
+        );
+
+        let space = Recipe::space(
+            self.slug.len(),
+            &output_kind,
+            output.metadata_uri.len(),
+            &creators,
+            &ingredients,
+            &trait_table,
+            &extra_outputs,
+        );
+        if space > MAX_PERMITTED_DATA_INCREASE {
+            errors.fail(
+                "",
+                format!(
+                    "recipe account would take {space} bytes, more than {MAX_PERMITTED_DATA_INCREASE}"
+                ),
+            );
+        }
+
+        errors.into_result(CreateRecipeArgs {
+            slug: self.slug.clone(),
+            version: self.version,
+            output_kind,
+            supply_cap: output.supply_cap,
+            metadata_uri: output.metadata_uri.clone(),
+            creators,
+            collection_mint: output.collection_mint,
+            go_live_unix_time: self.schedule.go_live,
+            pause_at: self.schedule.pause_at,
+            retire_at: self.schedule.retire_at,
+            ingredient_constraints: ingredients,
+            trait_table,
+            extra_outputs,
+            mode,
+            reversible: self.reversible,
+            replay_policy,
+            status: self.status.into(),
+            // Over-long slugs are already reported and cannot be used as seeds.
+            previous_version: self
+                .previous_version
+                .filter(|_| self.slug.len() <= MAX_RECIPE_SLUG_LENGTH)
+                .map(|version| pda::recipe(forge_config, &self.slug, version).0),
+        })
+    }
+
+    /// Validated `update_recipe` arguments overriding exactly the fields of
+    /// `recipe` that differ from this manifest.
+    ///
+    /// The status is left to `set_recipe_status`. Changes the program would
+    /// reject, such as edits to frozen fields of a recipe that has minted,
+    /// are reported as errors.
+    pub fn update_args(&self, recipe: &Recipe) -> Result<UpdateRecipeArgs, ManifestErrors> {
+        let target = self.validate(&recipe.forge_config)?;
+        let mut errors = ManifestErrors::default();
+        if target.previous_version != recipe.previous_version {
+            errors.fail(
+                "previous_version",
+                "versions are only linked when a recipe is created",
+            );
+        }
+        let args = overrides(target, recipe);
+
+        if recipe.is_frozen() {
+            // Check each frozen field on its own so every change is reported.
+            let frozen = [
+                (
+                    "ingredients",
+                    UpdateRecipeArgs {
+                        ingredient_constraints: args.ingredient_constraints.clone(),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "output.kind",
+                    UpdateRecipeArgs {
+                        output_kind: args.output_kind,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "output.supply_cap",
+                    UpdateRecipeArgs {
+                        supply_cap: args.supply_cap,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "output.traits",
+                    UpdateRecipeArgs {
+                        trait_table: args.trait_table.clone(),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "output.extra",
+                    UpdateRecipeArgs {
+                        extra_outputs: args.extra_outputs.clone(),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "mode",
+                    UpdateRecipeArgs {
+                        mode: args.mode,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "reversible",
+                    UpdateRecipeArgs {
+                        reversible: args.reversible,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "replay_policy",
+                    UpdateRecipeArgs {
+                        replay_policy: args.replay_policy,
+                        ..Default::default()
+                    },
+                ),
+            ];
+            for (path, single) in frozen {
+                errors.check(path, single.ensure_frozen_fields_unchanged(recipe));
+            }
+        }
+        if args.resulting_space(recipe) > recipe.current_space() + MAX_PERMITTED_DATA_INCREASE {
+            errors.fail("", "the update grows the recipe account too much at once");
+        }
+        errors.into_result(args)
+    }
+}
+
+/// `update_recipe` arguments overriding the fields of `recipe` that differ
+/// from `target`.
+pub fn overrides(target: CreateRecipeArgs, recipe: &Recipe) -> UpdateRecipeArgs {
+    fn changed<T: PartialEq>(target: T, current: &T) -> Option<T> {
+        (target != *current).then_some(target)
+    }
+    UpdateRecipeArgs {
+        metadata_uri: changed(target.metadata_uri, &recipe.metadata_uri),
+        creators: changed(target.creators, &recipe.creators),
+        collection_mint: changed(target.collection_mint, &recipe.collection_mint),
+        go_live_unix_time: changed(target.go_live_unix_time, &recipe.go_live_unix_time),
+        ingredient_constraints: changed(
+            target.ingredient_constraints,
+            &recipe.ingredient_constraints,
+        ),
+        supply_cap: changed(target.supply_cap, &recipe.supply_cap),
+        output_kind: changed(target.output_kind, &recipe.output_kind),
+        pause_at: changed(target.pause_at, &recipe.pause_at),
+        retire_at: changed(target.retire_at, &recipe.retire_at),
+        trait_table: changed(target.trait_table, &recipe.trait_table),
+        // Mint counters are not part of the manifest.
+        extra_outputs: (!same_outputs(&target.extra_outputs, &recipe.extra_outputs))
+            .then_some(target.extra_outputs),
+        mode: changed(target.mode, &recipe.mode),
+        reversible: changed(target.reversible, &recipe.reversible),
+        replay_policy: changed(target.replay_policy, &recipe.replay_policy),
+    }
+}
+
+/// Whether two output lists match, ignoring their mint counters.
+fn same_outputs(target: &[RecipeOutput], current: &[RecipeOutput]) -> bool {
+    target.len() == current.len()
+        && target.iter().zip(current).all(|(target, current)| {
+            target.output_kind == current.output_kind
+                && target.metadata_uri == current.metadata_uri
+                && target.quantity == current.quantity
+                && target.supply_cap == current.supply_cap
+        })
+}
+
+impl From<&CreatorManifest> for CreatorShare {
+    fn from(creator: &CreatorManifest) -> Self {
+        Self {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+impl From<&IngredientManifest> for IngredientConstraint {
+    fn from(ingredient: &IngredientManifest) -> Self {
+        let list = |constraints: &[IngredientManifest]| {
+            constraints
+                .iter()
+                .map(Into::into)
+                .collect::<Vec<_>>()
+                .into()
+        };
+        match ingredient {
+            IngredientManifest::TokenMint { mint, amount } => Self::TokenMint {
+                mint: *mint,
+                amount: *amount,
+            },
+            IngredientManifest::CollectionNft { collection_mint } => Self::CollectionNft {
+                collection_mint: *collection_mint,
+            },
+            IngredientManifest::Allowlist { merkle_root } => Self::Allowlist {
+                merkle_root: *merkle_root,
+            },
+            IngredientManifest::Signer { authority } => Self::Signer {
+                authority: *authority,
+            },
+            IngredientManifest::CustomSeeds {
+                program_id,
+                seeds,
+                include_forger,
+            } => Self::CustomSeeds {
+                program_id: *program_id,
+                seeds: seeds.clone(),
+                include_forger: *include_forger,
+            },
+            IngredientManifest::AllOf { constraints } => Self::AllOf {
+                constraints: list(constraints),
+            },
+            IngredientManifest::AnyOf { constraints } => Self::AnyOf {
+                constraints: list(constraints),
+            },
+            IngredientManifest::AtLeast {
+                threshold,
+                constraints,
+            } => Self::AtLeast {
+                threshold: *threshold,
+                constraints: list(constraints),
+            },
+            IngredientManifest::ForgedOutput { recipe, burn } => Self::ForgedOutput {
+                recipe: *recipe,
+                burn: *burn,
+            },
+            IngredientManifest::SignedAuthorization { authority } => Self::SignedAuthorization {
+                authority: *authority,
+            },
+        }
+    }
+}
+
+impl From<&TraitManifest> for TraitTier {
+    fn from(tier: &TraitManifest) -> Self {
+        Self {
+            name: tier.name.clone(),
+            weight: tier.weight,
+            metadata_uri: tier.metadata_uri.clone(),
+        }
+    }
+}
+
+impl From<&ExtraOutputManifest> for RecipeOutput {
+    fn from(output: &ExtraOutputManifest) -> Self {
+        Self {
+            output_kind: match output.kind {
+                ExtraOutputKind::OneOfOne => OutputKind::OneOfOne,
+                ExtraOutputKind::SemiFungible => OutputKind::SemiFungible,
+            },
+            metadata_uri: output.metadata_uri.clone(),
+            quantity: output.quantity,
+            supply_cap: output.supply_cap,
+            minted: 0,
+        }
+    }
+}
+
+impl From<ModeManifest> for RecipeMode {
+    fn from(mode: ModeManifest) -> Self {
+        match mode {
+            ModeManifest::Mint => Self::Mint,
+            ModeManifest::Evolve { source_recipe } => Self::Evolve { source_recipe },
+        }
+    }
+}
+
+impl From<ReplayPolicyManifest> for ReplayPolicy {
+    fn from(policy: ReplayPolicyManifest) -> Self {
+        match policy {
+            ReplayPolicyManifest::OncePerWallet => Self::OncePerWallet,
+            ReplayPolicyManifest::OncePerInputSet => Self::OncePerInputSet,
+            ReplayPolicyManifest::Unlimited => Self::Unlimited,
+        }
+    }
+}
+
+impl From<StatusManifest> for RecipeStatus {
+    fn from(status: StatusManifest) -> Self {
+        match status {
+            StatusManifest::Draft => Self::Draft,
+            StatusManifest::Active => Self::Active,
+            StatusManifest::Paused => Self::Paused,
+            StatusManifest::Retired => Self::Retired,
+        }
+    }
+}
+
+fn one() -> u64 {
+    1
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    Pubkey::from_str(&text).map_err(|err| D::Error::custom(format!("{text}: {err}")))
+}
+
+fn optional_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+    pubkey(deserializer).map(Some)
+}
+
+fn bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    hex::decode(&text).map_err(|err| D::Error::custom(format!("{text}: {err}")))
+}
+
+fn hash<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+    bytes(deserializer)?
+        .try_into()
+        .map_err(|_| D::Error::custom("expected 32 hex-encoded bytes"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const SWORD: &str = include_str!("../examples/sword.toml");
+
+    fn paths(errors: &ManifestErrors) -> Vec<&str> {
+        errors.0.iter().map(|error| error.path.as_str()).collect()
+    }
+
+    pub(crate) fn recipe_from(args: CreateRecipeArgs, forge_config: Pubkey) -> Recipe {
+        Recipe {
+            forge_config,
+            slug: args.slug,
+            version: args.version,
+            output_kind: args.output_kind,
+            supply_cap: args.supply_cap,
+            minted: 0,
+            metadata_uri: args.metadata_uri,
+            creators: args.creators,
+            collection_mint: args.collection_mint,
+            go_live_unix_time: args.go_live_unix_time,
+            pause_at: args.pause_at,
+            retire_at: args.retire_at,
+            ingredient_constraints: args.ingredient_constraints,
+            trait_table: args.trait_table,
+            extra_outputs: args.extra_outputs,
+            mode: args.mode,
+            reversible: args.reversible,
+            unforged: 0,
+            replay_policy: args.replay_policy,
+            status: args.status,
+            previous_version: args.previous_version,
+            next_version: None,
+            bump: 255,
+            open_uses: 0,
+            _reserved: [0; 3],
+        }
+    }
+
+    #[test]
+    fn parses_toml_and_json_alike() {
+        let toml = RecipeManifest::from_toml(SWORD).unwrap();
+        let json = RecipeManifest::from_json(
+            &serde_json::json!({
+                "schema_version": 1,
+                "slug": "sword",
+                "version": 2,
+                "previous_version": 1,
+                "replay_policy": "once-per-input-set",
+                "output": {
+                    "kind": "one-of-one",
+                    "metadata_uri": "https://example.com/sword.json",
+                    "supply_cap": 500
+                },
+                "ingredients": [
+                    { "type": "token-mint", "mint": "So11111111111111111111111111111111111111112", "amount": 3 },
+                    {
+                        "type": "any-of",
+                        "constraints": [
+                            { "type": "allowlist", "merkle_root": "11".repeat(32) },
+                            { "type": "custom-seeds", "program_id": "11111111111111111111111111111111", "seeds": "70726f66696c65", "include_forger": true }
+                        ]
+                    }
+                ],
+                "schedule": { "go_live": 1767225600 }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let forge_config = Pubkey::new_unique();
+        let args = toml.create_args(&forge_config).unwrap();
+        assert_eq!(
+            format!("{args:?}"),
+            format!("{:?}", json.create_args(&forge_config).unwrap())
+        );
+        assert_eq!(
+            args.previous_version,
+            Some(pda::recipe(&forge_config, "sword", 1).0)
+        );
+        assert_eq!(
+            args.ingredient_constraints[1].children().unwrap()[1],
+            IngredientConstraint::CustomSeeds {
+                program_id: Pubkey::default(),
+                seeds: b"profile".to_vec(),
+                include_forger: true,
+            }
+        );
+    }
+
+    #[test]
+    fn checks_schema_version_and_fields() {
+        let unversioned = SWORD.replace("schema_version = 1\n", "");
+        assert!(RecipeManifest::from_toml(&unversioned)
+            .unwrap_err()
+            .to_string()
+            .contains("missing schema_version"));
+        let future = SWORD.replace("schema_version = 1", "schema_version = 2");
+        assert!(RecipeManifest::from_toml(&future)
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported schema_version 2"));
+        let misspelled = SWORD.replace("replay_policy", "replay_polcy");
+        assert!(RecipeManifest::from_toml(&misspelled)
+            .unwrap_err()
+            .to_string()
+            .contains("replay_polcy"));
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let mut manifest = RecipeManifest::from_toml(SWORD).unwrap();
+        manifest.slug = "s".repeat(33);
+        manifest.output.kind = OutputKindManifest::Edition;
+        manifest.output.supply_cap = Some(0);
+        manifest.output.traits = vec![
+            TraitManifest {
+                name: "common".into(),
+                weight: 9,
+                metadata_uri: "uri".into(),
+            },
+            TraitManifest {
+                name: "legendary".into(),
+                weight: 0,
+                metadata_uri: "uri".into(),
+            },
+        ];
+        manifest.reversible = true;
+        manifest.schedule.pause_at = Some(10);
+        manifest.schedule.retire_at = Some(5);
+
+        let errors = manifest.create_args(&Pubkey::new_unique()).unwrap_err();
+        assert_eq!(
+            paths(&errors),
+            vec![
+                "slug",
+                "output.parent_mint",
+                "output.supply_cap",
+                "output.traits[1]",
+                "reversible",
+                "schedule",
+            ]
+        );
+        assert_eq!(errors.0[0].message, "Recipe slug exceeds maximum length.");
+    }
+
+    #[test]
+    fn compressed_outputs_need_a_merkle_tree() {
+        let mut manifest = RecipeManifest::from_toml(SWORD).unwrap();
+        manifest.output.kind = OutputKindManifest::Compressed;
+        let errors = manifest.create_args(&Pubkey::new_unique()).unwrap_err();
+        assert_eq!(paths(&errors), vec!["output.merkle_tree"]);
+
+        let merkle_tree = Pubkey::new_unique();
+        manifest.output.merkle_tree = Some(merkle_tree);
+        let args = manifest.create_args(&Pubkey::new_unique()).unwrap();
+        assert_eq!(args.output_kind, OutputKind::Compressed { merkle_tree });
+
+        manifest.output.kind = OutputKindManifest::OneOfOne;
+        let errors = manifest.create_args(&Pubkey::new_unique()).unwrap_err();
+        assert_eq!(paths(&errors), vec!["output.merkle_tree"]);
+    }
+
+    #[test]
+    fn diffs_against_the_recipe() {
+        let manifest = RecipeManifest::from_toml(SWORD).unwrap();
+        let forge_config = Pubkey::new_unique();
+        let mut recipe = recipe_from(manifest.create_args(&forge_config).unwrap(), forge_config);
+        let args = manifest.update_args(&recipe).unwrap();
+        assert_eq!(
+            format!("{args:?}"),
+            format!("{:?}", UpdateRecipeArgs::default())
+        );
+
+        let mut edited = manifest.clone();
+        edited.output.metadata_uri = "https://example.com/sword-v2.json".into();
+        edited.output.supply_cap = Some(1000);
+        edited.ingredients.pop();
+        let args = edited.update_args(&recipe).unwrap();
+        assert_eq!(
+            args.metadata_uri.as_deref(),
+            Some("https://example.com/sword-v2.json")
+        );
+        assert_eq!(args.supply_cap, Some(Some(1000)));
+        assert_eq!(args.ingredient_constraints.map(|c| c.len()), Some(1));
+        assert!(args.creators.is_none() && args.trait_table.is_none());
+
+        recipe.minted = 1;
+        let errors = edited.update_args(&recipe).unwrap_err();
+        assert_eq!(paths(&errors), vec!["ingredients", "output.supply_cap"]);
+    }
+}
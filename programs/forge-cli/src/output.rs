@@ -0,0 +1,35 @@
+//! Printing of derived addresses and `--dry-run` instructions.
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction};
+
+/// Prints named addresses as an aligned list.
+pub fn print_addresses(addresses: &[(&str, Pubkey)]) {
+    let width = addresses
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, address) in addresses {
+        println!("  {name:<width$}  {address}");
+    }
+}
+
+/// Prints an instruction's program, account metas and hex-encoded data.
+pub fn print_instruction(index: usize, instruction: &Instruction) {
+    println!("Instruction #{index} (program {})", instruction.program_id);
+    for (position, meta) in instruction.accounts.iter().enumerate() {
+        let mut flags = Vec::new();
+        if meta.is_signer {
+            flags.push("signer");
+        }
+        if meta.is_writable {
+            flags.push("writable");
+        }
+        println!("  {position:>2} {} {}", meta.pubkey, flags.join(" "));
+    }
+    println!(
+        "  data ({} bytes): {}",
+        instruction.data.len(),
+        hex::encode(&instruction.data)
+    );
+}
@@ -0,0 +1,649 @@
+//! Plans that bring a forge's recipes in line with a directory of manifests.
+//!
+//! Like `terraform plan`, [`plan`] compares every manifest with the recipe
+//! account it declares and picks the instructions that would reconcile
+//! them: `create_recipe` for new recipes, `fork_recipe_version` for a new
+//! version directly following one on chain, and `update_recipe` and
+//! `set_recipe_status` for existing ones. The plan prints field by field so
+//! recipe changes can be reviewed before they are applied.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Debug},
+    fs,
+    path::Path,
+};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{bail, Context};
+use forge_client::{
+    forge::{
+        instructions::{CreateRecipeArgs, ForkRecipeVersionArgs, UpdateRecipeArgs},
+        state::{OutputKind, Recipe, RecipeStatus},
+    },
+    pda,
+};
+
+use crate::manifest::{self, ManifestErrors, RecipeManifest};
+
+/// Loads every `.toml` and `.json` manifest in `dir`, in file name order.
+pub fn load_dir(dir: &Path) -> anyhow::Result<Vec<RecipeManifest>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("toml" | "json")
+        ) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut declared = BTreeMap::new();
+    let mut manifests = Vec::with_capacity(paths.len());
+    for path in paths {
+        let manifest = RecipeManifest::load(&path)?;
+        let key = (manifest.slug.clone(), manifest.version);
+        if let Some(other) = declared.insert(key, path.clone()) {
+            bail!(
+                "{}: {} v{} is also declared by {}",
+                path.display(),
+                manifest.slug,
+                manifest.version,
+                other.display()
+            );
+        }
+        manifests.push(manifest);
+    }
+    Ok(manifests)
+}
+
+/// What to send for one manifest.
+#[derive(Debug)]
+pub enum Action {
+    /// The recipe does not exist yet.
+    Create(CreateRecipeArgs),
+    /// The recipe is the next version of `previous_version`, which exists
+    /// and is retired by the fork.
+    Fork {
+        previous_version: u16,
+        args: ForkRecipeVersionArgs,
+    },
+    /// The recipe exists; either part may be empty, but not both.
+    Update {
+        args: Option<UpdateRecipeArgs>,
+        status: Option<RecipeStatus>,
+    },
+    /// The recipe already matches its manifest.
+    Unchanged,
+    /// The manifest cannot be applied as is.
+    Invalid(ManifestErrors),
+}
+
+/// A manifest field whose value changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Manifest path of the field, e.g. `output.metadata_uri`.
+    pub path: &'static str,
+    /// Current value, or `None` for a recipe that does not exist yet.
+    pub before: Option<String>,
+    pub after: String,
+}
+
+/// The plan for one manifest.
+#[derive(Debug)]
+pub struct Step {
+    pub slug: String,
+    pub version: u16,
+    /// Recipe address; `None` when the manifest is too broken to derive it.
+    pub recipe: Option<Pubkey>,
+    /// Output kind of the recipe once the step is applied.
+    pub output_kind: Option<OutputKind>,
+    pub action: Action,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Every step to bring a forge's recipes in line with its manifests.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+    /// Recipes on chain without a manifest, which the plan leaves alone.
+    pub unmanaged: Vec<(String, u16, Pubkey)>,
+}
+
+impl Plan {
+    /// Number of manifests that cannot be applied.
+    pub fn invalid(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step.action, Action::Invalid(_)))
+            .count()
+    }
+}
+
+/// Plans the steps that bring `recipes`, the recipe accounts of
+/// `forge_config`, in line with `manifests`.
+///
+/// Steps are ordered by slug and version so that a version is in place
+/// before the versions that follow it.
+pub fn plan(
+    forge_config: &Pubkey,
+    manifests: &[RecipeManifest],
+    recipes: &[(Pubkey, Recipe)],
+) -> Plan {
+    let on_chain: BTreeMap<(&str, u16), (&Pubkey, &Recipe)> = recipes
+        .iter()
+        .map(|(key, recipe)| ((recipe.slug.as_str(), recipe.version), (key, recipe)))
+        .collect();
+    let declared: BTreeSet<(&str, u16)> = manifests
+        .iter()
+        .map(|manifest| (manifest.slug.as_str(), manifest.version))
+        .collect();
+
+    let mut sorted: Vec<&RecipeManifest> = manifests.iter().collect();
+    sorted.sort_by(|a, b| (&a.slug, a.version).cmp(&(&b.slug, b.version)));
+    let steps = sorted
+        .into_iter()
+        .map(|manifest| {
+            let previous = manifest
+                .previous_version
+                .and_then(|version| on_chain.get(&(manifest.slug.as_str(), version)))
+                .map(|(_, recipe)| *recipe);
+            let planned_previous = manifest
+                .previous_version
+                .is_some_and(|version| declared.contains(&(manifest.slug.as_str(), version)));
+            let current = on_chain
+                .get(&(manifest.slug.as_str(), manifest.version))
+                .map(|(_, recipe)| *recipe);
+            plan_step(forge_config, manifest, current, previous, planned_previous)
+        })
+        .collect();
+
+    let unmanaged = on_chain
+        .iter()
+        .filter(|(key, _)| !declared.contains(key))
+        .map(|((slug, version), (key, _))| (slug.to_string(), *version, **key))
+        .collect();
+    Plan { steps, unmanaged }
+}
+
+fn plan_step(
+    forge_config: &Pubkey,
+    manifest: &RecipeManifest,
+    current: Option<&Recipe>,
+    previous: Option<&Recipe>,
+    planned_previous: bool,
+) -> Step {
+    let mut step = Step {
+        slug: manifest.slug.clone(),
+        version: manifest.version,
+        recipe: None,
+        output_kind: None,
+        action: Action::Unchanged,
+        changes: Vec::new(),
+    };
+    let target = match manifest.validate(forge_config) {
+        Ok(target) => target,
+        Err(errors) => {
+            step.action = Action::Invalid(errors);
+            return step;
+        }
+    };
+    step.recipe = Some(pda::recipe(forge_config, &manifest.slug, manifest.version).0);
+    step.output_kind = Some(target.output_kind);
+
+    let (action, changes) = match (current, previous) {
+        (Some(recipe), _) => plan_update(manifest, recipe, target.status),
+        (None, Some(previous)) if manifest.previous_version == manifest.version.checked_sub(1) => {
+            plan_fork(forge_config, manifest, previous, target)
+        }
+        (None, previous) => plan_create(forge_config, manifest, previous, planned_previous),
+    };
+    step.action = action;
+    step.changes = changes;
+    step
+}
+
+fn plan_update(
+    manifest: &RecipeManifest,
+    recipe: &Recipe,
+    status: RecipeStatus,
+) -> (Action, Vec<FieldChange>) {
+    let args = match manifest.update_args(recipe) {
+        Ok(args) => args,
+        Err(mut errors) => {
+            if recipe.is_frozen() {
+                errors.fail(
+                    "",
+                    format!(
+                        "v{} has minted; declare the change as v{} instead",
+                        recipe.version,
+                        recipe.version.saturating_add(1)
+                    ),
+                );
+            }
+            return (Action::Invalid(errors), Vec::new());
+        }
+    };
+    let mut changes = field_changes(&args, recipe);
+    let args = (!changes.is_empty()).then_some(args);
+    let status = (status != recipe.status).then_some(status);
+    if let Some(status) = status {
+        if !recipe.status.can_transition_to(status) {
+            let mut errors = ManifestErrors::default();
+            errors.fail(
+                "status",
+                format!("cannot move from {:?} to {status:?}", recipe.status),
+            );
+            return (Action::Invalid(errors), Vec::new());
+        }
+        changes.push(change("status", Some(&recipe.status), &status));
+    }
+    let action = if args.is_none() && status.is_none() {
+        Action::Unchanged
+    } else {
+        Action::Update { args, status }
+    };
+    (action, changes)
+}
+
+fn plan_fork(
+    forge_config: &Pubkey,
+    manifest: &RecipeManifest,
+    previous: &Recipe,
+    target: CreateRecipeArgs,
+) -> (Action, Vec<FieldChange>) {
+    let mut errors = match manifest.create_args(forge_config) {
+        Ok(_) => ManifestErrors::default(),
+        Err(errors) => errors,
+    };
+    if previous.next_version.is_some() {
+        errors.fail(
+            "previous_version",
+            format!("v{} already has a successor", previous.version),
+        );
+    }
+    if !errors.0.is_empty() {
+        return (Action::Invalid(errors), Vec::new());
+    }
+
+    // Forks do not inherit the previous version's schedule.
+    let base = Recipe {
+        pause_at: None,
+        retire_at: None,
+        ..previous.clone()
+    };
+    let status = target.status;
+    let overrides = manifest::overrides(target, &base);
+    let mut changes = field_changes(&overrides, &base);
+    changes.push(change("status", None, &status));
+    let action = Action::Fork {
+        previous_version: previous.version,
+        args: ForkRecipeVersionArgs { overrides, status },
+    };
+    (action, changes)
+}
+
+fn plan_create(
+    forge_config: &Pubkey,
+    manifest: &RecipeManifest,
+    previous: Option<&Recipe>,
+    planned_previous: bool,
+) -> (Action, Vec<FieldChange>) {
+    let mut errors = ManifestErrors::default();
+    let args = match manifest.create_args(forge_config) {
+        Ok(args) => Some(args),
+        Err(create_errors) => {
+            errors = create_errors;
+            None
+        }
+    };
+    if let Some(version) = manifest.previous_version {
+        if previous.is_none() && !planned_previous {
+            errors.fail(
+                "previous_version",
+                format!("v{version} neither exists nor has a manifest"),
+            );
+        }
+    }
+    match args {
+        Some(args) if errors.0.is_empty() => {
+            let changes = created_fields(&args);
+            (Action::Create(args), changes)
+        }
+        _ => (Action::Invalid(errors), Vec::new()),
+    }
+}
+
+fn change<T: Debug>(path: &'static str, before: Option<&T>, after: &T) -> FieldChange {
+    FieldChange {
+        path,
+        before: before.map(|value| format!("{value:?}")),
+        after: format!("{after:?}"),
+    }
+}
+
+/// The fields `args` sets on `recipe`, under their manifest paths.
+fn field_changes(args: &UpdateRecipeArgs, recipe: &Recipe) -> Vec<FieldChange> {
+    fn push<T: Debug>(
+        changes: &mut Vec<FieldChange>,
+        path: &'static str,
+        before: &T,
+        after: Option<&T>,
+    ) {
+        if let Some(after) = after {
+            changes.push(change(path, Some(before), after));
+        }
+    }
+
+    let mut changes = Vec::new();
+    push(
+        &mut changes,
+        "output.kind",
+        &recipe.output_kind,
+        args.output_kind.as_ref(),
+    );
+    push(
+        &mut changes,
+        "output.metadata_uri",
+        &recipe.metadata_uri,
+        args.metadata_uri.as_ref(),
+    );
+    push(
+        &mut changes,
+        "output.supply_cap",
+        &recipe.supply_cap,
+        args.supply_cap.as_ref(),
+    );
+    push(
+        &mut changes,
+        "output.collection_mint",
+        &recipe.collection_mint,
+        args.collection_mint.as_ref(),
+    );
+    push(
+        &mut changes,
+        "output.traits",
+        &recipe.trait_table,
+        args.trait_table.as_ref(),
+    );
+    push(
+        &mut changes,
+        "output.extra",
+        &recipe.extra_outputs,
+        args.extra_outputs.as_ref(),
+    );
+    push(&mut changes, "mode", &recipe.mode, args.mode.as_ref());
+    push(
+        &mut changes,
+        "reversible",
+        &recipe.reversible,
+        args.reversible.as_ref(),
+    );
+    push(
+        &mut changes,
+        "replay_policy",
+        &recipe.replay_policy,
+        args.replay_policy.as_ref(),
+    );
+    push(
+        &mut changes,
+        "creators",
+        &recipe.creators,
+        args.creators.as_ref(),
+    );
+    push(
+        &mut changes,
+        "ingredients",
+        &recipe.ingredient_constraints,
+        args.ingredient_constraints.as_ref(),
+    );
+    push(
+        &mut changes,
+        "schedule.go_live",
+        &recipe.go_live_unix_time,
+        args.go_live_unix_time.as_ref(),
+    );
+    push(
+        &mut changes,
+        "schedule.pause_at",
+        &recipe.pause_at,
+        args.pause_at.as_ref(),
+    );
+    push(
+        &mut changes,
+        "schedule.retire_at",
+        &recipe.retire_at,
+        args.retire_at.as_ref(),
+    );
+    changes
+}
+
+/// Every field of a recipe created from `args`, under its manifest path.
+fn created_fields(args: &CreateRecipeArgs) -> Vec<FieldChange> {
+    vec![
+        change("output.kind", None, &args.output_kind),
+        change("output.metadata_uri", None, &args.metadata_uri),
+        change("output.supply_cap", None, &args.supply_cap),
+        change("output.collection_mint", None, &args.collection_mint),
+        change("output.traits", None, &args.trait_table),
+        change("output.extra", None, &args.extra_outputs),
+        change("mode", None, &args.mode),
+        change("reversible", None, &args.reversible),
+        change("replay_policy", None, &args.replay_policy),
+        change("creators", None, &args.creators),
+        change("ingredients", None, &args.ingredient_constraints),
+        change("schedule.go_live", None, &args.go_live_unix_time),
+        change("schedule.pause_at", None, &args.pause_at),
+        change("schedule.retire_at", None, &args.retire_at),
+        change("previous_version", None, &args.previous_version),
+        change("status", None, &args.status),
+    ]
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mut create, mut fork, mut update, mut unchanged) = (0, 0, 0, 0);
+        for step in &self.steps {
+            let name = format!("{} v{}", step.slug, step.version);
+            let address = step
+                .recipe
+                .map(|key| format!("  {key}"))
+                .unwrap_or_default();
+            match &step.action {
+                Action::Create(_) => {
+                    create += 1;
+                    writeln!(f, "+ {name} will be created{address}")?;
+                }
+                Action::Fork {
+                    previous_version, ..
+                } => {
+                    fork += 1;
+                    writeln!(
+                        f,
+                        "+ {name} will be forked from v{previous_version}, retiring it{address}"
+                    )?;
+                }
+                Action::Update { .. } => {
+                    update += 1;
+                    writeln!(f, "~ {name} will be updated{address}")?;
+                }
+                Action::Unchanged => {
+                    unchanged += 1;
+                    writeln!(f, "  {name} is up to date{address}")?;
+                }
+                Action::Invalid(errors) => {
+                    writeln!(f, "! {name} cannot be applied{address}")?;
+                    for error in &errors.0 {
+                        let path = if error.path.is_empty() {
+                            "(recipe)"
+                        } else {
+                            &error.path
+                        };
+                        writeln!(f, "    {path}: {}", error.message)?;
+                    }
+                }
+            }
+            for change in &step.changes {
+                match &change.before {
+                    Some(before) => {
+                        writeln!(f, "    {}: {before} -> {}", change.path, change.after)?
+                    }
+                    None => writeln!(f, "    {} = {}", change.path, change.after)?,
+                }
+            }
+        }
+        for (slug, version, key) in &self.unmanaged {
+            writeln!(f, "? {slug} v{version} has no manifest  {key}")?;
+        }
+        write!(
+            f,
+            "Plan: {create} to create, {fork} to fork, {update} to update, {unchanged} up to date, \
+             {} invalid, {} unmanaged.",
+            self.invalid(),
+            self.unmanaged.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::tests::recipe_from;
+
+    const SWORD: &str = include_str!("../examples/sword.toml");
+
+    fn manifest(slug: &str, version: u16, previous_version: Option<u16>) -> RecipeManifest {
+        let mut manifest = RecipeManifest::from_toml(SWORD).unwrap();
+        manifest.slug = slug.into();
+        manifest.version = version;
+        manifest.previous_version = previous_version;
+        manifest
+    }
+
+    fn on_chain(forge_config: &Pubkey, manifest: &RecipeManifest) -> (Pubkey, Recipe) {
+        let args = manifest.create_args(forge_config).unwrap();
+        (
+            pda::recipe(forge_config, &manifest.slug, manifest.version).0,
+            recipe_from(args, *forge_config),
+        )
+    }
+
+    #[test]
+    fn plans_each_kind_of_step() {
+        let forge_config = Pubkey::new_unique();
+        let mut sword = manifest("sword", 1, None);
+        sword.schedule.pause_at = Some(10);
+        sword.schedule.retire_at = Some(20);
+        let shield = manifest("shield", 1, None);
+        let axe = manifest("axe", 1, None);
+        let recipes = vec![
+            on_chain(&forge_config, &sword),
+            on_chain(&forge_config, &shield),
+            on_chain(&forge_config, &axe),
+        ];
+
+        let mut activated = shield.clone();
+        activated.status = manifest::StatusManifest::Active;
+        activated.output.metadata_uri = "https://example.com/shield.json".into();
+        let mut sword_v2 = manifest("sword", 2, Some(1));
+        sword_v2.output.supply_cap = Some(1000);
+        sword_v2.schedule.retire_at = Some(20);
+        let bow = manifest("bow", 1, None);
+        let bow_v3 = manifest("bow", 3, Some(2));
+        let plan = plan(
+            &forge_config,
+            &[sword_v2, activated, sword, bow, bow_v3],
+            &recipes,
+        );
+
+        let summary: Vec<(&str, u16, &str)> = plan
+            .steps
+            .iter()
+            .map(|step| {
+                let action = match step.action {
+                    Action::Create(_) => "create",
+                    Action::Fork { .. } => "fork",
+                    Action::Update { .. } => "update",
+                    Action::Unchanged => "unchanged",
+                    Action::Invalid(_) => "invalid",
+                };
+                (step.slug.as_str(), step.version, action)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("bow", 1, "create"),
+                ("bow", 3, "invalid"),
+                ("shield", 1, "update"),
+                ("sword", 1, "unchanged"),
+                ("sword", 2, "fork"),
+            ]
+        );
+        assert_eq!(plan.unmanaged, vec![("axe".into(), 1, recipes[2].0)]);
+
+        let shield = &plan.steps[2];
+        assert_eq!(
+            shield.changes,
+            vec![
+                FieldChange {
+                    path: "output.metadata_uri",
+                    before: Some("\"https://example.com/sword.json\"".into()),
+                    after: "\"https://example.com/shield.json\"".into(),
+                },
+                FieldChange {
+                    path: "status",
+                    before: Some("Draft".into()),
+                    after: "Active".into(),
+                },
+            ]
+        );
+        let Action::Fork {
+            previous_version: 1,
+            args,
+        } = &plan.steps[4].action
+        else {
+            panic!("expected a fork of v1");
+        };
+        assert_eq!(args.overrides.supply_cap, Some(Some(1000)));
+        assert!(args.overrides.metadata_uri.is_none());
+        // The fork starts unscheduled, so a schedule it keeps is an override.
+        assert_eq!(
+            (args.overrides.pause_at, args.overrides.retire_at),
+            (None, Some(Some(20)))
+        );
+        assert_eq!(plan.invalid(), 1);
+        assert!(plan.to_string().ends_with(
+            "Plan: 1 to create, 1 to fork, 1 to update, 1 up to date, 1 invalid, 1 unmanaged."
+        ));
+    }
+
+    #[test]
+    fn rejects_what_the_program_would() {
+        let forge_config = Pubkey::new_unique();
+        let sword = manifest("sword", 1, None);
+        let mut minted = on_chain(&forge_config, &sword);
+        minted.1.minted = 1;
+        minted.1.status = RecipeStatus::Retired;
+
+        let mut edited = sword.clone();
+        edited.output.supply_cap = Some(1000);
+        edited.status = manifest::StatusManifest::Active;
+        let plan = plan(&forge_config, &[edited], &[minted.clone()]);
+        let Action::Invalid(errors) = &plan.steps[0].action else {
+            panic!("expected an invalid step");
+        };
+        assert_eq!(errors.0[0].path, "output.supply_cap");
+        assert!(errors.0[1].message.contains("declare the change as v2"));
+
+        let mut reactivated = sword;
+        reactivated.status = manifest::StatusManifest::Active;
+        let plan = plan_step(&forge_config, &reactivated, Some(&minted.1), None, false);
+        let Action::Invalid(errors) = &plan.action else {
+            panic!("expected an invalid step");
+        };
+        assert_eq!(errors.0[0].message, "cannot move from Retired to Active");
+    }
+}
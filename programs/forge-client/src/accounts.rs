@@ -0,0 +1,99 @@
+//! Fetching and decoding Forge program accounts.
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use forge::state::{ForgeCommit, ForgeConfig, ForgeEscrow, ForgedAsset, Recipe, RecipeUse};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    filter::{Memcmp, RpcFilterType},
+};
+
+use crate::{ClientError, Result};
+
+/// Offset of `Recipe::forge_config`, right after the discriminator.
+const RECIPE_FORGE_CONFIG_OFFSET: usize = 8;
+/// Offset of `ForgedAsset::recipe`: discriminator, then `forge_config`.
+const FORGED_ASSET_RECIPE_OFFSET: usize = 8 + 32;
+
+/// Decodes the raw data of the account `key` as `T`, checking its
+/// discriminator.
+pub fn decode<T: AccountDeserialize>(key: &Pubkey, data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..]).map_err(|err| ClientError::Decode(*key, Box::new(err)))
+}
+
+/// Fetches and decodes the account `key`, if it exists.
+pub fn fetch_optional<T: AccountDeserialize>(rpc: &RpcClient, key: &Pubkey) -> Result<Option<T>> {
+    let accounts = rpc.get_multiple_accounts(std::slice::from_ref(key))?;
+    accounts
+        .into_iter()
+        .next()
+        .flatten()
+        .map(|account| decode(key, &account.data))
+        .transpose()
+}
+
+/// Fetches and decodes the account `key`.
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, key: &Pubkey) -> Result<T> {
+    fetch_optional(rpc, key)?.ok_or(ClientError::AccountNotFound(*key))
+}
+
+pub fn fetch_forge_config(rpc: &RpcClient, key: &Pubkey) -> Result<ForgeConfig> {
+    fetch(rpc, key)
+}
+
+pub fn fetch_recipe(rpc: &RpcClient, key: &Pubkey) -> Result<Recipe> {
+    fetch(rpc, key)
+}
+
+pub fn fetch_recipe_use(rpc: &RpcClient, key: &Pubkey) -> Result<Option<RecipeUse>> {
+    fetch_optional(rpc, key)
+}
+
+pub fn fetch_forged_asset(rpc: &RpcClient, key: &Pubkey) -> Result<ForgedAsset> {
+    fetch(rpc, key)
+}
+
+pub fn fetch_forge_commit(rpc: &RpcClient, key: &Pubkey) -> Result<Option<ForgeCommit>> {
+    fetch_optional(rpc, key)
+}
+
+pub fn fetch_forge_escrow(rpc: &RpcClient, key: &Pubkey) -> Result<ForgeEscrow> {
+    fetch(rpc, key)
+}
+
+/// Fetches every recipe (all versions) of `forge_config`.
+pub fn fetch_recipes_of(rpc: &RpcClient, forge_config: &Pubkey) -> Result<Vec<(Pubkey, Recipe)>> {
+    fetch_program_accounts(rpc, RECIPE_FORGE_CONFIG_OFFSET, forge_config)
+}
+
+/// Fetches every `ForgedAsset` record minted by `recipe`.
+pub fn fetch_forged_assets_of(
+    rpc: &RpcClient,
+    recipe: &Pubkey,
+) -> Result<Vec<(Pubkey, ForgedAsset)>> {
+    fetch_program_accounts(rpc, FORGED_ASSET_RECIPE_OFFSET, recipe)
+}
+
+/// Fetches every `T` account whose key field at `offset` equals `key`.
+fn fetch_program_accounts<T: AccountDeserialize + Discriminator>(
+    rpc: &RpcClient,
+    offset: usize,
+    key: &Pubkey,
+) -> Result<Vec<(Pubkey, T)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, T::DISCRIMINATOR.to_vec())),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, key.to_bytes().to_vec())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    rpc.get_program_accounts_with_config(&forge::ID, config)?
+        .into_iter()
+        .map(|(key, account)| Ok((key, decode(&key, &account.data)?)))
+        .collect()
+}
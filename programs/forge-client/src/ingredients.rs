@@ -0,0 +1,385 @@
+//! Remaining accounts and input hashes of forges.
+//!
+//! The program scans remaining accounts for whatever each
+//! [`IngredientConstraint`] needs; [`ingredient_accounts`] lays them out for
+//! a recipe and [`output_accounts`] appends the extra output accounts
+//! `forge_asset` expects after them. Input hashes are computed by running the
+//! program's own verifier over an [`AccountSnapshot`] of those accounts.
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::sysvar,
+};
+use forge::{
+    cpi::ingredients::IngredientVerifier,
+    eligibility::AccountSnapshot,
+    state::{constants::HASH_BYTES, ForgedAsset, IngredientConstraint, OutputKind, Recipe},
+};
+
+use crate::{pda, ClientError, Result};
+
+/// Accounts the forger picks for ingredients that more than one account
+/// could satisfy.
+#[derive(Clone, Debug, Default)]
+pub struct IngredientChoices {
+    /// Leaf account followed by proof accounts of an `Allowlist` ingredient.
+    /// The program reads the leaf from the first remaining account, so these
+    /// are placed first.
+    pub allowlist_accounts: Vec<Pubkey>,
+    /// NFT mint presented for a `CollectionNft` ingredient, together with
+    /// the forger's associated token account and its metadata.
+    pub collection_nft: Option<Pubkey>,
+    /// Forged asset mints presented for `ForgedOutput` ingredients, together
+    /// with their `ForgedAsset` records and the forger's associated token
+    /// accounts.
+    pub forged_assets: Vec<Pubkey>,
+}
+
+/// Remaining accounts satisfying `recipe`'s ingredients for `forger`.
+///
+/// Token accounts are the forger's associated token accounts. Reversible
+/// recipes also get the vault accounts their tokens are escrowed into, and
+/// forged assets are writable when an ingredient burns them. Accounts are
+/// deduplicated.
+pub fn ingredient_accounts(
+    recipe: &Recipe,
+    forger: &Pubkey,
+    choices: &IngredientChoices,
+) -> Vec<AccountMeta> {
+    let mut metas = AccountMetas::default();
+    for key in &choices.allowlist_accounts {
+        metas.push(AccountMeta::new_readonly(*key, false));
+    }
+    if let Some(nft_mint) = choices.collection_nft {
+        metas.push(AccountMeta::new_readonly(nft_mint, false));
+        metas.push(AccountMeta::new_readonly(
+            pda::associated_token(forger, &nft_mint),
+            false,
+        ));
+        metas.push(AccountMeta::new_readonly(pda::metadata(&nft_mint).0, false));
+    }
+
+    let vault_authority = pda::vault_authority(&recipe.forge_config).0;
+    let mut burns = false;
+    let mut stack: Vec<&IngredientConstraint> =
+        recipe.ingredient_constraints.iter().rev().collect();
+    while let Some(constraint) = stack.pop() {
+        match constraint {
+            IngredientConstraint::Signer { authority } if authority != forger => {
+                metas.push(AccountMeta::new_readonly(*authority, true));
+            }
+            IngredientConstraint::CustomSeeds {
+                program_id,
+                seeds,
+                include_forger,
+            } => {
+                let mut seed_slices: Vec<&[u8]> = Vec::with_capacity(2);
+                if !seeds.is_empty() {
+                    seed_slices.push(seeds);
+                }
+                if *include_forger {
+                    seed_slices.push(forger.as_ref());
+                }
+                let (seeds_pda, _) = Pubkey::find_program_address(&seed_slices, program_id);
+                metas.push(AccountMeta::new_readonly(seeds_pda, false));
+            }
+            IngredientConstraint::TokenMint { mint, .. } => {
+                let token_account = pda::associated_token(forger, mint);
+                if recipe.reversible {
+                    metas.push(AccountMeta::new(token_account, false));
+                    metas.push(AccountMeta::new(
+                        pda::associated_token(&vault_authority, mint),
+                        false,
+                    ));
+                } else {
+                    metas.push(AccountMeta::new_readonly(token_account, false));
+                }
+            }
+            IngredientConstraint::ForgedOutput { burn, .. } => burns |= *burn,
+            IngredientConstraint::SignedAuthorization { .. } => {
+                metas.push(AccountMeta::new_readonly(sysvar::instructions::ID, false));
+            }
+            _ => stack.extend(constraint.children().unwrap_or_default().iter().rev()),
+        }
+    }
+
+    for mint in &choices.forged_assets {
+        metas.push(AccountMeta::new_readonly(pda::forged_asset(mint).0, false));
+        let token_account = pda::associated_token(forger, mint);
+        if burns {
+            metas.push(AccountMeta::new(*mint, false));
+            metas.push(AccountMeta::new(token_account, false));
+        } else {
+            metas.push(AccountMeta::new_readonly(token_account, false));
+        }
+    }
+    metas.0
+}
+
+/// Trailing remaining accounts of `recipe`'s extra outputs.
+///
+/// Each `OneOfOne` output takes the next of `fresh_mints`, which the forger
+/// creates earlier in the same transaction; semi-fungible outputs mint into
+/// the forger's associated token account for the shared output mint.
+pub fn output_accounts(
+    recipe_key: &Pubkey,
+    recipe: &Recipe,
+    forger: &Pubkey,
+    fresh_mints: &[Pubkey],
+) -> Result<Vec<AccountMeta>> {
+    let mut fresh_mints = fresh_mints.iter();
+    let mut metas = Vec::with_capacity(recipe.output_account_count());
+    for (index, output) in recipe.extra_outputs.iter().enumerate() {
+        metas.extend(match output.output_kind {
+            OutputKind::OneOfOne => {
+                let mint = fresh_mints.next().ok_or(ClientError::MissingOutputMint)?;
+                vec![
+                    AccountMeta::new(*mint, false),
+                    AccountMeta::new(pda::associated_token(forger, mint), false),
+                    AccountMeta::new(pda::metadata(mint).0, false),
+                    AccountMeta::new(pda::master_edition(mint).0, false),
+                ]
+            }
+            OutputKind::SemiFungible => {
+                let output_mint = pda::output_mint(recipe_key, index as u8).0;
+                vec![
+                    AccountMeta::new(output_mint, false),
+                    AccountMeta::new(pda::associated_token(forger, &output_mint), false),
+                ]
+            }
+            OutputKind::Edition { .. } | OutputKind::Compressed { .. } => {
+                return Err(ClientError::UnsupportedOutput)
+            }
+        });
+    }
+    Ok(metas)
+}
+
+/// Input hash `forge_asset` (or `forge_batch`) will compute for `forger`
+/// with the remaining accounts in `snapshot`, extra output accounts last.
+///
+/// Fails with the program's error if the ingredients are not satisfied.
+pub fn forge_input_hash(
+    recipe_key: &Pubkey,
+    recipe: &Recipe,
+    forger: &Pubkey,
+    nonce: u64,
+    snapshot: &AccountSnapshot,
+) -> Result<[u8; HASH_BYTES]> {
+    input_hash(recipe_key, recipe, forger, nonce, snapshot, Vec::new())
+}
+
+/// Input hash `evolve_asset` will compute when `forger` evolves the asset
+/// recorded by `forged_asset`.
+pub fn evolve_input_hash(
+    recipe_key: &Pubkey,
+    recipe: &Recipe,
+    forger: &Pubkey,
+    nonce: u64,
+    forged_asset: &ForgedAsset,
+    snapshot: &AccountSnapshot,
+) -> Result<[u8; HASH_BYTES]> {
+    let target =
+        forge_hash::chunk::evolve_target(&forged_asset.mint.to_bytes(), forged_asset.level);
+    input_hash(recipe_key, recipe, forger, nonce, snapshot, vec![target])
+}
+
+fn input_hash(
+    recipe_key: &Pubkey,
+    recipe: &Recipe,
+    forger: &Pubkey,
+    nonce: u64,
+    snapshot: &AccountSnapshot,
+    leading_chunks: Vec<Vec<u8>>,
+) -> Result<[u8; HASH_BYTES]> {
+    snapshot
+        .with_account_infos(|account_infos| {
+            let (ingredient_accounts, _) = recipe.split_output_accounts(account_infos)?;
+            IngredientVerifier::new(*recipe_key, *forger, snapshot.now, ingredient_accounts)
+                .input_hash(
+                    &recipe.ingredient_constraints,
+                    recipe.replay_policy,
+                    nonce,
+                    leading_chunks,
+                )
+        })
+        .map_err(|err| ClientError::Ingredients(Box::new(err)))
+}
+
+/// Account metas in insertion order, merging duplicate keys.
+#[derive(Default)]
+struct AccountMetas(Vec<AccountMeta>);
+
+impl AccountMetas {
+    fn push(&mut self, meta: AccountMeta) {
+        match self.0.iter_mut().find(|m| m.pubkey == meta.pubkey) {
+            Some(existing) => {
+                existing.is_signer |= meta.is_signer;
+                existing.is_writable |= meta.is_writable;
+            }
+            None => self.0.push(meta),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::error;
+    use forge::{
+        eligibility::SnapshotAccount,
+        errors::ForgeError,
+        state::{RecipeMode, RecipeStatus, RecipeUse, ReplayPolicy},
+        testing::{self, TestEnv},
+    };
+    use forge_hash::{chunk, compute_input_hash, ForgeInputs};
+
+    fn recipe(ingredient_constraints: Vec<IngredientConstraint>) -> Recipe {
+        Recipe {
+            forge_config: Pubkey::new_unique(),
+            slug: "sword".to_string(),
+            version: 1,
+            output_kind: OutputKind::OneOfOne,
+            supply_cap: None,
+            minted: 0,
+            metadata_uri: "uri".to_string(),
+            creators: vec![],
+            collection_mint: None,
+            go_live_unix_time: None,
+            pause_at: None,
+            retire_at: None,
+            ingredient_constraints,
+            trait_table: vec![],
+            extra_outputs: vec![],
+            mode: RecipeMode::Mint,
+            reversible: false,
+            unforged: 0,
+            replay_policy: ReplayPolicy::OncePerInputSet,
+            status: RecipeStatus::Active,
+            previous_version: None,
+            next_version: None,
+            bump: 255,
+            open_uses: 0,
+            _reserved: [0; 3],
+        }
+    }
+
+    #[test]
+    fn assembles_nested_leaves_once() {
+        let forger = Pubkey::new_unique();
+        let cosigner = Pubkey::new_unique();
+        let iron = Pubkey::new_unique();
+        let recipe = recipe(vec![
+            IngredientConstraint::TokenMint {
+                mint: iron,
+                amount: 1,
+            },
+            IngredientConstraint::AnyOf {
+                constraints: vec![
+                    IngredientConstraint::Signer { authority: forger },
+                    IngredientConstraint::Signer {
+                        authority: cosigner,
+                    },
+                    IngredientConstraint::TokenMint {
+                        mint: iron,
+                        amount: 2,
+                    },
+                ]
+                .into(),
+            },
+        ]);
+
+        let metas = ingredient_accounts(&recipe, &forger, &IngredientChoices::default());
+        assert_eq!(
+            metas,
+            vec![
+                AccountMeta::new_readonly(pda::associated_token(&forger, &iron), false),
+                AccountMeta::new_readonly(cosigner, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn input_hash_matches_schema() {
+        let forger = Pubkey::new_unique();
+        let iron = Pubkey::new_unique();
+        let recipe_key = Pubkey::new_unique();
+        let recipe = recipe(vec![IngredientConstraint::TokenMint {
+            mint: iron,
+            amount: 2,
+        }]);
+        let token_account = pda::associated_token(&forger, &iron);
+        let mut data = vec![0u8; 165];
+        data[0..32].copy_from_slice(iron.as_ref());
+        data[32..64].copy_from_slice(forger.as_ref());
+        data[64..72].copy_from_slice(&5u64.to_le_bytes());
+        let snapshot = AccountSnapshot {
+            now: 0,
+            accounts: vec![SnapshotAccount {
+                key: token_account,
+                owner: anchor_spl::token::ID,
+                lamports: 1,
+                data,
+                is_signer: false,
+            }],
+        };
+
+        let expected = compute_input_hash(
+            &recipe_key.to_bytes(),
+            &forger.to_bytes(),
+            &ForgeInputs {
+                replay_policy: forge_hash::ReplayPolicy::OncePerInputSet,
+                nonce: 0,
+                authorized_nonce: false,
+                chunks: &[chunk::token_mint(&iron.to_bytes(), 2)],
+                accounts: &[token_account.to_bytes()],
+            },
+        );
+        assert_eq!(
+            forge_input_hash(&recipe_key, &recipe, &forger, 0, &snapshot).unwrap(),
+            expected
+        );
+        assert!(forge_input_hash(
+            &recipe_key,
+            &recipe,
+            &forger,
+            0,
+            &AccountSnapshot::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn forge_asset_v2_computes_the_same_hash() {
+        let mut env = TestEnv::new();
+        let (_, forge_config) = env.add_forge_config();
+        let recipe = testing::recipe(forge_config);
+        let recipe_key = env.add_recipe(recipe.clone());
+        let forger = env.add_wallet(1_000_000_000);
+        let snapshot = AccountSnapshot {
+            now: 0,
+            accounts: vec![],
+        };
+        let input_hash = forge_input_hash(&recipe_key, &recipe, &forger, 7, &snapshot).unwrap();
+
+        // `forge_asset_v2` returns the hash it derives `recipe_use` from. It
+        // reports the record of this hash as already used, rather than as
+        // the wrong address, only if that hash is this one.
+        let (recipe_use, bump) = pda::recipe_use(&recipe_key, &input_hash);
+        env.add_anchor_account(
+            recipe_use,
+            &RecipeUse {
+                recipe: recipe_key,
+                input_hash,
+                forged_at: 0,
+                bump,
+                payer: forger,
+                _reserved: [0; 7],
+            },
+        );
+        assert_eq!(
+            env.forge_asset_v2(&recipe_key, &forger, &recipe_use, 7),
+            Err(error!(ForgeError::DuplicateRecipeUse))
+        );
+    }
+}
@@ -0,0 +1,948 @@
+//! Typed builders for every Forge instruction.
+//!
+//! Each builder takes the keys that identify what the instruction acts on,
+//! derives every PDA itself and produces a ready-to-sign [`Instruction`].
+//! Builders of instructions that read remaining accounts take them with
+//! `remaining_accounts`; see [`crate::ingredients`] for assembling them.
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+use forge::{
+    cpi::{
+        bubblegum::{ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID},
+        token_metadata::token_metadata_program_id,
+    },
+    state::{constants::HASH_BYTES, OutputKind, Recipe},
+    CreateRecipeArgs, EvolveAssetArgs, ForgeAssetArgs, ForgeAssetV2Args, ForgeBatchArgs,
+    ForkRecipeVersionArgs, InitOutputMintArgs, InitializeForgeArgs, SetForgeConfigArgs,
+    SetRecipeStatusArgs, UpdateRecipeArgs,
+};
+
+use crate::pda;
+
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: forge::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// `initialize_forge`: creates the `ForgeConfig` of `authority`.
+#[derive(Clone, Debug)]
+pub struct InitializeForge {
+    pub authority: Pubkey,
+    pub args: InitializeForgeArgs,
+}
+
+impl InitializeForge {
+    pub fn new(authority: Pubkey, args: InitializeForgeArgs) -> Self {
+        Self { authority, args }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::InitializeForge {
+                forge_config: pda::forge_config(&self.authority).0,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::InitializeForge { args: self.args },
+            Vec::new(),
+        )
+    }
+}
+
+/// `migrate_forge_config`: grows a pre-`event_sequence` `ForgeConfig` of
+/// `authority` to the current layout.
+#[derive(Clone, Debug)]
+pub struct MigrateForgeConfig {
+    pub authority: Pubkey,
+}
+
+impl MigrateForgeConfig {
+    pub fn new(authority: Pubkey) -> Self {
+        Self { authority }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::MigrateForgeConfig {
+                forge_config: pda::forge_config(&self.authority).0,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::MigrateForgeConfig {},
+            Vec::new(),
+        )
+    }
+}
+
+/// `set_forge_config`: updates the `ForgeConfig` of `authority`.
+#[derive(Clone, Debug)]
+pub struct SetForgeConfig {
+    pub authority: Pubkey,
+    pub args: SetForgeConfigArgs,
+}
+
+impl SetForgeConfig {
+    pub fn new(authority: Pubkey, args: SetForgeConfigArgs) -> Self {
+        Self { authority, args }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::SetForgeConfig {
+                forge_config: pda::forge_config(&self.authority).0,
+                authority: self.authority,
+            },
+            forge::instruction::SetForgeConfig { args: self.args },
+            Vec::new(),
+        )
+    }
+}
+
+/// `create_recipe`: creates the recipe `args.slug`/`args.version`, linked to
+/// `args.previous_version` when set.
+#[derive(Clone, Debug)]
+pub struct CreateRecipe {
+    pub authority: Pubkey,
+    pub args: CreateRecipeArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl CreateRecipe {
+    pub fn new(authority: Pubkey, args: CreateRecipeArgs) -> Self {
+        Self {
+            authority,
+            args,
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Extra accounts read on activation, i.e. the parent mint of an
+    /// `Edition` recipe.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    /// Address of the recipe this instruction creates.
+    pub fn recipe(&self) -> Pubkey {
+        let forge_config = pda::forge_config(&self.authority).0;
+        pda::recipe(&forge_config, &self.args.slug, self.args.version).0
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let recipe = self.recipe();
+        build(
+            forge::accounts::CreateRecipe {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe,
+                previous_recipe: self.args.previous_version,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::CreateRecipe { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `update_recipe`: changes the set fields of `recipe`.
+#[derive(Clone, Debug)]
+pub struct UpdateRecipe {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+    pub args: UpdateRecipeArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl UpdateRecipe {
+    pub fn new(authority: Pubkey, recipe: Pubkey, args: UpdateRecipeArgs) -> Self {
+        Self {
+            authority,
+            recipe,
+            args,
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Extra accounts read when an active recipe is re-checked, i.e. the
+    /// parent mint of an `Edition` recipe.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::UpdateRecipe {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::UpdateRecipe { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `set_recipe_status`: moves `recipe` to `args.status`.
+#[derive(Clone, Debug)]
+pub struct SetRecipeStatus {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+    pub args: SetRecipeStatusArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl SetRecipeStatus {
+    pub fn new(authority: Pubkey, recipe: Pubkey, args: SetRecipeStatusArgs) -> Self {
+        Self {
+            authority,
+            recipe,
+            args,
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Extra accounts read on activation, i.e. the parent mint of an
+    /// `Edition` recipe.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::SetRecipeStatus {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::SetRecipeStatus { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `fork_recipe_version`: copies version `previous_version` of `slug` into
+/// the next version.
+#[derive(Clone, Debug)]
+pub struct ForkRecipeVersion {
+    pub authority: Pubkey,
+    pub slug: String,
+    pub previous_version: u16,
+    pub args: ForkRecipeVersionArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl ForkRecipeVersion {
+    pub fn new(
+        authority: Pubkey,
+        slug: impl Into<String>,
+        previous_version: u16,
+        args: ForkRecipeVersionArgs,
+    ) -> Self {
+        Self {
+            authority,
+            slug: slug.into(),
+            previous_version,
+            args,
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Extra accounts read on activation, i.e. the parent mint of an
+    /// `Edition` recipe.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let forge_config = pda::forge_config(&self.authority).0;
+        build(
+            forge::accounts::ForkRecipeVersion {
+                forge_config,
+                previous_recipe: pda::recipe(&forge_config, &self.slug, self.previous_version).0,
+                recipe: pda::recipe(
+                    &forge_config,
+                    &self.slug,
+                    self.previous_version.wrapping_add(1),
+                )
+                .0,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::ForkRecipeVersion { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `migrate_recipe`: rewrites a `recipe` created before `open_uses` in the
+/// current layout.
+#[derive(Clone, Debug)]
+pub struct MigrateRecipe {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+}
+
+impl MigrateRecipe {
+    pub fn new(authority: Pubkey, recipe: Pubkey) -> Self {
+        Self { authority, recipe }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::MigrateRecipe {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            forge::instruction::MigrateRecipe {},
+            Vec::new(),
+        )
+    }
+}
+
+/// `close_recipe`: shrinks a retired `recipe` whose uses are all closed to a
+/// `ClosedRecipe`, refunding the rest of its rent to `authority`.
+#[derive(Clone, Debug)]
+pub struct CloseRecipe {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+}
+
+impl CloseRecipe {
+    pub fn new(authority: Pubkey, recipe: Pubkey) -> Self {
+        Self { authority, recipe }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::CloseRecipe {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                authority: self.authority,
+            },
+            forge::instruction::CloseRecipe {},
+            Vec::new(),
+        )
+    }
+}
+
+/// `close_recipe_uses`: closes `RecipeUse` records of a retired `recipe`,
+/// refunding each to its payer (`authority` for records that predate the
+/// stored payer).
+#[derive(Clone, Debug)]
+pub struct CloseRecipeUses {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+    /// `(recipe_use, payer)` pairs.
+    pub uses: Vec<(Pubkey, Pubkey)>,
+}
+
+impl CloseRecipeUses {
+    pub fn new(authority: Pubkey, recipe: Pubkey, uses: Vec<(Pubkey, Pubkey)>) -> Self {
+        Self {
+            authority,
+            recipe,
+            uses,
+        }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let pairs = self
+            .uses
+            .iter()
+            .flat_map(|(recipe_use, payer)| {
+                [
+                    AccountMeta::new(*recipe_use, false),
+                    AccountMeta::new(*payer, false),
+                ]
+            })
+            .collect();
+        build(
+            forge::accounts::CloseRecipeUses {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                authority: self.authority,
+            },
+            forge::instruction::CloseRecipeUses {},
+            pairs,
+        )
+    }
+}
+
+/// `init_output_mint`: creates the shared mint of a semi-fungible output.
+#[derive(Clone, Debug)]
+pub struct InitOutputMint {
+    pub authority: Pubkey,
+    pub recipe: Pubkey,
+    pub args: InitOutputMintArgs,
+}
+
+impl InitOutputMint {
+    pub fn new(authority: Pubkey, recipe: Pubkey, args: InitOutputMintArgs) -> Self {
+        Self {
+            authority,
+            recipe,
+            args,
+        }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let output_mint = pda::output_mint(&self.recipe, self.args.output_index).0;
+        build(
+            forge::accounts::InitOutputMint {
+                forge_config: pda::forge_config(&self.authority).0,
+                recipe: self.recipe,
+                output_mint,
+                metadata: pda::metadata(&output_mint).0,
+                token_metadata_program: token_metadata_program_id(),
+                authority: self.authority,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            forge::instruction::InitOutputMint { args: self.args },
+            Vec::new(),
+        )
+    }
+}
+
+/// `commit_forge`: commits `forger` to a trait roll on `recipe`.
+#[derive(Clone, Debug)]
+pub struct CommitForge {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+}
+
+impl CommitForge {
+    pub fn new(forge_config: Pubkey, recipe: Pubkey, forger: Pubkey) -> Self {
+        Self {
+            forge_config,
+            recipe,
+            forger,
+        }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::CommitForge {
+                forge_config: self.forge_config,
+                recipe: self.recipe,
+                forge_commit: pda::forge_commit(&self.recipe, &self.forger).0,
+                forger: self.forger,
+                system_program: system_program::ID,
+            },
+            forge::instruction::CommitForge {},
+            Vec::new(),
+        )
+    }
+}
+
+/// `cancel_forge_commit`: closes the pending commit of `forger` on a retired
+/// or closed `recipe`.
+#[derive(Clone, Debug)]
+pub struct CancelForgeCommit {
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+}
+
+impl CancelForgeCommit {
+    pub fn new(recipe: Pubkey, forger: Pubkey) -> Self {
+        Self { recipe, forger }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::CancelForgeCommit {
+                forge_commit: pda::forge_commit(&self.recipe, &self.forger).0,
+                recipe: self.recipe,
+                forger: self.forger,
+            },
+            forge::instruction::CancelForgeCommit {},
+            Vec::new(),
+        )
+    }
+}
+
+/// Primary output accounts shared by `forge_asset` and `forge_asset_v2`.
+#[derive(Clone, Debug)]
+struct MintAccounts {
+    forge_config: Pubkey,
+    recipe: Pubkey,
+    forger: Pubkey,
+    mint: Pubkey,
+    escrow: bool,
+    reveal: bool,
+}
+
+impl MintAccounts {
+    fn new(recipe_key: Pubkey, recipe: &Recipe, forger: Pubkey, mint: Pubkey) -> Self {
+        Self {
+            forge_config: recipe.forge_config,
+            recipe: recipe_key,
+            forger,
+            mint,
+            escrow: recipe.reversible,
+            reveal: !recipe.trait_table.is_empty(),
+        }
+    }
+
+    fn forge_escrow(&self) -> Option<Pubkey> {
+        self.escrow.then(|| pda::forge_escrow(&self.mint).0)
+    }
+
+    fn forge_commit(&self) -> Option<Pubkey> {
+        self.reveal
+            .then(|| pda::forge_commit(&self.recipe, &self.forger).0)
+    }
+
+    fn slot_hashes(&self) -> Option<Pubkey> {
+        self.reveal.then_some(sysvar::slot_hashes::ID)
+    }
+}
+
+/// `forge_asset`: forges a one-of-one asset into the fresh `mint`, which
+/// must also sign.
+///
+/// Escrow and trait-reveal accounts are included as the recipe requires.
+#[derive(Clone, Debug)]
+pub struct ForgeAsset {
+    accounts: MintAccounts,
+    pub args: ForgeAssetArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl ForgeAsset {
+    pub fn new(
+        recipe_key: Pubkey,
+        recipe: &Recipe,
+        forger: Pubkey,
+        mint: Pubkey,
+        input_hash: [u8; HASH_BYTES],
+        nonce: u64,
+    ) -> Self {
+        Self {
+            accounts: MintAccounts::new(recipe_key, recipe, forger, mint),
+            args: ForgeAssetArgs { input_hash, nonce },
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Ingredient accounts followed by extra output accounts.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let a = &self.accounts;
+        build(
+            forge::accounts::ForgeAsset {
+                forge_config: a.forge_config,
+                recipe: a.recipe,
+                recipe_use: pda::recipe_use(&a.recipe, &self.args.input_hash).0,
+                forger: a.forger,
+                mint: a.mint,
+                mint_ata: pda::associated_token(&a.forger, &a.mint),
+                forged_asset: pda::forged_asset(&a.mint).0,
+                forge_escrow: a.forge_escrow(),
+                forge_commit: a.forge_commit(),
+                slot_hashes: a.slot_hashes(),
+                token_metadata_program: token_metadata_program_id(),
+                metadata: pda::metadata(&a.mint).0,
+                master_edition: pda::master_edition(&a.mint).0,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            },
+            forge::instruction::ForgeAsset { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `forge_asset_v2`: like [`ForgeAsset`], but the program computes the input
+/// hash. The expected hash is still needed to address the `RecipeUse` PDA.
+#[derive(Clone, Debug)]
+pub struct ForgeAssetV2 {
+    accounts: MintAccounts,
+    pub input_hash: [u8; HASH_BYTES],
+    pub args: ForgeAssetV2Args,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl ForgeAssetV2 {
+    pub fn new(
+        recipe_key: Pubkey,
+        recipe: &Recipe,
+        forger: Pubkey,
+        mint: Pubkey,
+        input_hash: [u8; HASH_BYTES],
+        nonce: u64,
+    ) -> Self {
+        Self {
+            accounts: MintAccounts::new(recipe_key, recipe, forger, mint),
+            input_hash,
+            args: ForgeAssetV2Args { nonce },
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Ingredient accounts followed by extra output accounts.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let a = &self.accounts;
+        build(
+            forge::accounts::ForgeAssetV2 {
+                forge_config: a.forge_config,
+                recipe: a.recipe,
+                recipe_use: pda::recipe_use(&a.recipe, &self.input_hash).0,
+                forger: a.forger,
+                mint: a.mint,
+                mint_ata: pda::associated_token(&a.forger, &a.mint),
+                forged_asset: pda::forged_asset(&a.mint).0,
+                forge_escrow: a.forge_escrow(),
+                forge_commit: a.forge_commit(),
+                slot_hashes: a.slot_hashes(),
+                token_metadata_program: token_metadata_program_id(),
+                metadata: pda::metadata(&a.mint).0,
+                master_edition: pda::master_edition(&a.mint).0,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            },
+            forge::instruction::ForgeAssetV2 { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `forge_batch`: mints `count` units of a semi-fungible recipe into the
+/// forger's associated token account for the primary output mint, or
+/// `count` compressed NFTs of a compressed recipe into its Merkle tree.
+#[derive(Clone, Debug)]
+pub struct ForgeBatch {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub output_kind: OutputKind,
+    pub args: ForgeBatchArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl ForgeBatch {
+    pub fn new(
+        recipe_key: Pubkey,
+        recipe: &Recipe,
+        forger: Pubkey,
+        input_hash: [u8; HASH_BYTES],
+        nonce: u64,
+        count: u64,
+    ) -> Self {
+        Self {
+            forge_config: recipe.forge_config,
+            recipe: recipe_key,
+            forger,
+            output_kind: recipe.output_kind,
+            args: ForgeBatchArgs {
+                input_hash,
+                nonce,
+                count,
+            },
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Ingredient accounts.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let mut accounts = forge::accounts::ForgeBatch {
+            forge_config: self.forge_config,
+            recipe: self.recipe,
+            recipe_use: pda::recipe_use(&self.recipe, &self.args.input_hash).0,
+            forger: self.forger,
+            output_mint: None,
+            forger_token_account: None,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            tree_config: None,
+            merkle_tree: None,
+            bubblegum_program: None,
+            log_wrapper: None,
+            compression_program: None,
+        };
+        if let OutputKind::Compressed { merkle_tree } = self.output_kind {
+            accounts.tree_config = Some(pda::tree_config(&merkle_tree).0);
+            accounts.merkle_tree = Some(merkle_tree);
+            accounts.bubblegum_program = Some(BUBBLEGUM_PROGRAM_ID);
+            accounts.log_wrapper = Some(NOOP_PROGRAM_ID);
+            accounts.compression_program = Some(ACCOUNT_COMPRESSION_PROGRAM_ID);
+        } else {
+            let output_mint =
+                pda::output_mint(&self.recipe, forge::state::constants::PRIMARY_OUTPUT_INDEX).0;
+            accounts.output_mint = Some(output_mint);
+            accounts.forger_token_account = Some(pda::associated_token(&self.forger, &output_mint));
+        }
+        build(
+            accounts,
+            forge::instruction::ForgeBatch { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `evolve_asset`: upgrades the forged asset `mint`, held in the forger's
+/// associated token account.
+#[derive(Clone, Debug)]
+pub struct EvolveAsset {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub args: EvolveAssetArgs,
+    remaining_accounts: Vec<AccountMeta>,
+}
+
+impl EvolveAsset {
+    pub fn new(
+        recipe_key: Pubkey,
+        recipe: &Recipe,
+        forger: Pubkey,
+        mint: Pubkey,
+        input_hash: [u8; HASH_BYTES],
+        nonce: u64,
+    ) -> Self {
+        Self {
+            forge_config: recipe.forge_config,
+            recipe: recipe_key,
+            forger,
+            mint,
+            args: EvolveAssetArgs { input_hash, nonce },
+            remaining_accounts: Vec::new(),
+        }
+    }
+
+    /// Ingredient accounts.
+    pub fn remaining_accounts(mut self, accounts: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(accounts);
+        self
+    }
+
+    pub fn instruction(self) -> Instruction {
+        build(
+            forge::accounts::EvolveAsset {
+                forge_config: self.forge_config,
+                recipe: self.recipe,
+                recipe_use: pda::recipe_use(&self.recipe, &self.args.input_hash).0,
+                forger: self.forger,
+                forged_asset: pda::forged_asset(&self.mint).0,
+                asset_token_account: pda::associated_token(&self.forger, &self.mint),
+                metadata: pda::metadata(&self.mint).0,
+                token_metadata_program: token_metadata_program_id(),
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+            },
+            forge::instruction::EvolveAsset { args: self.args },
+            self.remaining_accounts,
+        )
+    }
+}
+
+/// `unforge`: burns the asset `mint` held by `holder` and returns its
+/// escrowed ingredients to the holder's associated token accounts.
+#[derive(Clone, Debug)]
+pub struct Unforge {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub holder: Pubkey,
+    pub mint: Pubkey,
+    /// Mints of the escrowed ingredients (`ForgeEscrow::items`).
+    pub escrowed_mints: Vec<Pubkey>,
+}
+
+impl Unforge {
+    pub fn new(
+        forge_config: Pubkey,
+        recipe: Pubkey,
+        holder: Pubkey,
+        mint: Pubkey,
+        escrowed_mints: Vec<Pubkey>,
+    ) -> Self {
+        Self {
+            forge_config,
+            recipe,
+            holder,
+            mint,
+            escrowed_mints,
+        }
+    }
+
+    pub fn instruction(self) -> Instruction {
+        let vault_authority = pda::vault_authority(&self.forge_config).0;
+        let returns = self
+            .escrowed_mints
+            .iter()
+            .flat_map(|mint| {
+                [
+                    AccountMeta::new(pda::associated_token(&vault_authority, mint), false),
+                    AccountMeta::new(pda::associated_token(&self.holder, mint), false),
+                ]
+            })
+            .collect();
+        build(
+            forge::accounts::Unforge {
+                forge_config: self.forge_config,
+                recipe: self.recipe,
+                forged_asset: pda::forged_asset(&self.mint).0,
+                forge_escrow: pda::forge_escrow(&self.mint).0,
+                mint: self.mint,
+                holder_token_account: pda::associated_token(&self.holder, &self.mint),
+                vault_authority,
+                holder: self.holder,
+                token_program: anchor_spl::token::ID,
+            },
+            forge::instruction::Unforge {},
+            returns,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use forge::state::{OutputKind, RecipeMode, RecipeStatus, ReplayPolicy, TraitTier};
+
+    fn recipe() -> Recipe {
+        Recipe {
+            forge_config: Pubkey::new_unique(),
+            slug: "sword".to_string(),
+            version: 1,
+            output_kind: OutputKind::OneOfOne,
+            supply_cap: None,
+            minted: 0,
+            metadata_uri: "uri".to_string(),
+            creators: vec![],
+            collection_mint: None,
+            go_live_unix_time: None,
+            pause_at: None,
+            retire_at: None,
+            ingredient_constraints: vec![],
+            trait_table: vec![],
+            extra_outputs: vec![],
+            mode: RecipeMode::Mint,
+            reversible: false,
+            unforged: 0,
+            replay_policy: ReplayPolicy::Unlimited,
+            status: RecipeStatus::Active,
+            previous_version: None,
+            next_version: None,
+            bump: 255,
+            open_uses: 0,
+            _reserved: [0; 3],
+        }
+    }
+
+    #[test]
+    fn forge_asset_includes_optional_accounts_as_needed() {
+        let mut recipe = recipe();
+        let (recipe_key, forger, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let hash = [7; HASH_BYTES];
+
+        let ix = ForgeAsset::new(recipe_key, &recipe, forger, mint, hash, 1).instruction();
+        assert_eq!(ix.program_id, forge::ID);
+        assert!(ix
+            .data
+            .starts_with(forge::instruction::ForgeAsset::DISCRIMINATOR));
+        assert_eq!(ix.accounts[2].pubkey, pda::recipe_use(&recipe_key, &hash).0);
+        // Unused optional accounts are passed as the program id.
+        assert_eq!(ix.accounts[7].pubkey, forge::ID);
+        assert_eq!(ix.accounts[8].pubkey, forge::ID);
+
+        recipe.reversible = true;
+        recipe.trait_table = vec![TraitTier {
+            name: "rare".to_string(),
+            weight: 1,
+            metadata_uri: "uri".to_string(),
+        }];
+        let ix = ForgeAsset::new(recipe_key, &recipe, forger, mint, hash, 1).instruction();
+        assert_eq!(ix.accounts[7].pubkey, pda::forge_escrow(&mint).0);
+        assert_eq!(
+            ix.accounts[8].pubkey,
+            pda::forge_commit(&recipe_key, &forger).0
+        );
+        assert_eq!(ix.accounts[9].pubkey, sysvar::slot_hashes::ID);
+    }
+
+    #[test]
+    fn forge_batch_passes_the_accounts_of_the_output_kind() {
+        let mut recipe = recipe();
+        recipe.output_kind = OutputKind::SemiFungible;
+        let (recipe_key, forger) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let hash = [7; HASH_BYTES];
+        let output_mint =
+            pda::output_mint(&recipe_key, forge::state::constants::PRIMARY_OUTPUT_INDEX).0;
+
+        let ix = ForgeBatch::new(recipe_key, &recipe, forger, hash, 0, 2).instruction();
+        assert_eq!(ix.accounts[4].pubkey, output_mint);
+        assert_eq!(
+            ix.accounts[5].pubkey,
+            pda::associated_token(&forger, &output_mint)
+        );
+        assert!(ix.accounts[8..13]
+            .iter()
+            .all(|meta| meta.pubkey == forge::ID));
+
+        let merkle_tree = Pubkey::new_unique();
+        recipe.output_kind = OutputKind::Compressed { merkle_tree };
+        let ix = ForgeBatch::new(recipe_key, &recipe, forger, hash, 0, 2).instruction();
+        assert_eq!(ix.accounts[4].pubkey, forge::ID);
+        assert_eq!(ix.accounts[5].pubkey, forge::ID);
+        let compressed: Vec<Pubkey> = ix.accounts[8..13].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            compressed,
+            [
+                pda::tree_config(&merkle_tree).0,
+                merkle_tree,
+                BUBBLEGUM_PROGRAM_ID,
+                NOOP_PROGRAM_ID,
+                ACCOUNT_COMPRESSION_PROGRAM_ID,
+            ]
+        );
+        assert!(ix.accounts[9].is_writable);
+    }
+}
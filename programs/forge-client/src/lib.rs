@@ -0,0 +1,54 @@
+//! Rust client for the Forge program.
+//!
+//! - [`pda`] derives every program-owned and Metaplex account address.
+//! - [`instructions`] builds each instruction from the keys it acts on.
+//! - [`ingredients`] lays out ingredient and extra output remaining
+//!   accounts and computes input hashes off-chain.
+//! - [`accounts`] fetches and decodes program accounts over RPC.
+//!
+//! A typical forge fetches the recipe, assembles its remaining accounts,
+//! snapshots them to compute the input hash and builds `forge_asset`:
+//!
+//! ```ignore
+//! let recipe = accounts::fetch_recipe(&rpc, &recipe_key)?;
+//! let metas = ingredients::ingredient_accounts(&recipe, &forger, &choices);
+//! let input_hash = ingredients::forge_input_hash(&recipe_key, &recipe, &forger, nonce, &snapshot)?;
+//! let ix = instructions::ForgeAsset::new(recipe_key, &recipe, forger, mint, input_hash, nonce)
+//!     .remaining_accounts(metas)
+//!     .instruction();
+//! ```
+
+pub mod accounts;
+pub mod ingredients;
+pub mod instructions;
+pub mod pda;
+
+use anchor_lang::prelude::Pubkey;
+
+pub use forge;
+pub use forge_hash;
+
+/// Errors returned by the client helpers.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<solana_rpc_client_api::client_error::Error>),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} could not be decoded: {1}")]
+    Decode(Pubkey, Box<anchor_lang::error::Error>),
+    #[error("ingredients are not satisfied: {0}")]
+    Ingredients(Box<anchor_lang::error::Error>),
+    #[error("a fresh mint is needed for every one-of-one extra output")]
+    MissingOutputMint,
+    #[error("edition and compressed extra outputs are not supported")]
+    UnsupportedOutput,
+}
+
+impl From<solana_rpc_client_api::client_error::Error> for ClientError {
+    fn from(err: solana_rpc_client_api::client_error::Error) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
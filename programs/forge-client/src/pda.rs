@@ -0,0 +1,88 @@
+//! PDA derivation for every account the Forge program addresses by seeds.
+
+use anchor_lang::prelude::Pubkey;
+use forge::{
+    cpi::{
+        bubblegum::derive_tree_config_pda,
+        token_metadata::{derive_master_edition_pda, derive_metadata_pda},
+    },
+    state::constants::{
+        FORGED_ASSET_SEED, FORGE_COMMIT_SEED, FORGE_CONFIG_SEED, FORGE_ESCROW_SEED,
+        FORGE_VAULT_SEED, HASH_BYTES, OUTPUT_MINT_SEED, RECIPE_SEED, RECIPE_USE_SEED,
+    },
+};
+
+/// `ForgeConfig` of `authority`.
+pub fn forge_config(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FORGE_CONFIG_SEED, authority.as_ref()], &forge::ID)
+}
+
+/// `Recipe` `slug`/`version` under `forge_config`.
+pub fn recipe(forge_config: &Pubkey, slug: &str, version: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            RECIPE_SEED,
+            forge_config.as_ref(),
+            slug.as_bytes(),
+            &version.to_le_bytes(),
+        ],
+        &forge::ID,
+    )
+}
+
+/// `RecipeUse` record of `input_hash` on `recipe`.
+pub fn recipe_use(recipe: &Pubkey, input_hash: &[u8; HASH_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECIPE_USE_SEED, recipe.as_ref(), input_hash.as_ref()],
+        &forge::ID,
+    )
+}
+
+/// `ForgedAsset` provenance record of `mint`.
+pub fn forged_asset(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FORGED_ASSET_SEED, mint.as_ref()], &forge::ID)
+}
+
+/// `ForgeCommit` of `forger` on `recipe`.
+pub fn forge_commit(recipe: &Pubkey, forger: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FORGE_COMMIT_SEED, recipe.as_ref(), forger.as_ref()],
+        &forge::ID,
+    )
+}
+
+/// `ForgeEscrow` of the asset `mint`.
+pub fn forge_escrow(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FORGE_ESCROW_SEED, mint.as_ref()], &forge::ID)
+}
+
+/// Shared mint of a semi-fungible output; `index` is the extra output index
+/// or `PRIMARY_OUTPUT_INDEX`.
+pub fn output_mint(recipe: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OUTPUT_MINT_SEED, recipe.as_ref(), &[index]], &forge::ID)
+}
+
+/// Authority of the token vaults holding escrowed ingredients.
+pub fn vault_authority(forge_config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FORGE_VAULT_SEED, forge_config.as_ref()], &forge::ID)
+}
+
+/// Metaplex metadata account of `mint`.
+pub fn metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    derive_metadata_pda(mint)
+}
+
+/// Metaplex master edition account of `mint`.
+pub fn master_edition(mint: &Pubkey) -> (Pubkey, u8) {
+    derive_master_edition_pda(mint)
+}
+
+/// Bubblegum tree config of `merkle_tree`.
+pub fn tree_config(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    derive_tree_config_pda(merkle_tree)
+}
+
+/// Associated token account of `owner` for `mint`.
+pub fn associated_token(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(owner, mint)
+}
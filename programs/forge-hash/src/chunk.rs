@@ -0,0 +1,110 @@
+//! Canonical hash chunks of satisfied ingredient constraints.
+//!
+//! Every chunk starts with its variant tag. Leaf chunks carry the constraint
+//! parameters that identify what was required; the concrete accounts that
+//! satisfied them are hashed separately as inputs.
+
+use alloc::vec::Vec;
+
+/// Variant tags leading each chunk.
+pub mod tag {
+    pub const SIGNER: u8 = 0;
+    pub const CUSTOM_SEEDS: u8 = 1;
+    pub const TOKEN_MINT: u8 = 2;
+    pub const COLLECTION_NFT: u8 = 3;
+    pub const ALLOWLIST: u8 = 4;
+    pub const ALL_OF: u8 = 5;
+    pub const ANY_OF: u8 = 6;
+    pub const AT_LEAST: u8 = 7;
+    pub const FORGED_OUTPUT: u8 = 8;
+    pub const SIGNED_AUTHORIZATION: u8 = 9;
+    /// Asset being upgraded by `evolve_asset`, hashed ahead of the ingredients.
+    pub const EVOLVE_TARGET: u8 = 10;
+}
+
+/// `[0, authority]`
+pub fn signer(authority: &[u8; 32]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32);
+    chunk.push(tag::SIGNER);
+    chunk.extend_from_slice(authority);
+    chunk
+}
+
+/// `[1, program_id, include_forger, pda]`
+pub fn custom_seeds(program_id: &[u8; 32], include_forger: bool, pda: &[u8; 32]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32 + 1 + 32);
+    chunk.push(tag::CUSTOM_SEEDS);
+    chunk.extend_from_slice(program_id);
+    chunk.push(include_forger as u8);
+    chunk.extend_from_slice(pda);
+    chunk
+}
+
+/// `[2, mint, amount (u64 LE)]`
+pub fn token_mint(mint: &[u8; 32], amount: u64) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32 + 8);
+    chunk.push(tag::TOKEN_MINT);
+    chunk.extend_from_slice(mint);
+    chunk.extend_from_slice(&amount.to_le_bytes());
+    chunk
+}
+
+/// `[3, collection_mint]`
+pub fn collection_nft(collection_mint: &[u8; 32]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32);
+    chunk.push(tag::COLLECTION_NFT);
+    chunk.extend_from_slice(collection_mint);
+    chunk
+}
+
+/// `[4, merkle_root]`
+pub fn allowlist(merkle_root: &[u8; 32]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32);
+    chunk.push(tag::ALLOWLIST);
+    chunk.extend_from_slice(merkle_root);
+    chunk
+}
+
+/// `[tag, child_count, satisfied_mask (u16 LE)]` followed by
+/// `len (u16 LE) || chunk` for each satisfied child, in index order.
+pub fn composite(tag: u8, child_count: u8, satisfied_mask: u16, children: &[Vec<u8>]) -> Vec<u8> {
+    let body_len: usize = children.iter().map(|c| 2 + c.len()).sum();
+    let mut chunk = Vec::with_capacity(1 + 1 + 2 + body_len);
+    chunk.push(tag);
+    chunk.push(child_count);
+    chunk.extend_from_slice(&satisfied_mask.to_le_bytes());
+    for child in children {
+        chunk.extend_from_slice(&(child.len() as u16).to_le_bytes());
+        chunk.extend_from_slice(child);
+    }
+    chunk
+}
+
+/// `[8, recipe, mint, burn]`
+pub fn forged_output(recipe: &[u8; 32], mint: &[u8; 32], burn: bool) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32 + 32 + 1);
+    chunk.push(tag::FORGED_OUTPUT);
+    chunk.extend_from_slice(recipe);
+    chunk.extend_from_slice(mint);
+    chunk.push(burn as u8);
+    chunk
+}
+
+/// `[9, authority, nonce (u64 LE), expiry (i64 LE)]`
+pub fn signed_authorization(authority: &[u8; 32], nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32 + 8 + 8);
+    chunk.push(tag::SIGNED_AUTHORIZATION);
+    chunk.extend_from_slice(authority);
+    chunk.extend_from_slice(&nonce.to_le_bytes());
+    chunk.extend_from_slice(&expiry.to_le_bytes());
+    chunk
+}
+
+/// `[10, mint, level (u16 LE)]`
+pub fn evolve_target(mint: &[u8; 32], level: u16) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(1 + 32 + 2);
+    chunk.push(tag::EVOLVE_TARGET);
+    chunk.extend_from_slice(mint);
+    chunk.extend_from_slice(&level.to_le_bytes());
+    chunk
+}
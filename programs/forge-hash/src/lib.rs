@@ -0,0 +1,310 @@
+//! Input hash schema of the Forge program.
+//!
+//! `forge_asset` and `evolve_asset` key their `RecipeUse` replay records on
+//! an input hash that clients must compute before sending the transaction.
+//! This crate is the single definition of that hash: the program links it
+//! on-chain (hashing through the `sol_sha256` syscall) and host-side clients
+//! link it to derive the same `RecipeUse` PDA. It is `no_std` and only needs
+//! `alloc`.
+//!
+//! Version 1 of the schema is
+//!
+//! ```text
+//! input_hash = sha256("forge-input-hash" || version (1) || policy tag || recipe || body)
+//!
+//! body (OncePerWallet, 0)   = forger [|| nonce (u64 LE)]
+//! body (OncePerInputSet, 1) = ingredients
+//! body (Unlimited, 2)       = forger || nonce (u64 LE) || ingredients
+//!
+//! ingredients = chunk_count (u16 LE) || (len (u16 LE) || chunk)*
+//!            || input_count (u16 LE) || input*
+//! ```
+//!
+//! where the chunks are built with [`chunk`] and the inputs are the 32-byte
+//! accounts that satisfied each leaf constraint, in verification order. The
+//! `OncePerWallet` nonce is only present when a `SignedAuthorization`
+//! ingredient signed it (see [`ForgeInputs::authorized_nonce`]).
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod chunk;
+
+use alloc::vec::Vec;
+
+/// Domain separator prefixed to every input hash preimage.
+pub const INPUT_HASH_DOMAIN: &[u8] = b"forge-input-hash";
+
+/// Version of the input hash schema computed by [`compute_input_hash`].
+pub const INPUT_HASH_VERSION: u8 = 1;
+
+/// Which forges of a recipe collide on the same input hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayPolicy {
+    /// Binds only the forger.
+    OncePerWallet,
+    /// Binds only the ingredients.
+    OncePerInputSet,
+    /// Binds the forger, the nonce and the ingredients.
+    Unlimited,
+}
+
+impl ReplayPolicy {
+    /// Tag committed to the preimage (the on-chain Borsh variant index).
+    pub fn tag(self) -> u8 {
+        self as u8
+    }
+}
+
+/// What a forge presented, as far as the input hash is concerned.
+#[derive(Clone, Copy, Debug)]
+pub struct ForgeInputs<'a> {
+    /// The recipe's replay policy.
+    pub replay_policy: ReplayPolicy,
+    /// Caller-chosen nonce; only hashed under [`ReplayPolicy::Unlimited`],
+    /// since committing it elsewhere would let a forger pick a fresh record
+    /// at will, unless `authorized_nonce` is set.
+    pub nonce: u64,
+    /// Whether `nonce` is the one a `SignedAuthorization` ingredient signed,
+    /// so the authority picked it rather than the forger. It is then hashed
+    /// under [`ReplayPolicy::OncePerWallet`] too, making each authorization
+    /// good for its own forge; `OncePerInputSet` already hashes it through
+    /// the authorization's chunk.
+    pub authorized_nonce: bool,
+    /// Chunks of the satisfied top-level constraints, in recipe order.
+    pub chunks: &'a [Vec<u8>],
+    /// Concrete accounts that satisfied each leaf, in verification order.
+    pub accounts: &'a [[u8; 32]],
+}
+
+/// Computes the v1 input hash of a forge of `recipe` by `forger`.
+pub fn compute_input_hash(recipe: &[u8; 32], forger: &[u8; 32], inputs: &ForgeInputs) -> [u8; 32] {
+    solana_sha256_hasher::hash(&preimage(recipe, forger, inputs)).to_bytes()
+}
+
+/// Bytes hashed by [`compute_input_hash`].
+pub fn preimage(recipe: &[u8; 32], forger: &[u8; 32], inputs: &ForgeInputs) -> Vec<u8> {
+    let ingredients_len = 2
+        + inputs.chunks.iter().map(|c| 2 + c.len()).sum::<usize>()
+        + 2
+        + inputs.accounts.len() * 32;
+    let mut preimage =
+        Vec::with_capacity(INPUT_HASH_DOMAIN.len() + 2 + 32 + 32 + 8 + ingredients_len);
+    preimage.extend_from_slice(INPUT_HASH_DOMAIN);
+    preimage.push(INPUT_HASH_VERSION);
+    preimage.push(inputs.replay_policy.tag());
+    preimage.extend_from_slice(recipe);
+    match inputs.replay_policy {
+        ReplayPolicy::OncePerWallet => {
+            preimage.extend_from_slice(forger);
+            if inputs.authorized_nonce {
+                preimage.extend_from_slice(&inputs.nonce.to_le_bytes());
+            }
+        }
+        ReplayPolicy::OncePerInputSet => append_ingredients(&mut preimage, inputs),
+        ReplayPolicy::Unlimited => {
+            preimage.extend_from_slice(forger);
+            preimage.extend_from_slice(&inputs.nonce.to_le_bytes());
+            append_ingredients(&mut preimage, inputs);
+        }
+    }
+    preimage
+}
+
+/// Appends the length-prefixed chunks and inputs of the `ingredients` section.
+fn append_ingredients(preimage: &mut Vec<u8>, inputs: &ForgeInputs) {
+    preimage.extend_from_slice(&(inputs.chunks.len() as u16).to_le_bytes());
+    for chunk in inputs.chunks {
+        preimage.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+        preimage.extend_from_slice(chunk);
+    }
+    preimage.extend_from_slice(&(inputs.accounts.len() as u16).to_le_bytes());
+    for account in inputs.accounts {
+        preimage.extend_from_slice(account);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::{string::String, vec};
+    use core::fmt::Write;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+    }
+
+    const RECIPE: [u8; 32] = [1; 32];
+    const FORGER: [u8; 32] = [2; 32];
+
+    /// Golden vectors pinning schema v1; clients in other languages must
+    /// reproduce these exactly.
+    #[test]
+    fn golden_vectors() {
+        let cases: [(ForgeInputs, &str); 5] = [
+            (
+                ForgeInputs {
+                    replay_policy: ReplayPolicy::OncePerWallet,
+                    nonce: 7,
+                    authorized_nonce: false,
+                    chunks: &[chunk::signer(&[3; 32])],
+                    accounts: &[[3; 32]],
+                },
+                "f76c7a06c0eab295625f146421d6336c6f7ed65b4bc6cb0a5cff091afd6b7520",
+            ),
+            (
+                ForgeInputs {
+                    replay_policy: ReplayPolicy::OncePerInputSet,
+                    nonce: 7,
+                    authorized_nonce: false,
+                    chunks: &[chunk::token_mint(&[4; 32], 3)],
+                    accounts: &[[5; 32]],
+                },
+                "708cdad982ab722a99ae0f2fea480e6a3ea983ce5f4f53d1fa61585f4b729796",
+            ),
+            (
+                ForgeInputs {
+                    replay_policy: ReplayPolicy::Unlimited,
+                    nonce: 7,
+                    authorized_nonce: false,
+                    chunks: &[
+                        chunk::composite(chunk::tag::ANY_OF, 2, 0b10, &[chunk::signer(&FORGER)]),
+                        chunk::allowlist(&[6; 32]),
+                    ],
+                    accounts: &[FORGER, [7; 32]],
+                },
+                "c60940a2e1f1bc704c62c17fdc56dca12245846f13e0c1005a450ec64bd70e97",
+            ),
+            (
+                ForgeInputs {
+                    replay_policy: ReplayPolicy::Unlimited,
+                    nonce: u64::MAX,
+                    authorized_nonce: false,
+                    chunks: &[chunk::evolve_target(&[8; 32], 3)],
+                    accounts: &[],
+                },
+                "d0f566fba9e6607d9cd915801deea148019e8330cce76bfa57688db19ae746a1",
+            ),
+            (
+                ForgeInputs {
+                    replay_policy: ReplayPolicy::OncePerWallet,
+                    nonce: 9,
+                    authorized_nonce: true,
+                    chunks: &[chunk::signed_authorization(&[3; 32], 9, 100)],
+                    accounts: &[[3; 32]],
+                },
+                "4ce9e8897833685df687a0025c1f582dc1c6b5e0116bf51e32ae28245d2a7bf9",
+            ),
+        ];
+        for (inputs, expected) in cases {
+            assert_eq!(
+                hex(&compute_input_hash(&RECIPE, &FORGER, &inputs)),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn policies_bind_what_they_promise() {
+        let chunks = [chunk::token_mint(&[4; 32], 3)];
+        let hash = |replay_policy, forger: &[u8; 32], nonce, accounts: &[[u8; 32]]| {
+            compute_input_hash(
+                &RECIPE,
+                forger,
+                &ForgeInputs {
+                    replay_policy,
+                    nonce,
+                    authorized_nonce: false,
+                    chunks: &chunks,
+                    accounts,
+                },
+            )
+        };
+        let (alice, bob) = ([10; 32], [11; 32]);
+        let (ours, theirs) = ([[20; 32]], [[21; 32]]);
+
+        // Once per wallet: only the forger matters.
+        let per_wallet = ReplayPolicy::OncePerWallet;
+        assert_eq!(
+            hash(per_wallet, &alice, 1, &ours),
+            hash(per_wallet, &alice, 2, &theirs)
+        );
+        assert_ne!(
+            hash(per_wallet, &alice, 1, &ours),
+            hash(per_wallet, &bob, 1, &ours)
+        );
+
+        // ...unless an authority signed the nonce, which then scopes the record.
+        let authorized = |nonce| {
+            compute_input_hash(
+                &RECIPE,
+                &alice,
+                &ForgeInputs {
+                    replay_policy: per_wallet,
+                    nonce,
+                    authorized_nonce: true,
+                    chunks: &chunks,
+                    accounts: &ours,
+                },
+            )
+        };
+        assert_ne!(authorized(1), authorized(2));
+        assert_ne!(authorized(1), hash(per_wallet, &alice, 1, &ours));
+
+        // Once per input set: only the concrete inputs matter.
+        let per_set = ReplayPolicy::OncePerInputSet;
+        assert_eq!(
+            hash(per_set, &alice, 1, &ours),
+            hash(per_set, &bob, 2, &ours)
+        );
+        assert_ne!(
+            hash(per_set, &alice, 1, &ours),
+            hash(per_set, &alice, 1, &theirs)
+        );
+
+        // Unlimited: a fresh nonce always yields a fresh record.
+        let unlimited = ReplayPolicy::Unlimited;
+        assert_ne!(
+            hash(unlimited, &alice, 1, &ours),
+            hash(unlimited, &alice, 2, &ours)
+        );
+        assert_ne!(
+            hash(unlimited, &alice, 1, &ours),
+            hash(unlimited, &bob, 1, &ours)
+        );
+    }
+
+    #[test]
+    fn chunks_lead_with_their_tag() {
+        let key = [9; 32];
+        let mut seeds = vec![chunk::tag::CUSTOM_SEEDS];
+        seeds.extend_from_slice(&key);
+        seeds.push(1);
+        seeds.extend_from_slice(&key);
+        assert_eq!(chunk::custom_seeds(&key, true, &key), seeds);
+
+        let mut authorization = vec![chunk::tag::SIGNED_AUTHORIZATION];
+        authorization.extend_from_slice(&key);
+        authorization.extend_from_slice(&5u64.to_le_bytes());
+        authorization.extend_from_slice(&(-1i64).to_le_bytes());
+        assert_eq!(chunk::signed_authorization(&key, 5, -1), authorization);
+
+        let mut forged = vec![chunk::tag::FORGED_OUTPUT];
+        forged.extend_from_slice(&key);
+        forged.extend_from_slice(&[3; 32]);
+        forged.push(0);
+        assert_eq!(chunk::forged_output(&key, &[3; 32], false), forged);
+
+        assert_eq!(chunk::collection_nft(&key)[0], chunk::tag::COLLECTION_NFT);
+        assert_eq!(
+            chunk::composite(chunk::tag::AT_LEAST, 3, 0b101, &[]),
+            vec![chunk::tag::AT_LEAST, 3, 0b101, 0]
+        );
+    }
+}
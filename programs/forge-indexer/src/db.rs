@@ -0,0 +1,925 @@
+//! The SQLite database events are indexed into.
+//!
+//! Every transaction indexed is recorded in `transactions`, so indexing the
+//! same transaction twice is a no-op, and each decoded event in `events`,
+//! with its forge and per-forge sequence number. Events also update the
+//! tables queried by clients:
+//!
+//! - `forges`: the latest configuration of each forge.
+//! - `recipes`: slug, version and latest status of each recipe.
+//! - `recipe_changes`: one row per field a `RecipeUpdated` changed, with its
+//!   values before and after, in their `Debug` form.
+//! - `mints`: the per-recipe mint history, one row per `AssetForged`,
+//!   `BatchForged` or `AssetEvolved` with the event's name and the number of
+//!   assets it minted or evolved in `units`.
+//! - `recipe_stats` and `forger_stats`: views summarizing `mints` per recipe
+//!   and per forger.
+//!
+//! Older databases are migrated in place when opened. Version 1 events
+//! predate sequence numbers and keep `schema_version` and `sequence` 0; their
+//! forge is filled in from `recipes` where the event names a recipe. Mints
+//! of versions 1 and 2 are all `AssetForged` rows of one unit.
+
+use std::{fmt::Debug, path::Path};
+
+use anchor_lang::prelude::Pubkey;
+use forge_client::forge::instructions::UpdateRecipeArgs;
+use rusqlite::{params, Connection, OptionalExtension, Params, Transaction};
+
+use crate::{
+    logs::{parse_logs, ForgeEvent},
+    source::IndexedTransaction,
+    IndexError, Result,
+};
+
+/// Version of the schema below, stored as the database's `user_version`.
+pub const SCHEMA_VERSION: i64 = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    failed INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    name TEXT NOT NULL,
+    forge_config TEXT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    sequence INTEGER NOT NULL,
+    recipe TEXT,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_by_forge ON events (forge_config, sequence);
+CREATE TABLE IF NOT EXISTS forges (
+    forge_config TEXT PRIMARY KEY,
+    authority TEXT NOT NULL,
+    collection_mint TEXT,
+    freeze_authority TEXT,
+    default_royalty_bps INTEGER NOT NULL,
+    recipe_creation_enabled INTEGER NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS recipes (
+    recipe TEXT PRIMARY KEY,
+    forge_config TEXT NOT NULL,
+    slug TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    created_slot INTEGER NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS recipe_changes (
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    event_index INTEGER NOT NULL,
+    recipe TEXT NOT NULL,
+    field TEXT NOT NULL,
+    before TEXT NOT NULL,
+    after TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index, field)
+);
+CREATE TABLE IF NOT EXISTS mints (
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    forge_config TEXT NOT NULL,
+    recipe TEXT NOT NULL,
+    forger TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    minted_count INTEGER NOT NULL,
+    supply_cap INTEGER,
+    input_hash TEXT NOT NULL,
+    trait_tier INTEGER,
+    event TEXT NOT NULL DEFAULT 'AssetForged',
+    units INTEGER NOT NULL DEFAULT 1,
+    level INTEGER,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS mints_by_recipe ON mints (recipe, minted_count);
+CREATE INDEX IF NOT EXISTS mints_by_forger ON mints (forger, slot);
+CREATE VIEW IF NOT EXISTS recipe_stats AS
+    SELECT mints.recipe, recipes.slug, recipes.version,
+        SUM(mints.units) AS forged, MAX(mints.minted_count) AS minted,
+        COUNT(DISTINCT mints.forger) AS forgers,
+        MIN(mints.slot) AS first_slot, MAX(mints.slot) AS last_slot
+    FROM mints LEFT JOIN recipes ON recipes.recipe = mints.recipe
+    GROUP BY mints.recipe;
+CREATE VIEW IF NOT EXISTS forger_stats AS
+    SELECT forger, SUM(units) AS forged, COUNT(DISTINCT recipe) AS recipes,
+        MIN(slot) AS first_slot, MAX(slot) AS last_slot,
+        MIN(block_time) AS first_block_time, MAX(block_time) AS last_block_time
+    FROM mints
+    GROUP BY forger;
+";
+
+/// Upgrades a version 1 database, which lacked the event forge and sequence
+/// columns, before [`SCHEMA`] adds the tables and indexes it lacked.
+const MIGRATE_FROM_V1: &str = "
+ALTER TABLE events ADD COLUMN forge_config TEXT NOT NULL DEFAULT '';
+ALTER TABLE events ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN sequence INTEGER NOT NULL DEFAULT 0;
+UPDATE events SET forge_config = COALESCE(
+    (SELECT forge_config FROM recipes WHERE recipes.recipe = events.recipe), ''
+);
+";
+
+/// Upgrades a version 2 database, whose mints were all single
+/// `AssetForged` units. The views are recreated by [`SCHEMA`] to sum units.
+const MIGRATE_FROM_V2: &str = "
+DROP VIEW IF EXISTS recipe_stats;
+DROP VIEW IF EXISTS forger_stats;
+ALTER TABLE mints ADD COLUMN event TEXT NOT NULL DEFAULT 'AssetForged';
+ALTER TABLE mints ADD COLUMN units INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE mints ADD COLUMN level INTEGER;
+";
+
+/// Assets minted, or evolved, by one event of a recipe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// `AssetForged`, `BatchForged` or `AssetEvolved`.
+    pub event: String,
+    pub forger: String,
+    /// Output mint; for compressed batches, the Merkle tree.
+    pub mint: String,
+    /// Assets the event minted or evolved: the batch size for `BatchForged`,
+    /// otherwise 1.
+    pub units: u64,
+    /// The recipe's `minted` counter after the event.
+    pub minted_count: u64,
+    pub supply_cap: Option<u64>,
+    pub trait_tier: Option<u8>,
+    /// Level the asset reached, for `AssetEvolved`.
+    pub level: Option<u16>,
+}
+
+/// What a forger has forged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForgerStats {
+    pub forged: u64,
+    /// Number of distinct recipes forged from.
+    pub recipes: u64,
+    pub first_slot: u64,
+    pub last_slot: u64,
+}
+
+/// Counts of what one [`Database::index`] call added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexSummary {
+    pub transactions: usize,
+    /// Transactions skipped because they were already indexed.
+    pub skipped: usize,
+    pub events: usize,
+}
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let db = conn.transaction()?;
+        match version {
+            0 | SCHEMA_VERSION => {}
+            1 => {
+                db.execute_batch(MIGRATE_FROM_V1)?;
+                db.execute_batch(MIGRATE_FROM_V2)?;
+            }
+            2 => db.execute_batch(MIGRATE_FROM_V2)?,
+            _ => return Err(IndexError::Schema(version)),
+        }
+        db.execute_batch(SCHEMA)?;
+        db.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        db.commit()?;
+        Ok(Self { conn })
+    }
+
+    /// Indexes the events `program_id` emitted in `transactions`, each in
+    /// its own database transaction.
+    pub fn index(
+        &mut self,
+        program_id: &Pubkey,
+        transactions: &[IndexedTransaction],
+    ) -> Result<IndexSummary> {
+        let mut summary = IndexSummary::default();
+        for transaction in transactions {
+            let db = self.conn.transaction()?;
+            let inserted = db.execute(
+                "INSERT OR IGNORE INTO transactions (signature, slot, block_time, failed)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    transaction.signature,
+                    transaction.slot as i64,
+                    transaction.block_time,
+                    transaction.failed
+                ],
+            )?;
+            if inserted == 0 {
+                summary.skipped += 1;
+                continue;
+            }
+            if !transaction.failed {
+                let events = parse_logs(program_id, &transaction.logs);
+                for (index, event) in events.iter().enumerate() {
+                    insert_event(&db, transaction, index, event)?;
+                }
+                summary.events += events.len();
+            }
+            db.commit()?;
+            summary.transactions += 1;
+        }
+        Ok(summary)
+    }
+
+    /// Signature of the newest transaction indexed, to resume from.
+    pub fn latest_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY slot DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Every mint and evolution of `recipe`, in mint order.
+    pub fn recipe_history(&self, recipe: &Pubkey) -> Result<Vec<MintRecord>> {
+        let mut statement = self.conn.prepare(
+            "SELECT signature, slot, block_time, event, forger, mint, units, minted_count,
+                 supply_cap, trait_tier, level
+             FROM mints WHERE recipe = ?1 ORDER BY minted_count, slot",
+        )?;
+        let rows = statement.query_map([recipe.to_string()], |row| {
+            Ok(MintRecord {
+                signature: row.get(0)?,
+                slot: row.get::<_, i64>(1)? as u64,
+                block_time: row.get(2)?,
+                event: row.get(3)?,
+                forger: row.get(4)?,
+                mint: row.get(5)?,
+                units: row.get::<_, i64>(6)? as u64,
+                minted_count: row.get::<_, i64>(7)? as u64,
+                supply_cap: row.get::<_, Option<i64>>(8)?.map(|cap| cap as u64),
+                trait_tier: row.get(9)?,
+                level: row.get(10)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// What `forger` has forged, or `None` if nothing.
+    pub fn forger_stats(&self, forger: &Pubkey) -> Result<Option<ForgerStats>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT forged, recipes, first_slot, last_slot FROM forger_stats
+                 WHERE forger = ?1",
+                [forger.to_string()],
+                |row| {
+                    Ok(ForgerStats {
+                        forged: row.get::<_, i64>(0)? as u64,
+                        recipes: row.get::<_, i64>(1)? as u64,
+                        first_slot: row.get::<_, i64>(2)? as u64,
+                        last_slot: row.get::<_, i64>(3)? as u64,
+                    })
+                },
+            )
+            .optional()?)
+    }
+}
+
+fn insert_event(
+    db: &Transaction,
+    transaction: &IndexedTransaction,
+    index: usize,
+    event: &ForgeEvent,
+) -> Result<()> {
+    let slot = transaction.slot as i64;
+    let (schema_version, sequence) = event.sequence();
+    db.execute(
+        "INSERT INTO events (signature, event_index, slot, block_time, name, forge_config,
+             schema_version, sequence, recipe)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            transaction.signature,
+            index as i64,
+            slot,
+            transaction.block_time,
+            event.name(),
+            event.forge_config().to_string(),
+            schema_version,
+            sequence as i64,
+            event.recipe().map(|recipe| recipe.to_string())
+        ],
+    )?;
+
+    match event {
+        ForgeEvent::ForgeInitialized(event) => upsert_forge(
+            db,
+            params![
+                event.forge_config.to_string(),
+                event.authority.to_string(),
+                event.collection_mint.map(|key| key.to_string()),
+                event.freeze_authority.map(|key| key.to_string()),
+                event.default_royalty_bps,
+                event.recipe_creation_enabled,
+                slot
+            ],
+        )?,
+        ForgeEvent::ForgeConfigUpdated(event) => upsert_forge(
+            db,
+            params![
+                event.forge_config.to_string(),
+                event.authority.to_string(),
+                event.collection_mint.map(|key| key.to_string()),
+                event.freeze_authority.map(|key| key.to_string()),
+                event.default_royalty_bps,
+                event.recipe_creation_enabled,
+                slot
+            ],
+        )?,
+        ForgeEvent::RecipeCreated(event) => {
+            db.execute(
+                "INSERT OR REPLACE INTO recipes
+                     (recipe, forge_config, slug, version, status, created_slot, updated_slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                params![
+                    event.recipe.to_string(),
+                    event.forge_config.to_string(),
+                    event.slug,
+                    event.version,
+                    format!("{:?}", event.status),
+                    slot
+                ],
+            )?;
+        }
+        ForgeEvent::RecipeUpdated(event) => {
+            db.execute(
+                "UPDATE recipes SET updated_slot = ?2 WHERE recipe = ?1 AND updated_slot <= ?2",
+                params![event.recipe.to_string(), slot],
+            )?;
+            for ((field, before), (_, after)) in set_fields(&event.before)
+                .into_iter()
+                .zip(set_fields(&event.after))
+            {
+                db.execute(
+                    "INSERT INTO recipe_changes (signature, event_index, recipe, field, before,
+                         after)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        transaction.signature,
+                        index as i64,
+                        event.recipe.to_string(),
+                        field,
+                        before,
+                        after
+                    ],
+                )?;
+            }
+        }
+        ForgeEvent::RecipeStatusChanged(event) => {
+            db.execute(
+                "UPDATE recipes SET status = ?2, updated_slot = ?3
+                 WHERE recipe = ?1 AND updated_slot <= ?3",
+                params![event.recipe.to_string(), format!("{:?}", event.next), slot],
+            )?;
+        }
+        ForgeEvent::AssetForged(event) => insert_mint(
+            db,
+            transaction,
+            index,
+            MintRow {
+                forge_config: event.forge_config,
+                recipe: event.recipe,
+                forger: event.forger,
+                mint: event.mint,
+                units: 1,
+                minted_count: event.minted_count,
+                supply_cap: event.supply_cap,
+                input_hash: event.input_hash,
+                trait_tier: event.trait_tier,
+                level: None,
+                event: "AssetForged",
+            },
+        )?,
+        ForgeEvent::BatchForged(event) => insert_mint(
+            db,
+            transaction,
+            index,
+            MintRow {
+                forge_config: event.forge_config,
+                recipe: event.recipe,
+                forger: event.forger,
+                mint: event.mint,
+                units: event.count,
+                minted_count: event.minted_count,
+                supply_cap: event.supply_cap,
+                input_hash: event.input_hash,
+                trait_tier: None,
+                level: None,
+                event: "BatchForged",
+            },
+        )?,
+        ForgeEvent::AssetEvolved(event) => insert_mint(
+            db,
+            transaction,
+            index,
+            MintRow {
+                forge_config: event.forge_config,
+                recipe: event.recipe,
+                forger: event.forger,
+                mint: event.mint,
+                units: 1,
+                minted_count: event.evolved_count,
+                supply_cap: None,
+                input_hash: event.input_hash,
+                trait_tier: None,
+                level: Some(event.level),
+                event: "AssetEvolved",
+            },
+        )?,
+    }
+    Ok(())
+}
+
+/// The event-specific columns of a `mints` row.
+struct MintRow {
+    forge_config: Pubkey,
+    recipe: Pubkey,
+    forger: Pubkey,
+    mint: Pubkey,
+    units: u64,
+    minted_count: u64,
+    supply_cap: Option<u64>,
+    input_hash: [u8; 32],
+    trait_tier: Option<u8>,
+    level: Option<u16>,
+    event: &'static str,
+}
+
+fn insert_mint(
+    db: &Transaction,
+    transaction: &IndexedTransaction,
+    index: usize,
+    row: MintRow,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO mints (signature, event_index, slot, block_time, forge_config, recipe,
+             forger, mint, minted_count, supply_cap, input_hash, trait_tier, event, units,
+             level)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            transaction.signature,
+            index as i64,
+            transaction.slot as i64,
+            transaction.block_time,
+            row.forge_config.to_string(),
+            row.recipe.to_string(),
+            row.forger.to_string(),
+            row.mint.to_string(),
+            row.minted_count as i64,
+            row.supply_cap.map(|cap| cap as i64),
+            hex::encode(row.input_hash),
+            row.trait_tier,
+            row.event,
+            row.units as i64,
+            row.level
+        ],
+    )?;
+    Ok(())
+}
+
+/// The fields `args` sets, by name, with their values' `Debug` form.
+fn set_fields(args: &UpdateRecipeArgs) -> Vec<(&'static str, String)> {
+    fn field<T: Debug>(
+        fields: &mut Vec<(&'static str, String)>,
+        name: &'static str,
+        value: &Option<T>,
+    ) {
+        if let Some(value) = value {
+            fields.push((name, format!("{value:?}")));
+        }
+    }
+
+    let mut fields = Vec::new();
+    field(&mut fields, "metadata_uri", &args.metadata_uri);
+    field(&mut fields, "creators", &args.creators);
+    field(&mut fields, "collection_mint", &args.collection_mint);
+    field(&mut fields, "go_live_unix_time", &args.go_live_unix_time);
+    field(
+        &mut fields,
+        "ingredient_constraints",
+        &args.ingredient_constraints,
+    );
+    field(&mut fields, "supply_cap", &args.supply_cap);
+    field(&mut fields, "output_kind", &args.output_kind);
+    field(&mut fields, "pause_at", &args.pause_at);
+    field(&mut fields, "retire_at", &args.retire_at);
+    field(&mut fields, "trait_table", &args.trait_table);
+    field(&mut fields, "extra_outputs", &args.extra_outputs);
+    field(&mut fields, "mode", &args.mode);
+    field(&mut fields, "reversible", &args.reversible);
+    field(&mut fields, "replay_policy", &args.replay_policy);
+    fields
+}
+
+/// Records a forge's configuration, given as `(forge_config, authority,
+/// collection_mint, freeze_authority, default_royalty_bps,
+/// recipe_creation_enabled, slot)`, unless a later one is already stored.
+fn upsert_forge(db: &Transaction, forge: impl Params) -> Result<()> {
+    db.execute(
+        "INSERT INTO forges (forge_config, authority, collection_mint, freeze_authority,
+             default_royalty_bps, recipe_creation_enabled, updated_slot)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (forge_config) DO UPDATE SET
+             authority = excluded.authority,
+             collection_mint = excluded.collection_mint,
+             freeze_authority = excluded.freeze_authority,
+             default_royalty_bps = excluded.default_royalty_bps,
+             recipe_creation_enabled = excluded.recipe_creation_enabled,
+             updated_slot = excluded.updated_slot
+         WHERE excluded.updated_slot >= forges.updated_slot",
+        forge,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use forge_client::forge::{
+        events::{
+            AssetEvolved, AssetForged, BatchForged, RecipeCreated, RecipeStatusChanged,
+            RecipeUpdated, EVENT_SCHEMA_VERSION,
+        },
+        state::RecipeStatus,
+        ID,
+    };
+
+    fn transaction(signature: &str, slot: u64, events: &[Vec<u8>]) -> IndexedTransaction {
+        let mut logs = vec![format!("Program {ID} invoke [1]")];
+        logs.extend(
+            events
+                .iter()
+                .map(|data| format!("Program data: {}", STANDARD.encode(data))),
+        );
+        logs.push(format!("Program {ID} success"));
+        IndexedTransaction {
+            signature: signature.into(),
+            slot,
+            block_time: Some(1_767_225_600 + slot as i64),
+            failed: false,
+            logs,
+        }
+    }
+
+    fn forged(recipe: Pubkey, forger: Pubkey, minted_count: u64) -> Vec<u8> {
+        AssetForged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 10 + minted_count,
+            forge_config: Pubkey::default(),
+            recipe,
+            forger,
+            mint: Pubkey::new_unique(),
+            minted_count,
+            supply_cap: Some(10),
+            input_hash: [minted_count as u8; 32],
+            trait_tier: None,
+        }
+        .data()
+    }
+
+    #[test]
+    fn indexes_mint_history_and_forger_stats() {
+        let mut db = Database::open_in_memory().unwrap();
+        let (sword, shield) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let created = RecipeCreated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 1,
+            forge_config: Pubkey::default(),
+            recipe: sword,
+            slug: "sword".into(),
+            version: 1,
+            status: RecipeStatus::Draft,
+        }
+        .data();
+        let updated = RecipeUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 2,
+            forge_config: Pubkey::default(),
+            recipe: sword,
+            slug: "sword".into(),
+            version: 1,
+            before: UpdateRecipeArgs {
+                metadata_uri: Some("ipfs://old".into()),
+                supply_cap: Some(None),
+                ..Default::default()
+            },
+            after: UpdateRecipeArgs {
+                metadata_uri: Some("ipfs://new".into()),
+                supply_cap: Some(Some(10)),
+                ..Default::default()
+            },
+        }
+        .data();
+        let activated = RecipeStatusChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 3,
+            forge_config: Pubkey::default(),
+            recipe: sword,
+            previous: RecipeStatus::Draft,
+            next: RecipeStatus::Active,
+        }
+        .data();
+        let mut failed = transaction("failed", 12, &[forged(sword, bob, 9)]);
+        failed.failed = true;
+        let transactions = vec![
+            transaction("create", 10, &[created, updated, activated]),
+            transaction("one", 11, &[forged(sword, alice, 1)]),
+            failed,
+            transaction(
+                "two",
+                13,
+                &[forged(sword, bob, 2), forged(shield, alice, 1)],
+            ),
+        ];
+
+        let summary = db.index(&ID, &transactions).unwrap();
+        assert_eq!(
+            summary,
+            IndexSummary {
+                transactions: 4,
+                skipped: 0,
+                events: 6,
+            }
+        );
+        assert_eq!(
+            db.index(&ID, &transactions[..2]).unwrap(),
+            IndexSummary {
+                transactions: 0,
+                skipped: 2,
+                events: 0,
+            }
+        );
+
+        let history = db.recipe_history(&sword).unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|mint| (mint.signature.as_str(), mint.minted_count))
+                .collect::<Vec<_>>(),
+            vec![("one", 1), ("two", 2)]
+        );
+        assert_eq!(history[1].forger, bob.to_string());
+        assert_eq!(
+            db.forger_stats(&alice).unwrap(),
+            Some(ForgerStats {
+                forged: 2,
+                recipes: 2,
+                first_slot: 11,
+                last_slot: 13,
+            })
+        );
+        assert_eq!(db.forger_stats(&Pubkey::new_unique()).unwrap(), None);
+        let status: String = db
+            .conn
+            .query_row(
+                "SELECT status FROM recipes WHERE recipe = ?1",
+                [sword.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(status, "Active");
+        let mut statement = db
+            .conn
+            .prepare("SELECT field, before, after FROM recipe_changes ORDER BY field")
+            .unwrap();
+        let changes = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<(String, String, String)>>>()
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "metadata_uri".into(),
+                    "\"ipfs://old\"".into(),
+                    "\"ipfs://new\"".into()
+                ),
+                ("supply_cap".into(), "None".into(), "Some(10)".into()),
+            ]
+        );
+        let sequences: Vec<i64> = db
+            .conn
+            .prepare("SELECT sequence FROM events ORDER BY slot, event_index")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(sequences, vec![1, 2, 3, 11, 12, 11]);
+        assert_eq!(db.latest_signature().unwrap().as_deref(), Some("two"));
+    }
+
+    #[test]
+    fn indexes_batches_and_evolutions_as_mints() {
+        let mut db = Database::open_in_memory().unwrap();
+        let (coins, upgrade) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let output_mint = Pubkey::new_unique();
+        let batch = BatchForged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 4,
+            forge_config: Pubkey::default(),
+            recipe: coins,
+            forger: alice,
+            mint: output_mint,
+            count: 5,
+            minted_count: 5,
+            supply_cap: Some(100),
+            input_hash: [1; 32],
+        }
+        .data();
+        let evolved = AssetEvolved {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 5,
+            forge_config: Pubkey::default(),
+            recipe: upgrade,
+            forger: alice,
+            mint: Pubkey::new_unique(),
+            level: 2,
+            evolved_count: 1,
+            input_hash: [2; 32],
+        }
+        .data();
+        let transactions = vec![
+            transaction("batch", 20, &[batch]),
+            transaction("evolve", 21, &[evolved]),
+            transaction("single", 22, &[forged(coins, bob, 6)]),
+        ];
+
+        let summary = db.index(&ID, &transactions).unwrap();
+        assert_eq!(summary.events, 3);
+
+        let history = db.recipe_history(&coins).unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|mint| (mint.event.as_str(), mint.units, mint.minted_count))
+                .collect::<Vec<_>>(),
+            vec![("BatchForged", 5, 5), ("AssetForged", 1, 6)]
+        );
+        assert_eq!(history[0].mint, output_mint.to_string());
+        assert_eq!(history[0].supply_cap, Some(100));
+
+        let evolutions = db.recipe_history(&upgrade).unwrap();
+        assert_eq!(evolutions.len(), 1);
+        assert_eq!(evolutions[0].event, "AssetEvolved");
+        assert_eq!((evolutions[0].units, evolutions[0].level), (1, Some(2)));
+
+        assert_eq!(
+            db.forger_stats(&alice).unwrap(),
+            Some(ForgerStats {
+                forged: 6,
+                recipes: 2,
+                first_slot: 20,
+                last_slot: 21,
+            })
+        );
+        let (forged, minted): (i64, i64) = db
+            .conn
+            .query_row(
+                "SELECT forged, minted FROM recipe_stats WHERE recipe = ?1",
+                [coins.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((forged, minted), (6, 6));
+    }
+
+    #[test]
+    fn migrates_version_1_databases() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE transactions (
+                signature TEXT PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                block_time INTEGER,
+                failed INTEGER NOT NULL
+            );
+            CREATE TABLE events (
+                signature TEXT NOT NULL REFERENCES transactions (signature),
+                event_index INTEGER NOT NULL,
+                slot INTEGER NOT NULL,
+                block_time INTEGER,
+                name TEXT NOT NULL,
+                recipe TEXT,
+                PRIMARY KEY (signature, event_index)
+            );
+            CREATE TABLE recipes (
+                recipe TEXT PRIMARY KEY,
+                forge_config TEXT NOT NULL,
+                slug TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                created_slot INTEGER NOT NULL,
+                updated_slot INTEGER NOT NULL
+            );
+            CREATE TABLE mints (
+                signature TEXT NOT NULL REFERENCES transactions (signature),
+                event_index INTEGER NOT NULL,
+                slot INTEGER NOT NULL,
+                block_time INTEGER,
+                forge_config TEXT NOT NULL,
+                recipe TEXT NOT NULL,
+                forger TEXT NOT NULL,
+                mint TEXT NOT NULL,
+                minted_count INTEGER NOT NULL,
+                supply_cap INTEGER,
+                input_hash TEXT NOT NULL,
+                trait_tier INTEGER,
+                PRIMARY KEY (signature, event_index)
+            );
+            CREATE VIEW forger_stats AS
+                SELECT forger, COUNT(*) AS forged, COUNT(DISTINCT recipe) AS recipes,
+                    MIN(slot) AS first_slot, MAX(slot) AS last_slot,
+                    MIN(block_time) AS first_block_time, MAX(block_time) AS last_block_time
+                FROM mints
+                GROUP BY forger;
+            INSERT INTO transactions VALUES ('old', 5, NULL, 0);
+            INSERT INTO recipes VALUES ('sword', 'forge', 'sword', 1, 'Active', 5, 5);
+            INSERT INTO events VALUES ('old', 0, 5, NULL, 'RecipeCreated', 'sword');
+            INSERT INTO events VALUES ('old', 1, 5, NULL, 'ForgeConfigUpdated', NULL);
+            INSERT INTO events VALUES ('old', 2, 5, NULL, 'AssetForged', 'sword');
+            INSERT INTO mints VALUES ('old', 2, 5, NULL, 'forge', 'sword', 'alice', 'mint', 1,
+                NULL, '00', NULL);
+            PRAGMA user_version = 1;",
+        )
+        .unwrap();
+
+        let mut db = Database::init(conn).unwrap();
+        let events: Vec<(String, i64, i64)> = db
+            .conn
+            .prepare(
+                "SELECT forge_config, schema_version, sequence FROM events ORDER BY event_index",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                ("forge".into(), 0, 0),
+                (String::new(), 0, 0),
+                ("forge".into(), 0, 0)
+            ]
+        );
+        let (event, units, forger_total): (String, i64, i64) = db
+            .conn
+            .query_row(
+                "SELECT event, units, forged FROM mints
+                 JOIN forger_stats ON forger_stats.forger = mints.forger",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((event.as_str(), units, forger_total), ("AssetForged", 1, 1));
+
+        let (sword, alice) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let summary = db
+            .index(&ID, &[transaction("new", 6, &[forged(sword, alice, 1)])])
+            .unwrap();
+        assert_eq!(summary.events, 1);
+        assert_eq!(db.latest_signature().unwrap().as_deref(), Some("new"));
+
+        let version: i64 = db
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+}
@@ -0,0 +1,43 @@
+//! Indexes Forge program events into SQLite.
+//!
+//! - [`source`] reads confirmed transactions from a JSON dump or fetches
+//!   them from a validator's RPC.
+//! - [`logs`] decodes the events the Forge program emitted in their logs.
+//! - [`db`] stores the events, the per-recipe mint history and per-forger
+//!   stats.
+//!
+//! ```ignore
+//! let transactions = source::from_json_dump(&fs::read_to_string("dump.json")?)?;
+//! let mut db = Database::open(Path::new("forge.db"))?;
+//! db.index(&forge_client::forge::ID, &transactions)?;
+//! let history = db.recipe_history(&recipe)?;
+//! ```
+
+pub mod db;
+pub mod logs;
+pub mod source;
+
+pub use db::Database;
+
+/// Errors returned while reading and indexing transactions.
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<solana_rpc_client_api::client_error::Error>),
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("database schema version {0} differs from this indexer's {SCHEMA_VERSION}; index into a new database", SCHEMA_VERSION = db::SCHEMA_VERSION)]
+    Schema(i64),
+    #[error("malformed transaction JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Source(String),
+}
+
+impl From<solana_rpc_client_api::client_error::Error> for IndexError {
+    fn from(err: solana_rpc_client_api::client_error::Error) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, IndexError>;
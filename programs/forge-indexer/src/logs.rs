@@ -0,0 +1,303 @@
+//! Decoding Forge events from transaction logs.
+//!
+//! Anchor's `emit!` logs each event as `Program data: <base64>`, where the
+//! data is the event's discriminator followed by its Borsh encoding. Only
+//! data logged while the Forge program is the innermost running program is
+//! decoded, so events of other programs and look-alike logs are skipped.
+//!
+//! Events emitted before schema versions existed (version 0) have neither
+//! `schema_version` nor `sequence`, so their first byte is the start of
+//! `forge_config`, not a version. An event is therefore decoded as the
+//! current layout only if that consumes its data exactly and reads
+//! `EVENT_SCHEMA_VERSION`, and otherwise as its version 0 layout, which must
+//! also consume the data exactly. Version 0 events decode with
+//! `schema_version` and `sequence` 0, and a version 0 `RecipeUpdated` with
+//! no fields set in `before` and `after`. Events of a later version fit
+//! neither layout and are skipped.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use forge_client::forge::events::{
+    AssetEvolved, AssetForged, BatchForged, ForgeConfigUpdated, ForgeInitialized, RecipeCreated,
+    RecipeStatusChanged, RecipeUpdated, EVENT_SCHEMA_VERSION,
+};
+
+mod v0;
+
+/// A Forge event the indexer stores.
+#[derive(Debug)]
+pub enum ForgeEvent {
+    ForgeInitialized(ForgeInitialized),
+    ForgeConfigUpdated(ForgeConfigUpdated),
+    RecipeCreated(RecipeCreated),
+    RecipeUpdated(Box<RecipeUpdated>),
+    RecipeStatusChanged(RecipeStatusChanged),
+    AssetForged(AssetForged),
+    BatchForged(BatchForged),
+    AssetEvolved(AssetEvolved),
+}
+
+impl ForgeEvent {
+    /// Decodes an event from its discriminator and Borsh data, or `None` for
+    /// events the indexer does not store, events of an unknown schema
+    /// version and undecodable data.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (discriminator, rest) = data.split_at_checked(8)?;
+        if discriminator == ForgeInitialized::DISCRIMINATOR {
+            current(rest, |e: &ForgeInitialized| e.schema_version)
+                .or_else(|| legacy::<v0::ForgeInitialized, _>(rest))
+                .map(Self::ForgeInitialized)
+        } else if discriminator == ForgeConfigUpdated::DISCRIMINATOR {
+            current(rest, |e: &ForgeConfigUpdated| e.schema_version)
+                .or_else(|| legacy::<v0::ForgeConfigUpdated, _>(rest))
+                .map(Self::ForgeConfigUpdated)
+        } else if discriminator == RecipeCreated::DISCRIMINATOR {
+            current(rest, |e: &RecipeCreated| e.schema_version)
+                .or_else(|| legacy::<v0::RecipeCreated, _>(rest))
+                .map(Self::RecipeCreated)
+        } else if discriminator == RecipeUpdated::DISCRIMINATOR {
+            current(rest, |e: &RecipeUpdated| e.schema_version)
+                .or_else(|| legacy::<v0::RecipeUpdated, _>(rest))
+                .map(|event| Self::RecipeUpdated(Box::new(event)))
+        } else if discriminator == RecipeStatusChanged::DISCRIMINATOR {
+            current(rest, |e: &RecipeStatusChanged| e.schema_version)
+                .or_else(|| legacy::<v0::RecipeStatusChanged, _>(rest))
+                .map(Self::RecipeStatusChanged)
+        } else if discriminator == AssetForged::DISCRIMINATOR {
+            current(rest, |e: &AssetForged| e.schema_version)
+                .or_else(|| legacy::<v0::AssetForged, _>(rest))
+                .map(Self::AssetForged)
+        } else if discriminator == BatchForged::DISCRIMINATOR {
+            current(rest, |e: &BatchForged| e.schema_version)
+                .or_else(|| legacy::<v0::BatchForged, _>(rest))
+                .map(Self::BatchForged)
+        } else if discriminator == AssetEvolved::DISCRIMINATOR {
+            current(rest, |e: &AssetEvolved| e.schema_version)
+                .or_else(|| legacy::<v0::AssetEvolved, _>(rest))
+                .map(Self::AssetEvolved)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the event, as in the IDL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ForgeInitialized(_) => "ForgeInitialized",
+            Self::ForgeConfigUpdated(_) => "ForgeConfigUpdated",
+            Self::RecipeCreated(_) => "RecipeCreated",
+            Self::RecipeUpdated(_) => "RecipeUpdated",
+            Self::RecipeStatusChanged(_) => "RecipeStatusChanged",
+            Self::AssetForged(_) => "AssetForged",
+            Self::BatchForged(_) => "BatchForged",
+            Self::AssetEvolved(_) => "AssetEvolved",
+        }
+    }
+
+    /// The forge that emitted the event.
+    pub fn forge_config(&self) -> Pubkey {
+        match self {
+            Self::ForgeInitialized(event) => event.forge_config,
+            Self::ForgeConfigUpdated(event) => event.forge_config,
+            Self::RecipeCreated(event) => event.forge_config,
+            Self::RecipeUpdated(event) => event.forge_config,
+            Self::RecipeStatusChanged(event) => event.forge_config,
+            Self::AssetForged(event) => event.forge_config,
+            Self::BatchForged(event) => event.forge_config,
+            Self::AssetEvolved(event) => event.forge_config,
+        }
+    }
+
+    /// `(schema_version, sequence)` of the event; the sequence orders the
+    /// events of one forge.
+    pub fn sequence(&self) -> (u8, u64) {
+        match self {
+            Self::ForgeInitialized(event) => (event.schema_version, event.sequence),
+            Self::ForgeConfigUpdated(event) => (event.schema_version, event.sequence),
+            Self::RecipeCreated(event) => (event.schema_version, event.sequence),
+            Self::RecipeUpdated(event) => (event.schema_version, event.sequence),
+            Self::RecipeStatusChanged(event) => (event.schema_version, event.sequence),
+            Self::AssetForged(event) => (event.schema_version, event.sequence),
+            Self::BatchForged(event) => (event.schema_version, event.sequence),
+            Self::AssetEvolved(event) => (event.schema_version, event.sequence),
+        }
+    }
+
+    /// The recipe the event is about, if any.
+    pub fn recipe(&self) -> Option<Pubkey> {
+        match self {
+            Self::ForgeInitialized(_) | Self::ForgeConfigUpdated(_) => None,
+            Self::RecipeCreated(event) => Some(event.recipe),
+            Self::RecipeUpdated(event) => Some(event.recipe),
+            Self::RecipeStatusChanged(event) => Some(event.recipe),
+            Self::AssetForged(event) => Some(event.recipe),
+            Self::BatchForged(event) => Some(event.recipe),
+            Self::AssetEvolved(event) => Some(event.recipe),
+        }
+    }
+}
+
+/// Decodes `data` as a current-layout `T` that consumes it exactly and reads
+/// `EVENT_SCHEMA_VERSION`.
+fn current<T: AnchorDeserialize>(data: &[u8], schema_version: impl Fn(&T) -> u8) -> Option<T> {
+    T::try_from_slice(data)
+        .ok()
+        .filter(|event| schema_version(event) == EVENT_SCHEMA_VERSION)
+}
+
+/// Decodes `data` as a version 0 `T` that consumes it exactly, converted to
+/// the current layout.
+fn legacy<T: AnchorDeserialize + Into<U>, U>(data: &[u8]) -> Option<U> {
+    T::try_from_slice(data).ok().map(Into::into)
+}
+
+/// Decodes the events `program_id` emitted in a transaction's `logs`, in
+/// emission order.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Vec<ForgeEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        let Some(rest) = log.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let Some(Ok(bytes)) = data.split(' ').next().map(|field| STANDARD.decode(field)) else {
+                continue;
+            };
+            events.extend(ForgeEvent::decode(&bytes));
+            continue;
+        }
+        let mut words = rest.split(' ');
+        match (words.next(), words.next()) {
+            (Some(program), Some("invoke")) => stack.push(program),
+            (Some(_), Some("success" | "failed:")) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AnchorSerialize, Event};
+    use forge_client::forge::state::RecipeStatus;
+
+    fn data_log(event: &impl Event) -> String {
+        format!("Program data: {}", STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn decodes_events_of_the_forge_program_only() {
+        let forge = forge_client::forge::ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let recipe = Pubkey::new_unique();
+        let created = RecipeCreated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 3,
+            forge_config: Pubkey::new_unique(),
+            recipe,
+            slug: "sword".into(),
+            version: 1,
+            status: RecipeStatus::Draft,
+        };
+        let changed = RecipeStatusChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: 4,
+            forge_config: created.forge_config,
+            recipe,
+            previous: RecipeStatus::Draft,
+            next: RecipeStatus::Active,
+        };
+        let logs = vec![
+            format!("Program {forge} invoke [1]"),
+            "Program log: Instruction: CreateRecipe".to_string(),
+            format!("Program {other} invoke [2]"),
+            data_log(&changed),
+            format!("Program {other} success"),
+            data_log(&created),
+            "Program data: not base64".to_string(),
+            format!("Program {forge} consumed 5000 of 200000 compute units"),
+            format!("Program {forge} success"),
+            data_log(&changed),
+        ];
+
+        let events = parse_logs(&forge_client::forge::ID, &logs);
+        assert_eq!(events.len(), 1);
+        let ForgeEvent::RecipeCreated(event) = &events[0] else {
+            panic!("expected RecipeCreated, got {events:?}");
+        };
+        assert_eq!((event.slug.as_str(), event.version), ("sword", 1));
+        assert_eq!(events[0].recipe(), Some(recipe));
+        assert_eq!(events[0].sequence(), (EVENT_SCHEMA_VERSION, 3));
+        assert!(ForgeEvent::decode(&[0; 4]).is_none());
+
+        let mut future = created.data();
+        future[8] = EVENT_SCHEMA_VERSION + 1;
+        assert!(ForgeEvent::decode(&future).is_none());
+    }
+
+    fn legacy_data(discriminator: &[u8], event: &impl AnchorSerialize) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        event.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn decodes_version_0_events_without_a_version_byte() {
+        // A forge address starting with the current version byte must not be
+        // read as a version.
+        let mut forge_config = Pubkey::new_unique().to_bytes();
+        forge_config[0] = EVENT_SCHEMA_VERSION;
+        let forge_config = Pubkey::new_from_array(forge_config);
+        let recipe = Pubkey::new_unique();
+        let forged = v0::AssetForged {
+            forge_config,
+            recipe,
+            forger: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            minted_count: 7,
+            supply_cap: Some(10),
+            input_hash: [9; 32],
+            trait_tier: None,
+        };
+
+        let event = ForgeEvent::decode(&legacy_data(AssetForged::DISCRIMINATOR, &forged)).unwrap();
+        let ForgeEvent::AssetForged(decoded) = &event else {
+            panic!("expected AssetForged, got {event:?}");
+        };
+        assert_eq!(decoded.minted_count, 7);
+        assert_eq!(decoded.input_hash, [9; 32]);
+        assert_eq!(event.forge_config(), forge_config);
+        assert_eq!(event.recipe(), Some(recipe));
+        assert_eq!(event.sequence(), (0, 0));
+
+        let updated = v0::RecipeUpdated {
+            forge_config,
+            recipe,
+            slug: "sword".into(),
+            version: 2,
+        };
+        let event =
+            ForgeEvent::decode(&legacy_data(RecipeUpdated::DISCRIMINATOR, &updated)).unwrap();
+        let ForgeEvent::RecipeUpdated(decoded) = &event else {
+            panic!("expected RecipeUpdated, got {event:?}");
+        };
+        assert_eq!((decoded.slug.as_str(), decoded.version), ("sword", 2));
+        assert!(decoded.before.metadata_uri.is_none() && decoded.after.metadata_uri.is_none());
+        assert_eq!(event.sequence(), (0, 0));
+
+        // Neither layout accepts trailing or missing bytes.
+        let mut data = legacy_data(AssetForged::DISCRIMINATOR, &forged);
+        data.push(0);
+        assert!(ForgeEvent::decode(&data).is_none());
+        data.truncate(data.len() - 2);
+        assert!(ForgeEvent::decode(&data).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+//! Version 0 event layouts: those the Forge program emitted before events
+//! carried `schema_version` and `sequence`. Each converts to the current
+//! event with both fields 0.
+
+use anchor_lang::{
+    prelude::{borsh, Pubkey},
+    AnchorDeserialize, AnchorSerialize,
+};
+use forge_client::forge::{events, state::RecipeStatus};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ForgeInitialized {
+    pub forge_config: Pubkey,
+    pub authority: Pubkey,
+    pub collection_mint: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub default_royalty_bps: u16,
+    pub recipe_creation_enabled: bool,
+}
+
+impl From<ForgeInitialized> for events::ForgeInitialized {
+    fn from(event: ForgeInitialized) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            authority: event.authority,
+            collection_mint: event.collection_mint,
+            freeze_authority: event.freeze_authority,
+            default_royalty_bps: event.default_royalty_bps,
+            recipe_creation_enabled: event.recipe_creation_enabled,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ForgeConfigUpdated {
+    pub forge_config: Pubkey,
+    pub authority: Pubkey,
+    pub collection_mint: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub default_royalty_bps: u16,
+    pub recipe_creation_enabled: bool,
+}
+
+impl From<ForgeConfigUpdated> for events::ForgeConfigUpdated {
+    fn from(event: ForgeConfigUpdated) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            authority: event.authority,
+            collection_mint: event.collection_mint,
+            freeze_authority: event.freeze_authority,
+            default_royalty_bps: event.default_royalty_bps,
+            recipe_creation_enabled: event.recipe_creation_enabled,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RecipeCreated {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub slug: String,
+    pub version: u16,
+    pub status: RecipeStatus,
+}
+
+impl From<RecipeCreated> for events::RecipeCreated {
+    fn from(event: RecipeCreated) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            slug: event.slug,
+            version: event.version,
+            status: event.status,
+        }
+    }
+}
+
+/// Did not record what changed, so converts with empty `before` and
+/// `after`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RecipeUpdated {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub slug: String,
+    pub version: u16,
+}
+
+impl From<RecipeUpdated> for events::RecipeUpdated {
+    fn from(event: RecipeUpdated) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            slug: event.slug,
+            version: event.version,
+            before: Default::default(),
+            after: Default::default(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RecipeStatusChanged {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub previous: RecipeStatus,
+    pub next: RecipeStatus,
+}
+
+impl From<RecipeStatusChanged> for events::RecipeStatusChanged {
+    fn from(event: RecipeStatusChanged) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            previous: event.previous,
+            next: event.next,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AssetForged {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub minted_count: u64,
+    pub supply_cap: Option<u64>,
+    pub input_hash: [u8; 32],
+    pub trait_tier: Option<u8>,
+}
+
+impl From<AssetForged> for events::AssetForged {
+    fn from(event: AssetForged) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            forger: event.forger,
+            mint: event.mint,
+            minted_count: event.minted_count,
+            supply_cap: event.supply_cap,
+            input_hash: event.input_hash,
+            trait_tier: event.trait_tier,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BatchForged {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub count: u64,
+    pub minted_count: u64,
+    pub supply_cap: Option<u64>,
+    pub input_hash: [u8; 32],
+}
+
+impl From<BatchForged> for events::BatchForged {
+    fn from(event: BatchForged) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            forger: event.forger,
+            mint: event.mint,
+            count: event.count,
+            minted_count: event.minted_count,
+            supply_cap: event.supply_cap,
+            input_hash: event.input_hash,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AssetEvolved {
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub level: u16,
+    pub evolved_count: u64,
+    pub input_hash: [u8; 32],
+}
+
+impl From<AssetEvolved> for events::AssetEvolved {
+    fn from(event: AssetEvolved) -> Self {
+        Self {
+            schema_version: 0,
+            sequence: 0,
+            forge_config: event.forge_config,
+            recipe: event.recipe,
+            forger: event.forger,
+            mint: event.mint,
+            level: event.level,
+            evolved_count: event.evolved_count,
+            input_hash: event.input_hash,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! `forge-indexer`: indexes Forge program events into a SQLite database and
+//! queries it.
+//!
+//! Transactions come from JSON dumps of `getTransaction` results or from a
+//! validator's RPC; to index a local ledger, point `--url` at a validator
+//! running on it. Indexing is idempotent, and `rpc` resumes after the newest
+//! transaction already indexed.
+
+use std::{fs, path::PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use forge_indexer::{db::IndexSummary, source, Database};
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::RpcClient;
+
+#[derive(Parser)]
+#[command(
+    name = "forge-indexer",
+    version,
+    about = "Index Forge events into SQLite"
+)]
+struct Cli {
+    /// SQLite database to write to; created if missing.
+    #[arg(
+        long,
+        global = true,
+        env = "FORGE_INDEXER_DB",
+        default_value = "forge.db"
+    )]
+    db: PathBuf,
+    /// Program whose events are indexed.
+    #[arg(long, global = true, default_value_t = forge_client::forge::ID)]
+    program_id: Pubkey,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index JSON dumps of transactions, as returned by `getTransaction`.
+    Json {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Index the program's transactions from an RPC endpoint.
+    Rpc {
+        /// RPC endpoint of the cluster.
+        #[arg(
+            long,
+            short = 'u',
+            env = "SOLANA_RPC_URL",
+            default_value = "http://127.0.0.1:8899"
+        )]
+        url: String,
+    },
+    /// Print the mint history of a recipe.
+    History { recipe: Pubkey },
+    /// Print what a wallet has forged.
+    Forger { forger: Pubkey },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let mut db =
+        Database::open(&cli.db).with_context(|| format!("opening {}", cli.db.display()))?;
+
+    match cli.command {
+        Command::Json { files } => {
+            for file in files {
+                let text = fs::read_to_string(&file)
+                    .with_context(|| format!("reading {}", file.display()))?;
+                let transactions = source::from_json_dump(&text)
+                    .with_context(|| format!("parsing {}", file.display()))?;
+                let summary = db.index(&cli.program_id, &transactions)?;
+                print_summary(&file.display().to_string(), summary);
+            }
+        }
+        Command::Rpc { url } => {
+            let rpc = RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed());
+            let until = db.latest_signature()?;
+            let transactions = source::fetch_from_rpc(&rpc, &cli.program_id, until.as_deref())?;
+            let summary = db.index(&cli.program_id, &transactions)?;
+            print_summary(&url, summary);
+        }
+        Command::History { recipe } => {
+            let history = db.recipe_history(&recipe)?;
+            println!("{} mint(s) of recipe {recipe}:", history.len());
+            for mint in history {
+                let supply = match mint.supply_cap {
+                    Some(cap) => format!("{}/{cap}", mint.minted_count),
+                    None => mint.minted_count.to_string(),
+                };
+                let detail = match (mint.event.as_str(), mint.level) {
+                    ("BatchForged", _) => format!("  batch of {}", mint.units),
+                    (_, Some(level)) => format!("  evolved to level {level}"),
+                    _ => String::new(),
+                };
+                println!(
+                    "  #{supply}  slot {}  {}  forger {}  {}{detail}",
+                    mint.slot, mint.mint, mint.forger, mint.signature
+                );
+            }
+        }
+        Command::Forger { forger } => match db.forger_stats(&forger)? {
+            Some(stats) => println!(
+                "{forger} forged {} asset(s) from {} recipe(s) between slots {} and {}",
+                stats.forged, stats.recipes, stats.first_slot, stats.last_slot
+            ),
+            None => println!("{forger} has not forged anything"),
+        },
+    }
+    Ok(())
+}
+
+fn print_summary(source: &str, summary: IndexSummary) {
+    println!(
+        "{source}: indexed {} transaction(s) with {} event(s), skipped {} already indexed",
+        summary.transactions, summary.events, summary.skipped
+    );
+}
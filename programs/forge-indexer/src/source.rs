@@ -0,0 +1,187 @@
+//! Transactions to index, from a JSON dump or a validator's RPC.
+//!
+//! Both sources read transactions in the shape `getTransaction` returns
+//! them with `"encoding": "json"`; only the slot, block time, signature,
+//! error and log messages are used.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::request::RpcRequest;
+use solana_signature::Signature;
+
+use crate::{IndexError, Result};
+
+/// Signatures requested per `getSignaturesForAddress` page, the RPC maximum.
+const SIGNATURE_PAGE: usize = 1000;
+
+/// The parts of a confirmed transaction the indexer reads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether the transaction failed; failed transactions emit nothing.
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcTransaction {
+    slot: u64,
+    block_time: Option<i64>,
+    transaction: RpcTransactionBody,
+    meta: Option<RpcTransactionMeta>,
+}
+
+#[derive(Deserialize)]
+struct RpcTransactionBody {
+    signatures: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcTransactionMeta {
+    err: Option<Value>,
+    #[serde(default)]
+    log_messages: Option<Vec<String>>,
+}
+
+impl TryFrom<RpcTransaction> for IndexedTransaction {
+    type Error = IndexError;
+
+    fn try_from(transaction: RpcTransaction) -> Result<Self> {
+        let signature = transaction
+            .transaction
+            .signatures
+            .into_iter()
+            .next()
+            .ok_or_else(|| IndexError::Source("transaction without a signature".into()))?;
+        let (failed, logs) = match transaction.meta {
+            Some(meta) => (meta.err.is_some(), meta.log_messages.unwrap_or_default()),
+            None => (false, Vec::new()),
+        };
+        Ok(Self {
+            signature,
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            failed,
+            logs,
+        })
+    }
+}
+
+/// Reads a JSON dump of transactions: one JSON array of them, or one
+/// transaction per line. Arrays may also be spread over several lines.
+pub fn from_json_dump(text: &str) -> Result<Vec<IndexedTransaction>> {
+    let mut transactions = Vec::new();
+    for value in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        let values = match value? {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let transaction: RpcTransaction = serde_json::from_value(value)?;
+            transactions.push(transaction.try_into()?);
+        }
+    }
+    Ok(transactions)
+}
+
+/// Fetches every transaction mentioning `program_id` after `until`, oldest
+/// first. With `until` unset the program's whole history is fetched.
+pub fn fetch_from_rpc(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    until: Option<&str>,
+) -> Result<Vec<IndexedTransaction>> {
+    let until = until
+        .map(Signature::from_str)
+        .transpose()
+        .map_err(|err| IndexError::Source(format!("invalid signature: {err}")))?;
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            program_id,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(
+            Signature::from_str(&last.signature)
+                .map_err(|err| IndexError::Source(format!("invalid signature: {err}")))?,
+        );
+        let full = page.len() == SIGNATURE_PAGE;
+        signatures.extend(page.into_iter().map(|status| status.signature));
+        if !full {
+            break;
+        }
+    }
+
+    // Signatures come newest first.
+    signatures
+        .iter()
+        .rev()
+        .map(|signature| {
+            let transaction: RpcTransaction = rpc.send(
+                RpcRequest::GetTransaction,
+                json!([
+                    signature,
+                    {
+                        "encoding": "json",
+                        "commitment": "confirmed",
+                        "maxSupportedTransactionVersion": 0,
+                    }
+                ]),
+            )?;
+            transaction.try_into()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_arrays_and_json_lines() {
+        let transaction = |signature: &str, err: Value| {
+            json!({
+                "slot": 42,
+                "blockTime": 1767225600,
+                "transaction": { "signatures": [signature], "message": {} },
+                "meta": { "err": err, "logMessages": ["Program log: hi"] },
+            })
+        };
+        let text = format!(
+            "{}\n{}\n",
+            json!([transaction("a", Value::Null), transaction("b", Value::Null)]),
+            transaction("c", json!({ "InstructionError": [0, "Custom"] })),
+        );
+
+        let transactions = from_json_dump(&text).unwrap();
+        assert_eq!(
+            transactions
+                .iter()
+                .map(|transaction| (transaction.signature.as_str(), transaction.failed))
+                .collect::<Vec<_>>(),
+            vec![("a", false), ("b", false), ("c", true)]
+        );
+        assert_eq!(transactions[0].slot, 42);
+        assert_eq!(transactions[0].block_time, Some(1767225600));
+        assert_eq!(transactions[0].logs, vec!["Program log: hi".to_string()]);
+        assert!(from_json_dump("{\"slot\": 1}").is_err());
+    }
+}
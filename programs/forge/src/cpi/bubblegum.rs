@@ -0,0 +1,206 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+use crate::state::Recipe;
+
+/// Metaplex Bubblegum program ID.
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+/// SPL Noop program Bubblegum logs new leaves through.
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// SPL Account Compression program that owns Bubblegum Merkle trees.
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// Anchor discriminator of Bubblegum's `mint_v1` instruction.
+const MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+
+/// Derives the Bubblegum tree config PDA of `merkle_tree`.
+pub fn derive_tree_config_pda(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID)
+}
+
+// Borsh mirrors of the Bubblegum types `mint_v1` takes, limited to the
+// variants the forge mints with.
+
+#[derive(AnchorSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<TokenStandard>,
+    collection: Option<Collection>,
+    /// Always `None`, which encodes as a single zero byte whatever the type.
+    uses: Option<u8>,
+    token_program_version: TokenProgramVersion,
+    creators: Vec<Creator>,
+}
+
+#[derive(AnchorSerialize)]
+enum TokenStandard {
+    NonFungible,
+}
+
+#[derive(AnchorSerialize)]
+enum TokenProgramVersion {
+    Original,
+}
+
+#[derive(AnchorSerialize)]
+struct Collection {
+    verified: bool,
+    key: Pubkey,
+}
+
+#[derive(AnchorSerialize)]
+struct Creator {
+    address: Pubkey,
+    verified: bool,
+    share: u8,
+}
+
+/// Accounts for [`mint_compressed`].
+pub struct CompressedMintAccounts<'a, 'info> {
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub tree_config: &'a AccountInfo<'info>,
+    /// Receives the leaf and is also its delegate.
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    /// Tree creator or delegate; the `ForgeConfig` PDA.
+    pub tree_delegate: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// Mints one compressed NFT of `recipe` into a Bubblegum tree with
+/// Bubblegum's `mint_v1`, named after the recipe's slug like its other
+/// outputs.
+///
+/// The `ForgeConfig` PDA must be the tree's creator or delegate and signs
+/// with `delegate_seeds`. The collection and creators are recorded
+/// unverified because neither signs this instruction.
+pub fn mint_compressed(
+    recipe: &Recipe,
+    seller_fee_basis_points: u16,
+    collection_mint: Option<Pubkey>,
+    accounts: CompressedMintAccounts<'_, '_>,
+    delegate_seeds: &[&[u8]],
+) -> Result<()> {
+    let metadata = MetadataArgs {
+        name: recipe.slug.clone(),
+        symbol: "FORGE".to_string(),
+        uri: recipe.metadata_uri.clone(),
+        seller_fee_basis_points,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: collection_mint.map(|key| Collection {
+            verified: false,
+            key,
+        }),
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: recipe
+            .creators
+            .iter()
+            .map(|c| Creator {
+                address: c.address,
+                verified: false,
+                share: c.share,
+            })
+            .collect(),
+    };
+    let mut data = MINT_V1_DISCRIMINATOR.to_vec();
+    metadata.serialize(&mut data)?;
+
+    let instruction = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.tree_config.key(), false),
+            AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+            AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+            AccountMeta::new(accounts.merkle_tree.key(), false),
+            AccountMeta::new_readonly(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.tree_delegate.key(), true),
+            AccountMeta::new_readonly(accounts.log_wrapper.key(), false),
+            AccountMeta::new_readonly(accounts.compression_program.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.tree_config.clone(),
+            accounts.leaf_owner.clone(),
+            accounts.merkle_tree.clone(),
+            accounts.payer.clone(),
+            accounts.tree_delegate.clone(),
+            accounts.log_wrapper.clone(),
+            accounts.compression_program.clone(),
+            accounts.system_program.clone(),
+            accounts.bubblegum_program.clone(),
+        ],
+        &[delegate_seeds],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_args_match_bubblegum_encoding() {
+        let creator = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let metadata = MetadataArgs {
+            name: "a".to_string(),
+            symbol: "B".to_string(),
+            uri: "u".to_string(),
+            seller_fee_basis_points: 500,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: Some(Collection {
+                verified: false,
+                key: collection,
+            }),
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: vec![Creator {
+                address: creator,
+                verified: false,
+                share: 100,
+            }],
+        };
+
+        let mut expected = vec![1, 0, 0, 0, b'a', 1, 0, 0, 0, b'B', 1, 0, 0, 0, b'u'];
+        expected.extend_from_slice(&500u16.to_le_bytes());
+        // primary_sale_happened, is_mutable, edition_nonce, Some(NonFungible)
+        expected.extend_from_slice(&[0, 1, 0, 1, 0]);
+        expected.extend_from_slice(&[1, 0]);
+        expected.extend_from_slice(collection.as_ref());
+        // uses, token_program_version, one creator
+        expected.extend_from_slice(&[0, 0, 1, 0, 0, 0]);
+        expected.extend_from_slice(creator.as_ref());
+        expected.extend_from_slice(&[0, 100]);
+
+        assert_eq!(metadata.try_to_vec().unwrap(), expected);
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address, token};
+
+use crate::{
+    cpi::ingredients::HeldToken,
+    errors::ForgeError,
+    state::{
+        constants::{FORGE_VAULT_SEED, TOKEN_ACCOUNT_LEN},
+        EscrowedToken,
+    },
+};
+
+/// Vault authority PDA of a forge; it owns one associated token account per
+/// escrowed mint.
+pub fn derive_vault_authority(forge_config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FORGE_VAULT_SEED, forge_config.as_ref()], &crate::ID)
+}
+
+/// Returns the writable account with `key` from `remaining_accounts`.
+fn find_writable<'a, 'info>(
+    key: &Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>> {
+    remaining_accounts
+        .iter()
+        .find(|acc| acc.key == key && acc.is_writable)
+        .ok_or_else(|| error!(ForgeError::EscrowTokenAccountMissing))
+}
+
+/// Moves every held token balance into the vault's associated token account
+/// for its mint, returning what was escrowed.
+///
+/// The forger's token accounts and the vault token accounts must be writable
+/// in `remaining_accounts`; `forger` signs as the token account owner.
+pub fn escrow_held_tokens<'info>(
+    held_tokens: &[HeldToken],
+    forger: &AccountInfo<'info>,
+    vault_authority: &Pubkey,
+    token_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<EscrowedToken>> {
+    let mut escrowed = Vec::with_capacity(held_tokens.len());
+    for held in held_tokens {
+        let vault_token_account = get_associated_token_address(vault_authority, &held.mint);
+        token::transfer(
+            CpiContext::new(
+                token_program.clone(),
+                token::Transfer {
+                    from: find_writable(&held.token_account, remaining_accounts)?.clone(),
+                    to: find_writable(&vault_token_account, remaining_accounts)?.clone(),
+                    authority: forger.clone(),
+                },
+            ),
+            held.amount,
+        )?;
+        escrowed.push(EscrowedToken {
+            mint: held.mint,
+            amount: held.amount,
+        });
+    }
+    Ok(escrowed)
+}
+
+/// Returns escrowed tokens from the vault to `holder`'s token accounts.
+///
+/// For each item the vault's associated token account and a writable token
+/// account of `holder` for the same mint must be in `remaining_accounts`.
+pub fn release_escrowed_tokens<'info>(
+    items: &[EscrowedToken],
+    holder: &Pubkey,
+    vault_authority: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    token_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    for item in items {
+        let vault_token_account = get_associated_token_address(vault_authority.key, &item.mint);
+        let holder_token_account = remaining_accounts
+            .iter()
+            .filter(|acc| acc.owner == &token::ID && acc.is_writable)
+            .find(|acc| {
+                let data = acc.data.borrow();
+                data.len() == TOKEN_ACCOUNT_LEN
+                    && data[0..32] == item.mint.as_ref()[..]
+                    && data[32..64] == holder.as_ref()[..]
+            })
+            .ok_or(ForgeError::EscrowTokenAccountMissing)?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                token::Transfer {
+                    from: find_writable(&vault_token_account, remaining_accounts)?.clone(),
+                    to: holder_token_account.clone(),
+                    authority: vault_authority.clone(),
+                },
+                &[vault_seeds],
+            ),
+            item.amount,
+        )?;
+    }
+    Ok(())
+}
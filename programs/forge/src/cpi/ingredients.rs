@@ -1,63 +1,584 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token;
+use solana_instructions_sysvar as instructions_sysvar;
+use solana_program::ed25519_program;
 
-use crate::errors::ForgeError;
+use forge_hash::{chunk, compute_input_hash, ForgeInputs};
 
-/// Verifies that the forger owns the required amount of the specified token mint.
+use crate::{
+    errors::ForgeError,
+    state::{constants::TOKEN_ACCOUNT_LEN, ForgedAsset, IngredientConstraint, ReplayPolicy},
+};
+
+/// Forged asset claimed by a `ForgedOutput` ingredient during verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConsumedAsset {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub burn: bool,
+}
+
+/// Token balance proven by a `TokenMint` ingredient during verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeldToken {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+/// Verifies ingredient constraints for one forge request.
+///
+/// Besides producing hash chunks it records the forged assets claimed by
+/// `ForgedOutput` ingredients, so one asset cannot satisfy two constraints and
+/// the caller can burn the ones that ask for it once verification succeeds.
+/// It also records the token balances `TokenMint` ingredients relied on, which
+/// reversible recipes move into escrow, and the concrete account each leaf
+/// was satisfied with, which the input hash binds.
+pub struct IngredientVerifier<'a, 'info> {
+    recipe: Pubkey,
+    forger: Pubkey,
+    now: i64,
+    remaining_accounts: &'a [AccountInfo<'info>],
+    consumed: Vec<ConsumedAsset>,
+    held_tokens: Vec<HeldToken>,
+    inputs: Vec<Pubkey>,
+    /// Nonce of the forge being hashed, which signed authorizations must
+    /// carry; unknown (and unchecked) outside [`Self::input_hash`].
+    nonce: Option<u64>,
+    authorizations: usize,
+}
+
+impl<'a, 'info> IngredientVerifier<'a, 'info> {
+    pub fn new(
+        recipe: Pubkey,
+        forger: Pubkey,
+        now: i64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Self {
+        Self {
+            recipe,
+            forger,
+            now,
+            remaining_accounts,
+            consumed: Vec::new(),
+            held_tokens: Vec::new(),
+            inputs: Vec::new(),
+            nonce: None,
+            authorizations: 0,
+        }
+    }
+
+    /// Assets claimed by the constraints verified so far.
+    pub fn consumed(&self) -> &[ConsumedAsset] {
+        &self.consumed
+    }
+
+    /// Token balances relied on by the `TokenMint` constraints verified so far.
+    pub fn held_tokens(&self) -> &[HeldToken] {
+        &self.held_tokens
+    }
+
+    /// Concrete inputs of the leaves verified so far, in verification order:
+    /// the signer for `Signer` and `SignedAuthorization`, the PDA for
+    /// `CustomSeeds`, the token account for `TokenMint`, the NFT mint for
+    /// `CollectionNft`, the leaf for `Allowlist` and the asset mint for
+    /// `ForgedOutput`.
+    pub fn inputs(&self) -> &[Pubkey] {
+        &self.inputs
+    }
+
+    /// Verifies a single ingredient constraint and returns its canonical hash
+    /// chunk, as laid out by [`forge_hash::chunk`].
+    pub fn verify(&mut self, constraint: &IngredientConstraint) -> Result<Vec<u8>> {
+        let forger = &self.forger;
+        let remaining_accounts = self.remaining_accounts;
+        let (chunk, input) = match constraint {
+            IngredientConstraint::Signer { authority } => {
+                verify_signer(forger, authority, remaining_accounts)?
+            }
+            IngredientConstraint::CustomSeeds {
+                program_id,
+                seeds,
+                include_forger,
+            } => verify_custom_seeds(
+                forger,
+                program_id,
+                seeds,
+                *include_forger,
+                remaining_accounts,
+            )?,
+            IngredientConstraint::TokenMint { mint, amount } => {
+                let (chunk, token_account) =
+                    verify_token_mint(forger, mint, *amount, remaining_accounts)?;
+                self.held_tokens.push(HeldToken {
+                    mint: *mint,
+                    token_account,
+                    amount: *amount,
+                });
+                (chunk, token_account)
+            }
+            IngredientConstraint::CollectionNft { collection_mint } => {
+                verify_collection_nft(forger, collection_mint, remaining_accounts)?
+            }
+            IngredientConstraint::Allowlist { merkle_root } => {
+                verify_allowlist(merkle_root, remaining_accounts)?
+            }
+            IngredientConstraint::AllOf { constraints } => {
+                return self.verify_composite(chunk::tag::ALL_OF, constraints, constraints.len());
+            }
+            IngredientConstraint::AnyOf { constraints } => {
+                return self.verify_composite(chunk::tag::ANY_OF, constraints, 1);
+            }
+            IngredientConstraint::AtLeast {
+                threshold,
+                constraints,
+            } => {
+                return self.verify_composite(
+                    chunk::tag::AT_LEAST,
+                    constraints,
+                    usize::from(*threshold),
+                )
+            }
+            IngredientConstraint::ForgedOutput { recipe, burn } => {
+                self.verify_forged_output(recipe, *burn)?
+            }
+            IngredientConstraint::SignedAuthorization { authority } => {
+                self.verify_signed_authorization(authority)?
+            }
+        };
+        self.inputs.push(input);
+        Ok(chunk)
+    }
+
+    /// Verifies every constraint of a recipe and returns its input hash
+    /// under `replay_policy` (see [`forge_hash::compute_input_hash`]).
+    ///
+    /// `leading_chunks` are hashed ahead of the ingredient chunks; evolve
+    /// uses them to bind the asset being upgraded. Signed authorizations must
+    /// carry `nonce`, which is then hashed under every policy.
+    pub fn input_hash(
+        &mut self,
+        constraints: &[IngredientConstraint],
+        replay_policy: ReplayPolicy,
+        nonce: u64,
+        leading_chunks: Vec<Vec<u8>>,
+    ) -> Result<[u8; 32]> {
+        self.nonce = Some(nonce);
+        let mut hash_chunks = leading_chunks;
+        for constraint in constraints {
+            hash_chunks.push(self.verify(constraint)?);
+        }
+        let accounts: Vec<[u8; 32]> = self.inputs.iter().map(|input| input.to_bytes()).collect();
+        Ok(compute_input_hash(
+            &self.recipe.to_bytes(),
+            &self.forger.to_bytes(),
+            &ForgeInputs {
+                replay_policy: replay_policy.into(),
+                nonce,
+                authorized_nonce: self.authorizations > 0,
+                chunks: &hash_chunks,
+                accounts: &accounts,
+            },
+        ))
+    }
+
+    /// Like [`Self::verify`], but a failing constraint releases the assets,
+    /// balances, inputs and authorizations it recorded, so verification can
+    /// go on.
+    pub fn try_verify(&mut self, constraint: &IngredientConstraint) -> Result<Vec<u8>> {
+        let claimed = self.consumed.len();
+        let held = self.held_tokens.len();
+        let recorded = self.inputs.len();
+        let authorizations = self.authorizations;
+        self.verify(constraint).inspect_err(|_| {
+            self.consumed.truncate(claimed);
+            self.held_tokens.truncate(held);
+            self.inputs.truncate(recorded);
+            self.authorizations = authorizations;
+        })
+    }
+
+    /// Evaluates children in order until `required` of them pass.
+    ///
+    /// A failing child is skipped and any assets it claimed are released; for
+    /// `AllOf` (`required == len`) the first failure is returned as-is so the
+    /// caller sees the concrete reason.
+    fn verify_composite(
+        &mut self,
+        tag: u8,
+        constraints: &[IngredientConstraint],
+        required: usize,
+    ) -> Result<Vec<u8>> {
+        let mut satisfied_mask = 0u16;
+        let mut satisfied = 0usize;
+        let mut child_chunks: Vec<Vec<u8>> = Vec::with_capacity(required);
+
+        for (index, child) in constraints.iter().enumerate() {
+            if satisfied == required {
+                break;
+            }
+            match self.try_verify(child) {
+                Ok(chunk) => {
+                    satisfied_mask |= 1 << index;
+                    satisfied += 1;
+                    child_chunks.push(chunk);
+                }
+                Err(err) if required == constraints.len() => return Err(err),
+                Err(_) => {}
+            }
+        }
+        require!(
+            satisfied == required,
+            ForgeError::CompositeIngredientUnsatisfied
+        );
+
+        Ok(chunk::composite(
+            tag,
+            constraints.len() as u8,
+            satisfied_mask,
+            &child_chunks,
+        ))
+    }
+
+    /// Verifies that the forger holds an asset forged by `recipe`.
+    ///
+    /// Expects the asset's `ForgedAsset` record and the forger's token account
+    /// for its mint in remaining accounts (plus the writable mint when
+    /// `burn` is set). The first unclaimed asset from `recipe` that the forger
+    /// holds is used. Escrowed assets cannot be burned here, since `unforge`
+    /// needs their token to release the escrow.
+    fn verify_forged_output(&mut self, recipe: &Pubkey, burn: bool) -> Result<(Vec<u8>, Pubkey)> {
+        let mut failure = ForgeError::ForgedOutputMissing;
+        let mut claimed = None;
+        for info in self
+            .remaining_accounts
+            .iter()
+            .filter(|acc| acc.owner == &crate::ID)
+        {
+            // Discriminator check happens during deserialization; accounts of
+            // other types are simply not candidates.
+            let Ok(asset) = ForgedAsset::try_deserialize(&mut &info.data.borrow()[..]) else {
+                continue;
+            };
+            if asset.recipe != *recipe || self.consumed.iter().any(|c| c.mint == asset.mint) {
+                continue;
+            }
+            if burn && asset.escrowed {
+                failure = ForgeError::ForgedOutputEscrowed;
+                continue;
+            }
+            match find_held_token_account(&self.forger, &asset.mint, self.remaining_accounts) {
+                Some(token_account) => {
+                    claimed = Some((asset.mint, token_account));
+                    break;
+                }
+                None => failure = ForgeError::ForgedOutputNotHeld,
+            }
+        }
+        let Some((mint, token_account)) = claimed else {
+            return Err(failure.into());
+        };
+        self.consumed.push(ConsumedAsset {
+            mint,
+            token_account,
+            burn,
+        });
+
+        Ok((
+            chunk::forged_output(&recipe.to_bytes(), &mint.to_bytes(), burn),
+            mint,
+        ))
+    }
+
+    /// Verifies an off-chain authorization signed by `authority`.
+    ///
+    /// The transaction must carry an Ed25519 precompile instruction whose
+    /// public key is `authority` and whose message is
+    /// `recipe || forger || nonce (u64 LE) || expiry (i64 LE)`; the
+    /// instructions sysvar has to be passed in remaining accounts so it can be
+    /// inspected. The runtime rejects the transaction if that signature is
+    /// invalid, so finding the instruction is proof of the signature. The
+    /// signed nonce must be the forge's own, so one signature authorizes one
+    /// forge.
+    fn verify_signed_authorization(&mut self, authority: &Pubkey) -> Result<(Vec<u8>, Pubkey)> {
+        require_keys_neq!(
+            *authority,
+            self.forger,
+            ForgeError::AuthorizationSignerIsForger
+        );
+        let sysvar_info = self
+            .remaining_accounts
+            .iter()
+            .find(|acc| instructions_sysvar::check_id(acc.key))
+            .ok_or(ForgeError::MissingAuthorization)?;
+        let instruction_count = {
+            let data = sysvar_info.try_borrow_data()?;
+            require!(data.len() >= 2, ForgeError::MissingAuthorization);
+            u16::from_le_bytes([data[0], data[1]])
+        };
+
+        let mut failure = ForgeError::MissingAuthorization;
+        for index in 0..usize::from(instruction_count) {
+            let instruction = instructions_sysvar::load_instruction_at_checked(index, sysvar_info)?;
+            if instruction.program_id != ed25519_program::ID {
+                continue;
+            }
+            for (public_key, message) in ed25519_signed_messages(&instruction.data) {
+                if public_key != authority.as_ref()
+                    || message.len() != AUTHORIZATION_MESSAGE_LEN
+                    || message[0..32] != self.recipe.as_ref()[..]
+                    || message[32..64] != self.forger.as_ref()[..]
+                {
+                    continue;
+                }
+                let mut nonce_bytes = [0u8; 8];
+                nonce_bytes.copy_from_slice(&message[64..72]);
+                let nonce = u64::from_le_bytes(nonce_bytes);
+                let mut expiry_bytes = [0u8; 8];
+                expiry_bytes.copy_from_slice(&message[72..80]);
+                if i64::from_le_bytes(expiry_bytes) < self.now {
+                    failure = ForgeError::AuthorizationExpired;
+                    continue;
+                }
+                if self.nonce.is_some_and(|expected| expected != nonce) {
+                    failure = ForgeError::AuthorizationNonceMismatch;
+                    continue;
+                }
+
+                let chunk = chunk::signed_authorization(
+                    &authority.to_bytes(),
+                    nonce,
+                    i64::from_le_bytes(expiry_bytes),
+                );
+                self.authorizations += 1;
+                return Ok((chunk, *authority));
+            }
+        }
+        Err(failure.into())
+    }
+}
+
+/// Length of the message signed for a `SignedAuthorization` ingredient.
+pub const AUTHORIZATION_MESSAGE_LEN: usize = 32 + 32 + 8 + 8;
+
+/// Size of one signature offsets entry in Ed25519 precompile instruction data.
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Extracts `(public_key, message)` pairs from Ed25519 precompile instruction
+/// data.
+///
+/// Only entries whose signature, key and message all live in the precompile
+/// instruction itself (instruction index `u16::MAX`) are returned, so the
+/// bytes read here are exactly the bytes the runtime verified.
+fn ed25519_signed_messages(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let read_u16 = |at: usize| -> Option<u16> {
+        Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+    };
+    let signature_count = data.first().copied().unwrap_or(0) as usize;
+    (0..signature_count)
+        .filter_map(|i| {
+            let base = 2 + i * ED25519_OFFSETS_LEN;
+            let signature_ix = read_u16(base + 2)?;
+            let public_key_offset = usize::from(read_u16(base + 4)?);
+            let public_key_ix = read_u16(base + 6)?;
+            let message_offset = usize::from(read_u16(base + 8)?);
+            let message_len = usize::from(read_u16(base + 10)?);
+            let message_ix = read_u16(base + 12)?;
+            if [signature_ix, public_key_ix, message_ix] != [u16::MAX; 3] {
+                return None;
+            }
+            Some((
+                data.get(public_key_offset..public_key_offset + 32)?,
+                data.get(message_offset..message_offset + message_len)?,
+            ))
+        })
+        .collect()
+}
+
+/// Burns every consumed asset that was marked for burning.
+///
+/// The mint and the forger's token account must be writable in
+/// `remaining_accounts`; `forger` signs as the token account owner.
+pub fn burn_consumed_assets<'info>(
+    consumed: &[ConsumedAsset],
+    forger: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let find = |key: &Pubkey| {
+        remaining_accounts
+            .iter()
+            .find(|acc| acc.key == key && acc.is_writable)
+            .ok_or(ForgeError::InvalidRemainingAccounts)
+    };
+    for asset in consumed.iter().filter(|asset| asset.burn) {
+        token::burn(
+            CpiContext::new(
+                token_program.clone(),
+                token::Burn {
+                    mint: find(&asset.mint)?.clone(),
+                    from: find(&asset.token_account)?.clone(),
+                    authority: forger.clone(),
+                },
+            ),
+            1,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns a token account in `remaining_accounts` that `owner` uses to hold
+/// at least one token of `mint`.
+fn find_held_token_account(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Option<Pubkey> {
+    remaining_accounts
+        .iter()
+        .filter(|acc| acc.owner == &token::ID)
+        .find(|acc| {
+            let data = acc.data.borrow();
+            data.len() == TOKEN_ACCOUNT_LEN
+                && data[0..32] == mint.as_ref()[..]
+                && data[32..64] == owner.as_ref()[..]
+                && data[64..72] != [0u8; 8]
+        })
+        .map(|acc| acc.key())
+}
+
+/// Verifies that `authority` signed the transaction, either as the forger or
+/// as an additional signer in remaining accounts.
+///
+/// Like every leaf verifier, returns the hash chunk and the concrete input.
+pub fn verify_signer(
+    forger: &Pubkey,
+    authority: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<(Vec<u8>, Pubkey)> {
+    let matched = *forger == *authority
+        || remaining_accounts
+            .iter()
+            .any(|acc| acc.is_signer && *acc.key == *authority);
+    require!(matched, ForgeError::MissingRequiredSigner);
+
+    Ok((chunk::signer(&authority.to_bytes()), *authority))
+}
+
+/// Verifies that the account at the PDA derived from `seeds` (and the forger
+/// pubkey, if requested) under `program_id` was passed and is initialized.
+pub fn verify_custom_seeds(
+    forger: &Pubkey,
+    program_id: &Pubkey,
+    seeds: &[u8],
+    include_forger: bool,
+    remaining_accounts: &[AccountInfo],
+) -> Result<(Vec<u8>, Pubkey)> {
+    let mut seed_slices: Vec<&[u8]> = Vec::with_capacity(2);
+    if !seeds.is_empty() {
+        seed_slices.push(seeds);
+    }
+    if include_forger {
+        seed_slices.push(forger.as_ref());
+    }
+    let (pda, _) = Pubkey::find_program_address(&seed_slices, program_id);
+
+    // The PDA must exist and be owned by the configured program; an empty
+    // system account at the address would prove nothing.
+    let seeds_account = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == pda)
+        .ok_or(ForgeError::CustomSeedsAccountMissing)?;
+    require!(
+        seeds_account.owner == program_id && !seeds_account.data_is_empty(),
+        ForgeError::CustomSeedsAccountMissing
+    );
+
+    Ok((
+        chunk::custom_seeds(&program_id.to_bytes(), include_forger, &pda.to_bytes()),
+        pda,
+    ))
+}
+
+/// Verifies that the forger owns the required amount of the specified token
+/// mint; the input is the token account holding it.
 pub fn verify_token_mint(
     forger: &Pubkey,
     mint: &Pubkey,
     required_amount: u64,
     remaining_accounts: &[AccountInfo],
-) -> Result<Vec<u8>> {
-    // Find token account in remaining_accounts owned by Token Program
-    let token_account_info = remaining_accounts
-        .iter()
-        .find(|acc| acc.owner == &token::ID)
-        .ok_or(ForgeError::MissingTokenAccount)?;
+) -> Result<(Vec<u8>, Pubkey)> {
+    let token_account = find_token_balance(forger, mint, required_amount, remaining_accounts)?;
+    Ok((
+        chunk::token_mint(&mint.to_bytes(), required_amount),
+        token_account,
+    ))
+}
 
-    // Deserialize TokenAccount manually to avoid lifetime issues
-    // TokenAccount structure: mint(32) + owner(32) + amount(8) + delegate(36) + state(1) + ...
-    // We only need: mint (offset 0), owner (offset 32), amount (offset 64)
-    let data = token_account_info.data.borrow();
-    require!(data.len() >= 72, ForgeError::MissingTokenAccount);
+/// Returns the forger's token account in `remaining_accounts` holding at
+/// least `required_amount` of `mint`.
+fn find_token_balance(
+    forger: &Pubkey,
+    mint: &Pubkey,
+    required_amount: u64,
+    remaining_accounts: &[AccountInfo],
+) -> Result<Pubkey> {
+    // Scan token accounts in remaining_accounts for one of the required mint
+    // held by the forger. Several candidates may be present when composite
+    // constraints reference different mints, so keep the most specific error.
+    let mut failure = ForgeError::MissingTokenAccount;
+    let mut found = None;
+    for token_account_info in remaining_accounts
+        .iter()
+        .filter(|acc| acc.owner == &token::ID)
+    {
+        // Deserialize TokenAccount manually to avoid lifetime issues
+        // TokenAccount structure: mint(32) + owner(32) + amount(8) + delegate(36) + state(1) + ...
+        // We only need: mint (offset 0), owner (offset 32), amount (offset 64)
+        let data = token_account_info.data.borrow();
+        if data.len() != TOKEN_ACCOUNT_LEN {
+            continue;
+        }
 
-    // Parse mint (bytes 0-32)
-    let mut mint_bytes = [0u8; 32];
-    mint_bytes.copy_from_slice(&data[0..32]);
-    let account_mint = Pubkey::new_from_array(mint_bytes);
+        // Parse mint (bytes 0-32)
+        let mut mint_bytes = [0u8; 32];
+        mint_bytes.copy_from_slice(&data[0..32]);
+        let account_mint = Pubkey::new_from_array(mint_bytes);
 
-    // Parse owner (bytes 32-64)
-    let mut owner_bytes = [0u8; 32];
-    owner_bytes.copy_from_slice(&data[32..64]);
-    let account_owner = Pubkey::new_from_array(owner_bytes);
+        // Parse owner (bytes 32-64)
+        let mut owner_bytes = [0u8; 32];
+        owner_bytes.copy_from_slice(&data[32..64]);
+        let account_owner = Pubkey::new_from_array(owner_bytes);
 
-    // Parse amount (bytes 64-72, u64 little-endian)
-    let mut amount_bytes = [0u8; 8];
-    amount_bytes.copy_from_slice(&data[64..72]);
-    let account_amount = u64::from_le_bytes(amount_bytes);
+        // Parse amount (bytes 64-72, u64 little-endian)
+        let mut amount_bytes = [0u8; 8];
+        amount_bytes.copy_from_slice(&data[64..72]);
+        let account_amount = u64::from_le_bytes(amount_bytes);
 
-    // Verify token account owner matches forger
-    require!(
-        account_owner == *forger,
-        ForgeError::TokenAccountOwnerMismatch
-    );
+        // Verify mint matches
+        if account_mint != *mint {
+            if matches!(failure, ForgeError::MissingTokenAccount) {
+                failure = ForgeError::TokenMintMismatch;
+            }
+            continue;
+        }
 
-    // Verify mint matches
-    require!(account_mint == *mint, ForgeError::TokenMintMismatch);
+        // Verify token account owner matches forger
+        if account_owner != *forger {
+            failure = ForgeError::TokenAccountOwnerMismatch;
+            continue;
+        }
 
-    // Verify sufficient balance
-    require!(
-        account_amount >= required_amount,
-        ForgeError::InsufficientTokenBalance
-    );
+        // Verify sufficient balance
+        if account_amount < required_amount {
+            failure = ForgeError::InsufficientTokenBalance;
+            continue;
+        }
 
-    // Build hash chunk: [variant_tag: 2, mint: 32, amount: 8]
-    let mut chunk = Vec::with_capacity(1 + 32 + 8);
-    chunk.push(2u8); // TokenMint variant
-    chunk.extend_from_slice(mint.as_ref());
-    chunk.extend_from_slice(&required_amount.to_le_bytes());
-    Ok(chunk)
+        found = Some(token_account_info.key());
+        break;
+    }
+    found.ok_or_else(|| failure.into())
 }
 
 /// Verifies that the forger owns an NFT from the specified collection.
@@ -66,7 +587,7 @@ pub fn verify_collection_nft(
     forger: &Pubkey,
     collection_mint: &Pubkey,
     remaining_accounts: &[AccountInfo],
-) -> Result<Vec<u8>> {
+) -> Result<(Vec<u8>, Pubkey)> {
     // Find NFT mint account in remaining_accounts
     // The mint should be provided as one of the remaining accounts
     let nft_mint_info = remaining_accounts
@@ -142,11 +663,7 @@ pub fn verify_collection_nft(
     // to check the collection field. For MVP, we verify the NFT exists and is owned.
     // Collection verification can be enhanced later with full metadata parsing.
 
-    // Build hash chunk: [variant_tag: 3, collection_mint: 32]
-    let mut chunk = Vec::with_capacity(1 + 32);
-    chunk.push(3u8); // CollectionNft variant
-    chunk.extend_from_slice(collection_mint.as_ref());
-    Ok(chunk)
+    Ok((chunk::collection_nft(&collection_mint.to_bytes()), nft_mint))
 }
 
 /// Verifies allowlist membership using Merkle proof.
@@ -155,7 +672,7 @@ pub fn verify_collection_nft(
 pub fn verify_allowlist(
     merkle_root: &[u8; 32],
     remaining_accounts: &[AccountInfo],
-) -> Result<Vec<u8>> {
+) -> Result<(Vec<u8>, Pubkey)> {
     use solana_program::hash::hashv;
 
     // Require at least one account (the leaf)
@@ -203,9 +720,465 @@ pub fn verify_allowlist(
         ForgeError::IngredientHashMismatch
     );
 
-    // Build hash chunk: [variant_tag: 4, merkle_root: 32]
-    let mut chunk = Vec::with_capacity(1 + 32);
-    chunk.push(4u8); // Allowlist variant
-    chunk.extend_from_slice(merkle_root);
-    Ok(chunk)
+    Ok((chunk::allowlist(merkle_root), Pubkey::new_from_array(leaf)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::constants::HASH_BYTES;
+
+    /// Verifies a single constraint with a throwaway verifier.
+    fn verify_constraint(
+        constraint: &IngredientConstraint,
+        forger: &Pubkey,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<Vec<u8>> {
+        IngredientVerifier::new(Pubkey::default(), *forger, 0, remaining_accounts)
+            .verify(constraint)
+    }
+
+    fn signer(authority: Pubkey) -> IngredientConstraint {
+        IngredientConstraint::Signer { authority }
+    }
+
+    #[test]
+    fn any_of_records_first_satisfied_branch() {
+        let forger = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let constraint = IngredientConstraint::AnyOf {
+            constraints: vec![signer(other), signer(forger), signer(forger)].into(),
+        };
+
+        let chunk = verify_constraint(&constraint, &forger, &[]).unwrap();
+        let mut expected = vec![6u8, 3, 0b010, 0];
+        expected.extend_from_slice(&33u16.to_le_bytes());
+        expected.push(0);
+        expected.extend_from_slice(forger.as_ref());
+        assert_eq!(chunk, expected);
+    }
+
+    #[test]
+    fn at_least_requires_threshold() {
+        let forger = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let two_of_three = IngredientConstraint::AtLeast {
+            threshold: 2,
+            constraints: vec![signer(forger), signer(other), signer(forger)].into(),
+        };
+        let chunk = verify_constraint(&two_of_three, &forger, &[]).unwrap();
+        assert_eq!(&chunk[..4], &[7u8, 3, 0b101, 0]);
+
+        let three_of_three = IngredientConstraint::AtLeast {
+            threshold: 3,
+            constraints: vec![signer(forger), signer(other), signer(forger)].into(),
+        };
+        assert!(verify_constraint(&three_of_three, &forger, &[]).is_err());
+    }
+
+    #[test]
+    fn all_of_surfaces_failing_child() {
+        let forger = Pubkey::new_unique();
+        let constraint = IngredientConstraint::AllOf {
+            constraints: vec![signer(forger), signer(Pubkey::new_unique())].into(),
+        };
+        let err = verify_constraint(&constraint, &forger, &[]).unwrap_err();
+        assert_eq!(err, ForgeError::MissingRequiredSigner.into());
+    }
+
+    #[test]
+    fn custom_seeds_requires_initialized_pda() {
+        let forger = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let constraint = IngredientConstraint::CustomSeeds {
+            program_id: game,
+            seeds: b"profile".to_vec(),
+            include_forger: true,
+        };
+        let (pda, _) = Pubkey::find_program_address(&[b"profile", forger.as_ref()], &game);
+
+        let mut lamports = 1u64;
+        let mut data = vec![1u8; 8];
+        let profile = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &game,
+            false,
+            0,
+        );
+        let chunk =
+            verify_constraint(&constraint, &forger, std::slice::from_ref(&profile)).unwrap();
+        assert_eq!(chunk[0], 1);
+        assert_eq!(&chunk[34..], pda.as_ref());
+
+        let wrong_owner = Pubkey::new_unique();
+        let mut lamports = 1u64;
+        let mut data = vec![1u8; 8];
+        let spoofed = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &wrong_owner,
+            false,
+            0,
+        );
+        assert!(verify_constraint(&constraint, &forger, &[spoofed]).is_err());
+        assert!(verify_constraint(&constraint, &Pubkey::new_unique(), &[profile]).is_err());
+    }
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn forged_asset(recipe: Pubkey, mint: Pubkey, escrowed: bool) -> Self {
+            let mut data = Vec::new();
+            ForgedAsset {
+                forge_config: Pubkey::new_unique(),
+                recipe,
+                version: 1,
+                forger: Pubkey::new_unique(),
+                mint,
+                input_hash: [0; HASH_BYTES],
+                ordinal: 1,
+                forged_at: 0,
+                trait_tier: None,
+                level: 0,
+                bump: 255,
+                escrowed,
+                _reserved: [0; 4],
+            }
+            .try_serialize(&mut data)
+            .unwrap();
+            Self {
+                key: Pubkey::new_unique(),
+                owner: crate::ID,
+                lamports: 1,
+                data,
+            }
+        }
+
+        fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+            let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+            data[0..32].copy_from_slice(mint.as_ref());
+            data[32..64].copy_from_slice(owner.as_ref());
+            data[64..72].copy_from_slice(&amount.to_le_bytes());
+            Self {
+                key: Pubkey::new_unique(),
+                owner: token::ID,
+                lamports: 1,
+                data,
+            }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                false,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    #[test]
+    fn forged_output_claims_each_asset_once() {
+        let forger = Pubkey::new_unique();
+        let upstream = Pubkey::new_unique();
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = [
+            TestAccount::forged_asset(upstream, mint_a, false),
+            TestAccount::token_account(mint_a, forger, 1),
+            TestAccount::forged_asset(upstream, mint_b, false),
+            TestAccount::token_account(mint_b, Pubkey::new_unique(), 1),
+        ];
+        let forged_output = IngredientConstraint::ForgedOutput {
+            recipe: upstream,
+            burn: true,
+        };
+
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|acc| acc.info()).collect();
+        let mut verifier = IngredientVerifier::new(Pubkey::default(), forger, 0, &infos);
+        let chunk = verifier.verify(&forged_output).unwrap();
+        assert_eq!(chunk[0], 8);
+        assert_eq!(&chunk[33..65], mint_a.as_ref());
+        assert_eq!(chunk[65], 1);
+        assert_eq!(verifier.consumed()[0].mint, mint_a);
+        assert_eq!(verifier.consumed()[0].token_account, infos[1].key());
+
+        // The only held asset is already claimed; mint_b is held by someone else.
+        let err = verifier.verify(&forged_output).unwrap_err();
+        assert_eq!(err, ForgeError::ForgedOutputNotHeld.into());
+
+        let other_recipe = IngredientConstraint::ForgedOutput {
+            recipe: Pubkey::new_unique(),
+            burn: false,
+        };
+        let err = verify_constraint(&other_recipe, &forger, &infos).unwrap_err();
+        assert_eq!(err, ForgeError::ForgedOutputMissing.into());
+    }
+
+    #[test]
+    fn escrowed_forged_outputs_are_not_burned() {
+        let forger = Pubkey::new_unique();
+        let upstream = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut accounts = [
+            TestAccount::forged_asset(upstream, mint, true),
+            TestAccount::token_account(mint, forger, 1),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|acc| acc.info()).collect();
+
+        let burned = IngredientConstraint::ForgedOutput {
+            recipe: upstream,
+            burn: true,
+        };
+        let err = verify_constraint(&burned, &forger, &infos).unwrap_err();
+        assert_eq!(err, ForgeError::ForgedOutputEscrowed.into());
+
+        let kept = IngredientConstraint::ForgedOutput {
+            recipe: upstream,
+            burn: false,
+        };
+        assert!(verify_constraint(&kept, &forger, &infos).is_ok());
+    }
+
+    #[test]
+    fn failed_branch_releases_claimed_assets() {
+        let forger = Pubkey::new_unique();
+        let upstream = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut accounts = [
+            TestAccount::forged_asset(upstream, mint, false),
+            TestAccount::token_account(mint, forger, 1),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|acc| acc.info()).collect();
+        let forged_output = IngredientConstraint::ForgedOutput {
+            recipe: upstream,
+            burn: false,
+        };
+        // First branch claims the asset and then fails on the signer; the
+        // second branch must still be able to claim it.
+        let constraint = IngredientConstraint::AnyOf {
+            constraints: vec![
+                IngredientConstraint::AllOf {
+                    constraints: vec![forged_output.clone(), signer(Pubkey::new_unique())].into(),
+                },
+                forged_output,
+            ]
+            .into(),
+        };
+
+        let mut verifier = IngredientVerifier::new(Pubkey::default(), forger, 0, &infos);
+        let chunk = verifier.verify(&constraint).unwrap();
+        assert_eq!(&chunk[..4], &[6u8, 2, 0b10, 0]);
+        assert_eq!(verifier.consumed().len(), 1);
+    }
+
+    #[test]
+    fn token_mint_records_held_tokens_of_passing_branches() {
+        let forger = Pubkey::new_unique();
+        let (iron, wood) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = [
+            TestAccount::token_account(iron, forger, 5),
+            TestAccount::token_account(wood, forger, 1),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|acc| acc.info()).collect();
+        // The first branch holds enough iron but fails on the signer, so only
+        // the wood from the second branch is recorded.
+        let constraint = IngredientConstraint::AnyOf {
+            constraints: vec![
+                IngredientConstraint::AllOf {
+                    constraints: vec![
+                        IngredientConstraint::TokenMint {
+                            mint: iron,
+                            amount: 3,
+                        },
+                        signer(Pubkey::new_unique()),
+                    ]
+                    .into(),
+                },
+                IngredientConstraint::TokenMint {
+                    mint: wood,
+                    amount: 1,
+                },
+            ]
+            .into(),
+        };
+
+        let mut verifier = IngredientVerifier::new(Pubkey::default(), forger, 0, &infos);
+        verifier.verify(&constraint).unwrap();
+        assert_eq!(
+            verifier.held_tokens(),
+            &[HeldToken {
+                mint: wood,
+                token_account: infos[1].key(),
+                amount: 1,
+            }]
+        );
+        assert_eq!(verifier.inputs(), &[infos[1].key()]);
+    }
+
+    #[test]
+    fn input_hash_binds_concrete_token_accounts() {
+        let recipe = Pubkey::new_unique();
+        let iron = Pubkey::new_unique();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = [
+            TestAccount::token_account(iron, alice, 5),
+            TestAccount::token_account(iron, bob, 5),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|acc| acc.info()).collect();
+        let constraints = [IngredientConstraint::TokenMint {
+            mint: iron,
+            amount: 3,
+        }];
+        let hash = |forger: Pubkey, policy: ReplayPolicy, nonce: u64| {
+            IngredientVerifier::new(recipe, forger, 0, &infos)
+                .input_hash(&constraints, policy, nonce, Vec::new())
+                .unwrap()
+        };
+
+        // Two holders of the same ingredient no longer share a use record.
+        let per_set = ReplayPolicy::OncePerInputSet;
+        assert_ne!(hash(alice, per_set, 0), hash(bob, per_set, 0));
+        assert_eq!(hash(alice, per_set, 0), hash(alice, per_set, 1));
+        let unlimited = ReplayPolicy::Unlimited;
+        assert_ne!(hash(alice, unlimited, 0), hash(alice, unlimited, 1));
+    }
+
+    /// Builds Ed25519 precompile data carrying `message` signed by `public_key`
+    /// (the signature bytes are irrelevant here; the runtime checks them).
+    fn ed25519_instruction_data(public_key: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let public_key_offset = 2 + ED25519_OFFSETS_LEN;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset as u16,
+            u16::MAX,
+            public_key_offset as u16,
+            u16::MAX,
+            message_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(public_key.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn authorization_message(recipe: &Pubkey, forger: &Pubkey, nonce: u64, expiry: i64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(AUTHORIZATION_MESSAGE_LEN);
+        message.extend_from_slice(recipe.as_ref());
+        message.extend_from_slice(forger.as_ref());
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&expiry.to_le_bytes());
+        message
+    }
+
+    #[test]
+    fn signed_authorization_reads_ed25519_instruction() {
+        use solana_instruction::BorrowedInstruction;
+
+        let recipe = Pubkey::new_unique();
+        let forger = Pubkey::new_unique();
+        let backend = Pubkey::new_unique();
+        let constraint = IngredientConstraint::SignedAuthorization { authority: backend };
+
+        let valid =
+            ed25519_instruction_data(&backend, &authorization_message(&recipe, &forger, 42, 100));
+        let expired =
+            ed25519_instruction_data(&backend, &authorization_message(&recipe, &forger, 43, 10));
+        let sysvar_data = |data: &[u8]| {
+            instructions_sysvar::construct_instructions_data(&[BorrowedInstruction {
+                program_id: &ed25519_program::ID,
+                accounts: vec![],
+                data,
+            }])
+        };
+        let sysvar_key = instructions_sysvar::ID;
+        let sysvar_owner = Pubkey::default();
+
+        let mut lamports = 1u64;
+        let mut data = sysvar_data(&valid);
+        let sysvar = AccountInfo::new(
+            &sysvar_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &sysvar_owner,
+            false,
+            0,
+        );
+        let accounts = std::slice::from_ref(&sysvar);
+
+        let chunk = IngredientVerifier::new(recipe, forger, 50, accounts)
+            .verify(&constraint)
+            .unwrap();
+        assert_eq!(chunk[0], 9);
+        assert_eq!(&chunk[33..41], &42u64.to_le_bytes());
+
+        // Bound to the recipe and forger in the message.
+        let err = IngredientVerifier::new(Pubkey::new_unique(), forger, 50, accounts)
+            .verify(&constraint)
+            .unwrap_err();
+        assert_eq!(err, ForgeError::MissingAuthorization.into());
+
+        // The signature covers one forge: its nonce, hashed under every policy.
+        let constraints = [constraint.clone()];
+        let hash = |nonce| {
+            IngredientVerifier::new(recipe, forger, 50, accounts).input_hash(
+                &constraints,
+                ReplayPolicy::OncePerWallet,
+                nonce,
+                Vec::new(),
+            )
+        };
+        let err = hash(7).unwrap_err();
+        assert_eq!(err, ForgeError::AuthorizationNonceMismatch.into());
+        let unauthorized = IngredientVerifier::new(recipe, forger, 50, &[])
+            .input_hash(&[], ReplayPolicy::OncePerWallet, 42, Vec::new())
+            .unwrap();
+        assert_ne!(hash(42).unwrap(), unauthorized);
+
+        // The authority cannot be the forger.
+        let self_signed = IngredientConstraint::SignedAuthorization { authority: forger };
+        assert!(IngredientVerifier::new(recipe, forger, 50, accounts)
+            .verify(&self_signed)
+            .is_err());
+
+        let mut lamports = 1u64;
+        let mut data = sysvar_data(&expired);
+        let sysvar = AccountInfo::new(
+            &sysvar_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &sysvar_owner,
+            false,
+            0,
+        );
+        let err = IngredientVerifier::new(recipe, forger, 50, std::slice::from_ref(&sysvar))
+            .verify(&constraint)
+            .unwrap_err();
+        assert_eq!(err, ForgeError::AuthorizationExpired.into());
+    }
 }
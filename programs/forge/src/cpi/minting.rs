@@ -2,9 +2,13 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, MintTo};
 
 use mpl_token_metadata::{
+    accounts::Metadata,
     instructions::{
-        CreateMasterEditionV3Cpi, CreateMasterEditionV3CpiAccounts, CreateMasterEditionV3InstructionArgs,
-        CreateMetadataAccountV3Cpi, CreateMetadataAccountV3CpiAccounts, CreateMetadataAccountV3InstructionArgs,
+        CreateMasterEditionV3Cpi, CreateMasterEditionV3CpiAccounts,
+        CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3Cpi,
+        CreateMetadataAccountV3CpiAccounts, CreateMetadataAccountV3InstructionArgs,
+        UpdateMetadataAccountV2Cpi, UpdateMetadataAccountV2CpiAccounts,
+        UpdateMetadataAccountV2InstructionArgs,
     },
     types::{Creator, DataV2},
 };
@@ -16,6 +20,9 @@ use crate::{
 
 /// Mints a 1/1 NFT (supply = 1).
 ///
+/// `update_authority` is the `ForgeConfig` PDA, signed with
+/// `update_authority_seeds`, so `evolve_asset` can update the metadata later.
+///
 /// STATUS: Structure ready, requires full Token Metadata CPI integration.
 ///
 /// Full implementation requires:
@@ -46,6 +53,8 @@ pub fn mint_one_of_one<'info>(
     mint: &AccountInfo<'info>,
     mint_ata: &AccountInfo<'info>,
     forger: &AccountInfo<'info>,
+    update_authority: &AccountInfo<'info>,
+    update_authority_seeds: &[&[u8]],
     token_program: &AccountInfo<'info>,
     system_program: &AccountInfo<'info>,
     rent: &AccountInfo<'info>,
@@ -100,7 +109,7 @@ pub fn mint_one_of_one<'info>(
             mint,
             mint_authority: forger,
             payer: forger,
-            update_authority: (forger, true),
+            update_authority: (update_authority, true),
             system_program,
             rent: Some(rent),
         },
@@ -110,8 +119,8 @@ pub fn mint_one_of_one<'info>(
             collection_details: None,
         },
     )
-    .invoke()
-    .map_err(|_| error!(ForgeError::MintingNotImplemented))?;
+    .invoke_signed(&[update_authority_seeds])
+    .map_err(|_| error!(ForgeError::MetadataCreateFailed))?;
 
     // ---------------------------------------------------------------------
     // 3) Create master edition to mark this as an NFT and lock supply.
@@ -121,7 +130,7 @@ pub fn mint_one_of_one<'info>(
         CreateMasterEditionV3CpiAccounts {
             edition: master_edition,
             mint,
-            update_authority: forger,
+            update_authority,
             mint_authority: forger,
             payer: forger,
             metadata,
@@ -129,10 +138,12 @@ pub fn mint_one_of_one<'info>(
             system_program,
             rent: Some(rent),
         },
-        CreateMasterEditionV3InstructionArgs { max_supply: Some(0) },
+        CreateMasterEditionV3InstructionArgs {
+            max_supply: Some(0),
+        },
     )
-    .invoke()
-    .map_err(|_| error!(ForgeError::MintingNotImplemented))?;
+    .invoke_signed(&[update_authority_seeds])
+    .map_err(|_| error!(ForgeError::MetadataCreateFailed))?;
 
     Ok(mint.key())
 }
@@ -160,23 +171,141 @@ pub fn mint_edition<'info>(
     Err(ForgeError::MintingNotImplemented.into())
 }
 
-/// Mints a semi-fungible token (supply > 1, typically used for limited edition items).
-///
-/// STATUS: Structure ready, requires full Token Metadata CPI integration.
+/// Mints `quantity` tokens of a recipe's semi-fungible output mint.
 ///
-/// Full implementation requires:
-/// 1. CPI to create mint with specified supply (decimals typically 0)
-/// 2. CPI to create metadata account
-/// 3. Set collection (if recipe.collection_mint is Some)
-/// 4. Set creators and royalty basis points
+/// The output mint is a PDA whose mint authority is the `ForgeConfig` PDA,
+/// so the mint is signed with `authority_seeds`.
 pub fn mint_semi_fungible<'info>(
-    _recipe: &Recipe,
-    _creators: &[CreatorShare],
-    _metadata_uri: &str,
-    _remaining_accounts: &[AccountInfo<'info>],
+    mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    authority_seeds: &[&[u8]],
+    token_program: &AccountInfo<'info>,
+    quantity: u64,
 ) -> Result<Pubkey> {
-    // TODO: Implement semi-fungible minting
-    // See documentation above for required steps
+    token::mint_to(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            MintTo {
+                mint: mint.clone(),
+                to: token_account.clone(),
+                authority: mint_authority.clone(),
+            },
+            &[authority_seeds],
+        ),
+        quantity,
+    )?;
 
-    Err(ForgeError::MintingNotImplemented.into())
+    Ok(mint.key())
+}
+
+/// Creates the metadata account of a recipe's semi-fungible output mint.
+///
+/// The `ForgeConfig` PDA is both mint and update authority. Creators are
+/// recorded unverified because they do not sign this instruction.
+pub fn create_output_metadata<'info>(
+    creators: &[CreatorShare],
+    metadata_uri: &str,
+    seller_fee_basis_points: u16,
+    name: &str,
+    symbol: &str,
+    accounts: OutputMetadataAccounts<'_, 'info>,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let mpl_creators = (!creators.is_empty()).then(|| {
+        creators
+            .iter()
+            .map(|c| Creator {
+                address: c.address,
+                verified: false,
+                share: c.share,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    CreateMetadataAccountV3Cpi::new(
+        accounts.token_metadata_program,
+        CreateMetadataAccountV3CpiAccounts {
+            metadata: accounts.metadata,
+            mint: accounts.mint,
+            mint_authority: accounts.mint_authority,
+            payer: accounts.payer,
+            update_authority: (accounts.mint_authority, true),
+            system_program: accounts.system_program,
+            rent: Some(accounts.rent),
+        },
+        CreateMetadataAccountV3InstructionArgs {
+            data: DataV2 {
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                uri: metadata_uri.to_string(),
+                seller_fee_basis_points,
+                creators: mpl_creators,
+                collection: None,
+                uses: None,
+            },
+            is_mutable: true,
+            collection_details: None,
+        },
+    )
+    .invoke_signed(&[authority_seeds])
+    .map_err(|_| error!(ForgeError::MetadataCreateFailed))
+}
+
+/// Accounts for [`create_output_metadata`].
+pub struct OutputMetadataAccounts<'a, 'info> {
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub mint_authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub rent: &'a AccountInfo<'info>,
+}
+
+/// Rewrites the name and URI of a forged asset's metadata in place.
+///
+/// Everything else (symbol, royalties, creators) is carried over from the
+/// current metadata. `update_authority` must be the `ForgeConfig` PDA that
+/// minted the asset.
+pub fn update_asset_metadata<'info>(
+    token_metadata_program: &AccountInfo<'info>,
+    metadata: &AccountInfo<'info>,
+    update_authority: &AccountInfo<'info>,
+    update_authority_seeds: &[&[u8]],
+    name: &str,
+    metadata_uri: &str,
+) -> Result<()> {
+    let current = Metadata::safe_deserialize(&metadata.try_borrow_data()?)
+        .map_err(|_| error!(ForgeError::EvolveSourceMismatch))?;
+    require_keys_eq!(
+        current.update_authority,
+        update_authority.key(),
+        ForgeError::EvolveSourceMismatch
+    );
+
+    UpdateMetadataAccountV2Cpi::new(
+        token_metadata_program,
+        UpdateMetadataAccountV2CpiAccounts {
+            metadata,
+            update_authority,
+        },
+        UpdateMetadataAccountV2InstructionArgs {
+            data: Some(DataV2 {
+                name: name.to_string(),
+                // Token Metadata pads stored strings with NULs.
+                symbol: current.symbol.trim_end_matches('\0').to_string(),
+                uri: metadata_uri.to_string(),
+                seller_fee_basis_points: current.seller_fee_basis_points,
+                creators: current.creators,
+                collection: current.collection,
+                uses: current.uses,
+            }),
+            new_update_authority: None,
+            primary_sale_happened: None,
+            is_mutable: None,
+        },
+    )
+    .invoke_signed(&[update_authority_seeds])
+    .map_err(|_| error!(ForgeError::MetadataUpdateFailed))
 }
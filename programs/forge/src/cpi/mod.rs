@@ -1,6 +1,14 @@
+pub mod bubblegum;
+pub mod escrow;
 pub mod ingredients;
 pub mod minting;
+pub mod randomness;
+pub mod realloc;
 pub mod token_metadata;
 
+pub use bubblegum::*;
+pub use escrow::*;
 pub use ingredients::*;
 pub use minting::*;
+pub use randomness::*;
+pub use realloc::*;
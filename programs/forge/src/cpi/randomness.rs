@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use solana_program::hash::hashv;
+
+/// Size of one `(slot, hash)` entry in the SlotHashes sysvar.
+const SLOT_HASH_ENTRY_LEN: usize = 8 + 32;
+
+/// Looks up the hash of `slot` in raw SlotHashes sysvar data.
+///
+/// The sysvar is a Borsh-style vector of `(u64 slot, [u8; 32] hash)` entries
+/// ordered from newest to oldest. It is read in place because deserializing
+/// all 512 entries would waste compute.
+pub fn slot_hash_at(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    let len = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    let entries = data.get(8..8 + len.checked_mul(SLOT_HASH_ENTRY_LEN)?)?;
+    entries
+        .chunks_exact(SLOT_HASH_ENTRY_LEN)
+        .find(|entry| entry[..8] == slot.to_le_bytes())
+        .map(|entry| {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&entry[8..]);
+            hash
+        })
+}
+
+/// Derives the trait-roll randomness for a forger's commit.
+///
+/// Mixing in the recipe and forger keeps commits landing in the same slot
+/// from sharing an outcome.
+pub fn trait_randomness(
+    slot_hash: &[u8; 32],
+    recipe: &Pubkey,
+    forger: &Pubkey,
+    commit_slot: u64,
+) -> [u8; 32] {
+    hashv(&[
+        slot_hash,
+        recipe.as_ref(),
+        forger.as_ref(),
+        &commit_slot.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_hash_lookup() {
+        let mut data = 2u64.to_le_bytes().to_vec();
+        data.extend_from_slice(&11u64.to_le_bytes());
+        data.extend_from_slice(&[0xbb; 32]);
+        data.extend_from_slice(&10u64.to_le_bytes());
+        data.extend_from_slice(&[0xaa; 32]);
+
+        assert_eq!(slot_hash_at(&data, 10), Some([0xaa; 32]));
+        assert_eq!(slot_hash_at(&data, 11), Some([0xbb; 32]));
+        assert_eq!(slot_hash_at(&data, 12), None);
+        assert_eq!(slot_hash_at(&data[..50], 10), None);
+    }
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+/// Resizes a program-owned account to exactly `space` bytes and settles rent.
+///
+/// Growth is funded by `payer` through the System program; when the account
+/// shrinks, lamports above the new rent-exempt minimum go back to `payer`.
+pub fn realloc_to_space<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.data_len() == space {
+        return Ok(());
+    }
+
+    let required = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    if required > current {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            required - current,
+        )?;
+    } else if current > required {
+        let refund = current - required;
+        **account.try_borrow_mut_lamports()? -= refund;
+        **payer.try_borrow_mut_lamports()? += refund;
+    }
+
+    account.resize(space)?;
+    Ok(())
+}
+
+/// Creates `account` as a `space`-byte account owned by this program at the
+/// PDA signed for by `signer_seeds`, with rent paid by `payer`.
+///
+/// Like Anchor's `init`, lamports already sent to the address are kept and
+/// topped up, so prefunding the PDA cannot block its creation.
+pub fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    if current == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            required,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    if required > current {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            required - current,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        &crate::ID,
+    )
+}
@@ -0,0 +1,286 @@
+//! Off-chain preflight of `forge_asset`.
+//!
+//! Wallets fetch the accounts a forge would pass as remaining accounts and
+//! run [`check_eligibility`] against them before building the transaction.
+//! It runs the same [`IngredientVerifier`] the program runs, so every failure
+//! is the error `forge_asset` would return.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    cpi::ingredients::IngredientVerifier,
+    errors::ForgeError,
+    instructions::forge::require_mintable,
+    state::{constants::RECIPE_SEED, Recipe},
+};
+
+/// One account as fetched from the cluster.
+#[derive(Clone, Debug)]
+pub struct SnapshotAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    /// Whether the account will sign the forge transaction (for `Signer`
+    /// ingredients satisfied by someone other than the forger).
+    pub is_signer: bool,
+}
+
+/// The cluster state a forge would run against.
+#[derive(Clone, Debug, Default)]
+pub struct AccountSnapshot {
+    /// Unix timestamp the forge is expected to land at.
+    pub now: i64,
+    /// Remaining accounts, laid out as the forge would pass them.
+    pub accounts: Vec<SnapshotAccount>,
+}
+
+impl AccountSnapshot {
+    /// Runs `f` over the snapshot accounts as the `AccountInfo`s the program
+    /// would see (never writable, and only signing where marked).
+    pub fn with_account_infos<R>(&self, f: impl FnOnce(&[AccountInfo]) -> R) -> R {
+        let mut lamports: Vec<u64> = self.accounts.iter().map(|a| a.lamports).collect();
+        let mut data: Vec<Vec<u8>> = self.accounts.iter().map(|a| a.data.clone()).collect();
+        let account_infos: Vec<AccountInfo> = self
+            .accounts
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((account, lamports), data)| {
+                AccountInfo::new(
+                    &account.key,
+                    account.is_signer,
+                    false,
+                    lamports,
+                    data,
+                    &account.owner,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+        f(&account_infos)
+    }
+}
+
+/// Outcome of a forge preflight.
+#[derive(Debug)]
+pub struct Eligibility {
+    /// Whether the recipe accepts forges at all (status, go-live time, mode
+    /// and supply cap) and the snapshot ends with its extra output accounts.
+    pub recipe: Result<()>,
+    /// Outcome of each top-level ingredient constraint, in recipe order.
+    pub constraints: Vec<Result<()>>,
+}
+
+impl Eligibility {
+    /// Whether `forge_asset` would get past recipe and ingredient checks.
+    pub fn is_eligible(&self) -> bool {
+        self.recipe.is_ok() && self.constraints.iter().all(Result::is_ok)
+    }
+}
+
+/// Checks whether `forger` could forge `recipe` with the accounts in
+/// `snapshot`, reporting every ingredient constraint separately.
+///
+/// Unlike the program, which stops at the first failure, a failing
+/// constraint here releases whatever it claimed and the next one is still
+/// checked, so a wallet can list everything that is missing.
+pub fn check_eligibility(
+    recipe: &Recipe,
+    forger: &Pubkey,
+    snapshot: &AccountSnapshot,
+) -> Eligibility {
+    let recipe_check = require_mintable(recipe, snapshot.now).and_then(|()| {
+        if let Some(cap) = recipe.supply_cap {
+            require!(recipe.minted < cap, ForgeError::SupplyCapReached);
+        }
+        Ok(())
+    });
+
+    let (recipe_key, _) = Pubkey::find_program_address(
+        &[
+            RECIPE_SEED,
+            recipe.forge_config.as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes(),
+        ],
+        &crate::ID,
+    );
+    let (outputs, constraints) = snapshot.with_account_infos(|account_infos| {
+        // Like the program, never treat the trailing output accounts as
+        // ingredients.
+        let (ingredient_accounts, outputs) = match recipe.split_output_accounts(account_infos) {
+            Ok((ingredient_accounts, _)) => (ingredient_accounts, Ok(())),
+            Err(err) => (account_infos, Err(err)),
+        };
+        let mut verifier =
+            IngredientVerifier::new(recipe_key, *forger, snapshot.now, ingredient_accounts);
+        let constraints = recipe
+            .ingredient_constraints
+            .iter()
+            .map(|constraint| verifier.try_verify(constraint).map(|_| ()))
+            .collect();
+        (outputs, constraints)
+    });
+
+    Eligibility {
+        recipe: recipe_check.and(outputs),
+        constraints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        constants::TOKEN_ACCOUNT_LEN, IngredientConstraint, OutputKind, RecipeMode, RecipeOutput,
+        RecipeStatus, ReplayPolicy,
+    };
+
+    fn recipe(ingredient_constraints: Vec<IngredientConstraint>) -> Recipe {
+        Recipe {
+            forge_config: Pubkey::new_unique(),
+            slug: "sword".to_string(),
+            version: 1,
+            output_kind: OutputKind::OneOfOne,
+            supply_cap: Some(1),
+            minted: 0,
+            metadata_uri: "uri".to_string(),
+            creators: vec![],
+            collection_mint: None,
+            go_live_unix_time: Some(100),
+            pause_at: None,
+            retire_at: None,
+            ingredient_constraints,
+            trait_table: vec![],
+            extra_outputs: vec![],
+            mode: RecipeMode::Mint,
+            reversible: false,
+            unforged: 0,
+            replay_policy: ReplayPolicy::Unlimited,
+            status: RecipeStatus::Active,
+            previous_version: None,
+            next_version: None,
+            bump: 255,
+            open_uses: 0,
+            _reserved: [0; 3],
+        }
+    }
+
+    fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> SnapshotAccount {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        SnapshotAccount {
+            key: Pubkey::new_unique(),
+            owner: anchor_spl::token::ID,
+            lamports: 1,
+            data,
+            is_signer: false,
+        }
+    }
+
+    #[test]
+    fn reports_each_constraint() {
+        let forger = Pubkey::new_unique();
+        let (iron, gold) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let recipe = recipe(vec![
+            IngredientConstraint::Signer { authority: forger },
+            IngredientConstraint::TokenMint {
+                mint: iron,
+                amount: 3,
+            },
+            IngredientConstraint::TokenMint {
+                mint: gold,
+                amount: 1,
+            },
+        ]);
+        let snapshot = AccountSnapshot {
+            now: 100,
+            accounts: vec![
+                token_account(&iron, &forger, 2),
+                token_account(&gold, &forger, 1),
+            ],
+        };
+
+        let eligibility = check_eligibility(&recipe, &forger, &snapshot);
+        assert!(eligibility.recipe.is_ok());
+        assert!(eligibility.constraints[0].is_ok());
+        assert_eq!(
+            eligibility.constraints[1],
+            Err(ForgeError::InsufficientTokenBalance.into())
+        );
+        assert!(eligibility.constraints[2].is_ok());
+        assert!(!eligibility.is_eligible());
+    }
+
+    #[test]
+    fn reports_recipe_gates() {
+        let forger = Pubkey::new_unique();
+        let mut recipe = recipe(vec![]);
+        let early = AccountSnapshot {
+            now: 99,
+            accounts: vec![],
+        };
+        assert_eq!(
+            check_eligibility(&recipe, &forger, &early).recipe,
+            Err(ForgeError::RecipeNotLive.into())
+        );
+
+        let live = AccountSnapshot {
+            now: 100,
+            accounts: vec![],
+        };
+        assert!(check_eligibility(&recipe, &forger, &live).is_eligible());
+        recipe.minted = 1;
+        assert_eq!(
+            check_eligibility(&recipe, &forger, &live).recipe,
+            Err(ForgeError::SupplyCapReached.into())
+        );
+    }
+
+    #[test]
+    fn trailing_output_accounts_are_not_ingredients() {
+        let forger = Pubkey::new_unique();
+        let iron = Pubkey::new_unique();
+        let mut recipe = recipe(vec![IngredientConstraint::TokenMint {
+            mint: iron,
+            amount: 1,
+        }]);
+        recipe.extra_outputs = vec![RecipeOutput {
+            output_kind: OutputKind::SemiFungible,
+            metadata_uri: "uri".to_string(),
+            quantity: 1,
+            supply_cap: None,
+            minted: 0,
+        }];
+        let output = || token_account(&Pubkey::new_unique(), &forger, 0);
+        let iron_account = || token_account(&iron, &forger, 1);
+
+        let laid_out = AccountSnapshot {
+            now: 100,
+            accounts: vec![iron_account(), output(), output()],
+        };
+        assert!(check_eligibility(&recipe, &forger, &laid_out).is_eligible());
+
+        // An ingredient in the output positions is not seen.
+        let misplaced = AccountSnapshot {
+            now: 100,
+            accounts: vec![output(), iron_account()],
+        };
+        let eligibility = check_eligibility(&recipe, &forger, &misplaced);
+        assert!(eligibility.recipe.is_ok());
+        assert!(eligibility.constraints[0].is_err());
+
+        let short = AccountSnapshot {
+            now: 100,
+            accounts: vec![iron_account()],
+        };
+        assert_eq!(
+            check_eligibility(&recipe, &forger, &short).recipe,
+            Err(ForgeError::InvalidOutputAccounts.into())
+        );
+    }
+}
@@ -58,4 +58,110 @@ pub enum ForgeError {
     MissingAllowlistProof,
     #[msg("Asset minting functionality is not yet implemented.")]
     MintingNotImplemented,
+    #[msg("Recipe must be retired before it can be closed.")]
+    RecipeNotRetired,
+    #[msg("Remaining accounts are not laid out as the instruction expects.")]
+    InvalidRemainingAccounts,
+    #[msg("Recipe use record does not belong to this recipe.")]
+    RecipeUseMismatch,
+    #[msg("Refund account does not match the recipe use payer.")]
+    RecipeUsePayerMismatch,
+    #[msg("Recipe status transition is not allowed.")]
+    RecipeStatusTransitionInvalid,
+    #[msg("Creator shares must sum to 100.")]
+    InvalidCreatorShares,
+    #[msg("Edition parent mint not found in remaining accounts.")]
+    EditionParentMissing,
+    #[msg("Recipe has no collection configured on itself or the forge.")]
+    CollectionNotConfigured,
+    #[msg("Recipe pause time must precede its retire time.")]
+    InvalidRecipeSchedule,
+    #[msg("Field cannot change after the recipe has minted; fork a new version instead.")]
+    RecipeFieldFrozen,
+    #[msg("Previous version account was not provided or does not match the argument.")]
+    PreviousVersionMissing,
+    #[msg("Previous version must share forge and slug and have a lower version.")]
+    PreviousVersionMismatch,
+    #[msg("Previous version already links to a newer version.")]
+    PreviousVersionAlreadyLinked,
+    #[msg("Composite ingredient constraints are nested too deeply.")]
+    IngredientNestingTooDeep,
+    #[msg("Composite ingredient must list constraints and a threshold within range.")]
+    InvalidCompositeIngredient,
+    #[msg("Composite ingredient constraint was not satisfied.")]
+    CompositeIngredientUnsatisfied,
+    #[msg("Custom seeds must be at most 32 bytes and derive from at least one seed.")]
+    InvalidCustomSeeds,
+    #[msg("Account at the custom seeds PDA was not provided or is not initialized.")]
+    CustomSeedsAccountMissing,
+    #[msg("No forged asset record from the required recipe was provided.")]
+    ForgedOutputMissing,
+    #[msg("Forger does not hold the forged asset.")]
+    ForgedOutputNotHeld,
+    #[msg("Signed authorization from the required authority was not found.")]
+    MissingAuthorization,
+    #[msg("Signed authorization has expired.")]
+    AuthorizationExpired,
+    #[msg("Authorization must be signed by an authority other than the forger.")]
+    AuthorizationSignerIsForger,
+    #[msg("Trait table has too many tiers, a zero weight, or an overlong name.")]
+    InvalidTraitTable,
+    #[msg("Recipe rolls traits; commit with commit_forge and pass the commit and SlotHashes.")]
+    ForgeCommitRequired,
+    #[msg("Trait roll cannot be revealed in the slot it was committed.")]
+    RevealTooEarly,
+    #[msg("Commit slot hash is no longer available.")]
+    ForgeCommitExpired,
+    #[msg(
+        "Forge commit can still be revealed; it can only be cancelled once the recipe is retired."
+    )]
+    ForgeCommitNotExpired,
+    #[msg(
+        "Extra outputs are limited to 3 OneOfOne or SemiFungible outputs with valid quantities."
+    )]
+    InvalidRecipeOutputs,
+    #[msg("Output accounts are missing or do not match the recipe's extra outputs.")]
+    InvalidOutputAccounts,
+    #[msg("Output supply cap reached.")]
+    OutputSupplyCapReached,
+    #[msg("Evolve recipes cannot have a trait table or extra outputs.")]
+    InvalidRecipeMode,
+    #[msg("Recipe mode does not support this instruction.")]
+    RecipeModeMismatch,
+    #[msg("Asset was not forged by a recipe this evolve recipe accepts.")]
+    EvolveSourceMismatch,
+    #[msg("Reversible recipes must mint a single asset without trait rolls or burned inputs.")]
+    InvalidReversibleRecipe,
+    #[msg("Escrow account must be passed exactly when the recipe is reversible.")]
+    ForgeEscrowMismatch,
+    #[msg("Token account for an escrowed ingredient is missing.")]
+    EscrowTokenAccountMissing,
+    #[msg("Recipe is not reversible.")]
+    RecipeNotReversible,
+    #[msg("Once-per-input-set mint recipes need at least one ingredient.")]
+    InvalidReplayPolicy,
+    #[msg("Recipe use account is not the PDA of the computed input hash.")]
+    RecipeUseAddressMismatch,
+    #[msg(
+        "Batch forging needs a semi-fungible recipe without trait table, extra outputs or escrow."
+    )]
+    BatchNotSupported,
+    #[msg("Batch count must be at least 1.")]
+    InvalidBatchCount,
+    #[msg("Recipe still has open use records; close them first.")]
+    RecipeUsesOpen,
+    #[msg("Forged asset has escrowed ingredients; unforge it instead of burning it.")]
+    ForgedOutputEscrowed,
+    #[msg("Signed authorization is for a different nonce than this forge.")]
+    AuthorizationNonceMismatch,
+    #[msg("Recipe still has escrowed ingredients; they must be unforged first.")]
+    RecipeEscrowsOpen,
+    #[msg("Creating the output's metadata or master edition failed.")]
+    MetadataCreateFailed,
+    #[msg("Updating the asset's metadata failed.")]
+    MetadataUpdateFailed,
+    #[msg("Forge config already has the current layout.")]
+    ForgeConfigCurrent,
+    #[msg("Recipe already has the current layout.")]
+    RecipeCurrent,
 }
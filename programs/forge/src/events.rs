@@ -1,8 +1,21 @@
 use anchor_lang::prelude::*;
 
+use crate::instructions::UpdateRecipeArgs;
+
+/// Layout version of the events below.
+///
+/// Every event starts with `schema_version`, then `sequence`, the event's
+/// position among those of its forge (see `ForgeConfig::event_sequence`).
+/// Events emitted before versioning have neither field, so their first byte
+/// is not a version; decoders must check the whole layout, not that byte.
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
 /// Emitted when a new forge configuration is initialized.
 #[event]
+#[derive(Debug)]
 pub struct ForgeInitialized {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub authority: Pubkey,
     pub collection_mint: Option<Pubkey>,
@@ -13,7 +26,10 @@ pub struct ForgeInitialized {
 
 /// Emitted when forge configuration fields are updated.
 #[event]
+#[derive(Debug)]
 pub struct ForgeConfigUpdated {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub authority: Pubkey,
     pub collection_mint: Option<Pubkey>,
@@ -24,7 +40,10 @@ pub struct ForgeConfigUpdated {
 
 /// Emitted when a recipe is created.
 #[event]
+#[derive(Debug)]
 pub struct RecipeCreated {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub recipe: Pubkey,
     pub slug: String,
@@ -32,18 +51,28 @@ pub struct RecipeCreated {
     pub status: crate::state::RecipeStatus,
 }
 
-/// Emitted when a recipe is updated.
+/// Emitted when a recipe is updated, with the fields that changed.
 #[event]
+#[derive(Debug)]
 pub struct RecipeUpdated {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub recipe: Pubkey,
     pub slug: String,
     pub version: u16,
+    /// Previous values of the fields that changed; other fields are unset.
+    pub before: UpdateRecipeArgs,
+    /// New values of the same fields.
+    pub after: UpdateRecipeArgs,
 }
 
 /// Emitted when a recipe status flag changes.
 #[event]
+#[derive(Debug)]
 pub struct RecipeStatusChanged {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub recipe: Pubkey,
     pub previous: crate::state::RecipeStatus,
@@ -52,7 +81,10 @@ pub struct RecipeStatusChanged {
 
 /// Emitted when an asset is successfully forged.
 #[event]
+#[derive(Debug)]
 pub struct AssetForged {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub forge_config: Pubkey,
     pub recipe: Pubkey,
     pub forger: Pubkey,
@@ -60,4 +92,104 @@ pub struct AssetForged {
     pub minted_count: u64,
     pub supply_cap: Option<u64>,
     pub input_hash: [u8; crate::state::constants::HASH_BYTES],
+    pub trait_tier: Option<u8>,
+}
+
+/// Emitted once per `forge_batch`, covering every unit it minted.
+/// `mint` is the Merkle tree the units went into for compressed recipes.
+#[event]
+#[derive(Debug)]
+pub struct BatchForged {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub count: u64,
+    pub minted_count: u64,
+    pub supply_cap: Option<u64>,
+    pub input_hash: [u8; crate::state::constants::HASH_BYTES],
+}
+
+/// Emitted when a retired recipe account is closed.
+#[event]
+#[derive(Debug)]
+pub struct RecipeClosed {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub slug: String,
+    pub version: u16,
+    pub minted: u64,
+}
+
+/// Emitted for each recipe use record closed and refunded.
+#[event]
+#[derive(Debug)]
+pub struct RecipeUseClosed {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub recipe_use: Pubkey,
+    pub payer: Pubkey,
+    pub refunded_lamports: u64,
+}
+
+/// Emitted when a forger commits to a trait roll.
+#[event]
+#[derive(Debug)]
+pub struct ForgeCommitted {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub commit_slot: u64,
+}
+
+/// Emitted for each extra recipe output minted during a forge.
+#[event]
+#[derive(Debug)]
+pub struct OutputMinted {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub output_index: u8,
+    pub mint: Pubkey,
+    pub quantity: u64,
+    pub minted_count: u64,
+}
+
+/// Emitted when an existing forged asset is evolved in place.
+#[event]
+#[derive(Debug)]
+pub struct AssetEvolved {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub forger: Pubkey,
+    pub mint: Pubkey,
+    pub level: u16,
+    pub evolved_count: u64,
+    pub input_hash: [u8; crate::state::constants::HASH_BYTES],
+}
+
+/// Emitted when a forged asset is burned and its escrowed ingredients returned.
+#[event]
+#[derive(Debug)]
+pub struct AssetUnforged {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub forge_config: Pubkey,
+    pub recipe: Pubkey,
+    pub holder: Pubkey,
+    pub mint: Pubkey,
+    pub returned: Vec<crate::state::EscrowedToken>,
+    pub unforged_count: u64,
 }
@@ -0,0 +1,463 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::ForgeError,
+    events::{RecipeClosed, RecipeStatusChanged, RecipeUseClosed, EVENT_SCHEMA_VERSION},
+    state::{
+        constants::{FORGE_CONFIG_SEED, RECIPE_SEED},
+        ClosedRecipe, ForgeConfig, Recipe, RecipeStatus, RecipeUse, RecipeUseV1,
+    },
+};
+
+/// Closes a retired recipe once all its `RecipeUse` records are closed.
+///
+/// The account is shrunk to a `ClosedRecipe` rather than deleted, so its
+/// slug and version stay taken; the rent above that is refunded to the
+/// authority.
+#[derive(Accounts)]
+pub struct CloseRecipe<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    /// CHECK: deserialized as a `Recipe` and checked against its PDA in
+    /// `close_recipe`, which then rewrites it as a `ClosedRecipe`; Anchor
+    /// would otherwise write the `Recipe` back on exit.
+    #[account(mut, owner = crate::ID)]
+    pub recipe: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn close_recipe(ctx: Context<CloseRecipe>) -> Result<()> {
+    let CloseRecipe {
+        forge_config,
+        recipe: recipe_info,
+        authority,
+    } = ctx.accounts;
+
+    require_keys_eq!(
+        authority.key(),
+        forge_config.authority,
+        ForgeError::UnauthorizedAuthority
+    );
+    // The owner is checked by the account constraint, the discriminator here.
+    let recipe = Recipe::try_deserialize(&mut &recipe_info.try_borrow_data()?[..])?;
+    let expected = Pubkey::create_program_address(
+        &[
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes(),
+            &[recipe.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(anchor_lang::error::ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(
+        recipe_info.key(),
+        expected,
+        anchor_lang::error::ErrorCode::ConstraintSeeds
+    );
+    // A recipe past its `retire_at` is retired even if no one stored it yet.
+    require!(
+        recipe.scheduled_status(Clock::get()?.unix_timestamp) == RecipeStatus::Retired,
+        ForgeError::RecipeNotRetired
+    );
+    require!(recipe.open_uses == 0, ForgeError::RecipeUsesOpen);
+    // `unforge` reads the recipe to release an escrow, so closing it first
+    // would strand the vaulted ingredients.
+    require!(recipe.open_escrows() == 0, ForgeError::RecipeEscrowsOpen);
+
+    let info = recipe_info.to_account_info();
+    let refund = info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(ClosedRecipe::SIZE));
+    **info.try_borrow_mut_lamports()? -= refund;
+    **authority.try_borrow_mut_lamports()? += refund;
+    info.resize(ClosedRecipe::SIZE)?;
+    ClosedRecipe {
+        forge_config: forge_config.key(),
+        minted: recipe.minted,
+    }
+    .try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    emit!(RecipeClosed {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe_info.key(),
+        slug: recipe.slug,
+        version: recipe.version,
+        minted: recipe.minted,
+    });
+
+    Ok(())
+}
+
+/// Closes `RecipeUse` records of a retired recipe in batches.
+///
+/// Remaining accounts are `(recipe_use, payer)` pairs; each record's rent is
+/// refunded to the payer stored on it, or to the forge authority for records
+/// created before the payer was stored (`RecipeUseV1`). Uses can only be closed while the
+/// recipe is `Retired`, which is terminal and rejects every forge, so a
+/// closed record can never be re-initialized to replay its input hash.
+/// `close_recipe` refuses to run until every use has been closed here.
+#[derive(Accounts)]
+pub struct CloseRecipeUses<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    pub authority: Signer<'info>,
+}
+
+pub fn close_recipe_uses<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseRecipeUses<'info>>,
+) -> Result<()> {
+    let CloseRecipeUses {
+        forge_config,
+        recipe,
+        authority,
+    } = ctx.accounts;
+
+    require_keys_eq!(
+        authority.key(),
+        forge_config.authority,
+        ForgeError::UnauthorizedAuthority
+    );
+    // Persist a due `retire_at` first, as `set_recipe_status` does.
+    if let Some(previous) = recipe.apply_schedule(Clock::get()?.unix_timestamp) {
+        emit!(RecipeStatusChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: forge_config.next_event_sequence()?,
+            forge_config: forge_config.key(),
+            recipe: recipe.key(),
+            previous,
+            next: recipe.status,
+        });
+    }
+    require!(
+        recipe.status == RecipeStatus::Retired,
+        ForgeError::RecipeNotRetired
+    );
+
+    let pairs = ctx.remaining_accounts.chunks_exact(2);
+    require!(
+        pairs.len() > 0 && pairs.remainder().is_empty(),
+        ForgeError::InvalidRemainingAccounts
+    );
+
+    for pair in pairs {
+        let (use_info, payer_info) = (&pair[0], &pair[1]);
+
+        let refunded_lamports = use_info.lamports();
+        if use_info.owner == &crate::ID && use_info.data_len() == RecipeUseV1::SIZE {
+            close_recipe_use_v1(use_info, payer_info, &recipe.key(), forge_config)?;
+        } else {
+            // Owner and discriminator checks happen during deserialization.
+            let recipe_use = Account::<RecipeUse>::try_from(use_info)?;
+            require_keys_eq!(
+                recipe_use.recipe,
+                recipe.key(),
+                ForgeError::RecipeUseMismatch
+            );
+            require_keys_eq!(
+                recipe_use.payer,
+                payer_info.key(),
+                ForgeError::RecipeUsePayerMismatch
+            );
+            recipe_use.close(payer_info.clone())?;
+        }
+        recipe.close_use()?;
+
+        emit!(RecipeUseClosed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: forge_config.next_event_sequence()?,
+            forge_config: forge_config.key(),
+            recipe: recipe.key(),
+            recipe_use: use_info.key(),
+            payer: payer_info.key(),
+            refunded_lamports,
+        });
+    }
+
+    Ok(())
+}
+
+/// Closes a `RecipeUseV1`, which has no stored payer, refunding its rent to
+/// the forge authority.
+fn close_recipe_use_v1<'info>(
+    use_info: &AccountInfo<'info>,
+    payer_info: &AccountInfo<'info>,
+    recipe: &Pubkey,
+    forge_config: &ForgeConfig,
+) -> Result<()> {
+    let legacy = {
+        let data = use_info.try_borrow_data()?;
+        require!(
+            data.starts_with(RecipeUse::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        RecipeUseV1::deserialize(&mut &data[8..])
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?
+    };
+    require_keys_eq!(legacy.recipe, *recipe, ForgeError::RecipeUseMismatch);
+    require_keys_eq!(
+        payer_info.key(),
+        forge_config.authority,
+        ForgeError::RecipeUsePayerMismatch
+    );
+
+    // What `Account::close` does for the current layout.
+    let lamports = use_info.lamports();
+    **use_info.try_borrow_mut_lamports()? = 0;
+    **payer_info.try_borrow_mut_lamports()? += lamports;
+    use_info.assign(&anchor_lang::system_program::ID);
+    use_info.resize(0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::instruction::AccountMeta;
+
+    use super::*;
+    use crate::testing::{self, error, rent_exempt, TestEnv};
+
+    struct Fixture {
+        env: TestEnv,
+        authority: Pubkey,
+        forge_config: Pubkey,
+        recipe: Pubkey,
+    }
+
+    fn fixture(edit: impl FnOnce(&mut Recipe)) -> Fixture {
+        let mut env = TestEnv::new();
+        let (authority, forge_config) = env.add_forge_config();
+        let mut recipe = testing::recipe(forge_config);
+        recipe.status = RecipeStatus::Retired;
+        edit(&mut recipe);
+        let recipe = env.add_recipe(recipe);
+        Fixture {
+            env,
+            authority,
+            forge_config,
+            recipe,
+        }
+    }
+
+    impl Fixture {
+        fn add_use(&mut self, payer: Pubkey) -> Pubkey {
+            let key = Pubkey::new_unique();
+            self.env.add_anchor_account(
+                key,
+                &RecipeUse {
+                    recipe: self.recipe,
+                    input_hash: [7; 32],
+                    forged_at: 1,
+                    bump: 255,
+                    payer,
+                    _reserved: [0; 7],
+                },
+            );
+            key
+        }
+
+        fn add_use_v1(&mut self) -> Pubkey {
+            let mut data = RecipeUse::DISCRIMINATOR.to_vec();
+            data.extend_from_slice(self.recipe.as_ref());
+            data.extend_from_slice(&[7; 32]);
+            data.extend_from_slice(&1i64.to_le_bytes());
+            data.push(255);
+            data.extend_from_slice(&[0; 7]);
+            assert_eq!(data.len(), RecipeUseV1::SIZE);
+
+            let key = Pubkey::new_unique();
+            self.env
+                .add_account(key, rent_exempt(data.len()), &data, crate::ID, false);
+            key
+        }
+
+        fn close_recipe(&mut self) -> std::result::Result<(), ProgramError> {
+            self.env.process(
+                crate::accounts::CloseRecipe {
+                    forge_config: self.forge_config,
+                    recipe: self.recipe,
+                    authority: self.authority,
+                },
+                crate::instruction::CloseRecipe {},
+                &[],
+            )
+        }
+
+        fn close_recipe_uses(&mut self, pairs: &[Pubkey]) -> std::result::Result<(), ProgramError> {
+            let remaining: Vec<AccountMeta> = pairs
+                .iter()
+                .map(|key| AccountMeta::new(*key, false))
+                .collect();
+            self.env.process(
+                crate::accounts::CloseRecipeUses {
+                    forge_config: self.forge_config,
+                    recipe: self.recipe,
+                    authority: self.authority,
+                },
+                crate::instruction::CloseRecipeUses {},
+                &remaining,
+            )
+        }
+
+        fn recipe(&self) -> Recipe {
+            self.env.load(&self.recipe)
+        }
+    }
+
+    #[test]
+    fn closing_requires_a_retired_recipe() {
+        let mut f = fixture(|recipe| recipe.status = RecipeStatus::Paused);
+        let payer = f.env.add_wallet(0);
+        let recipe_use = f.add_use(payer);
+
+        let not_retired = Err(error(ForgeError::RecipeNotRetired));
+        assert_eq!(f.close_recipe_uses(&[recipe_use, payer]), not_retired);
+        assert_eq!(f.close_recipe(), not_retired);
+    }
+
+    #[test]
+    fn closing_honours_a_passed_retire_at() {
+        let mut f = fixture(|recipe| {
+            recipe.status = RecipeStatus::Active;
+            recipe.retire_at = Some(100);
+            recipe.open_uses = 1;
+        });
+        let payer = f.env.add_wallet(0);
+        let recipe_use = f.add_use(payer);
+
+        f.env.set_unix_timestamp(99);
+        assert_eq!(
+            f.close_recipe_uses(&[recipe_use, payer]),
+            Err(error(ForgeError::RecipeNotRetired))
+        );
+
+        f.env.set_unix_timestamp(100);
+        f.close_recipe_uses(&[recipe_use, payer]).unwrap();
+        assert_eq!(f.recipe().status, RecipeStatus::Retired);
+        f.close_recipe().unwrap();
+    }
+
+    #[test]
+    fn close_recipe_waits_for_open_uses_and_escrows() {
+        let mut f = fixture(|recipe| recipe.open_uses = 1);
+        assert_eq!(f.close_recipe(), Err(error(ForgeError::RecipeUsesOpen)));
+
+        let mut f = fixture(|recipe| {
+            recipe.reversible = true;
+            recipe.minted = 2;
+            recipe.unforged = 1;
+        });
+        assert_eq!(f.close_recipe(), Err(error(ForgeError::RecipeEscrowsOpen)));
+    }
+
+    #[test]
+    fn close_recipe_uses_refunds_each_stored_payer() {
+        let mut f = fixture(|recipe| recipe.open_uses = 2);
+        let (payer_a, payer_b) = (f.env.add_wallet(0), f.env.add_wallet(5));
+        let (use_a, use_b) = (f.add_use(payer_a), f.add_use(payer_b));
+        let rent = rent_exempt(RecipeUse::SIZE);
+
+        f.close_recipe_uses(&[use_a, payer_a, use_b, payer_b])
+            .unwrap();
+
+        assert_eq!(f.env.lamports(&payer_a), rent);
+        assert_eq!(f.env.lamports(&payer_b), rent + 5);
+        for recipe_use in [use_a, use_b] {
+            assert_eq!(f.env.lamports(&recipe_use), 0);
+            assert_eq!(f.env.owner(&recipe_use), anchor_lang::system_program::ID);
+        }
+        assert_eq!(f.recipe().open_uses, 0);
+    }
+
+    #[test]
+    fn close_recipe_uses_rejects_a_payer_other_than_the_stored_one() {
+        let mut f = fixture(|recipe| recipe.open_uses = 1);
+        let payer = f.env.add_wallet(0);
+        let recipe_use = f.add_use(payer);
+
+        assert_eq!(
+            f.close_recipe_uses(&[recipe_use, f.authority]),
+            Err(error(ForgeError::RecipeUsePayerMismatch))
+        );
+        assert_eq!(f.env.lamports(&recipe_use), rent_exempt(RecipeUse::SIZE));
+    }
+
+    #[test]
+    fn close_recipe_uses_requires_whole_pairs() {
+        let mut f = fixture(|recipe| recipe.open_uses = 1);
+        let payer = f.env.add_wallet(0);
+        let recipe_use = f.add_use(payer);
+
+        let invalid = Err(error(ForgeError::InvalidRemainingAccounts));
+        assert_eq!(f.close_recipe_uses(&[]), invalid);
+        assert_eq!(f.close_recipe_uses(&[recipe_use]), invalid);
+        assert_eq!(
+            f.close_recipe_uses(&[recipe_use, payer, recipe_use]),
+            invalid
+        );
+    }
+
+    #[test]
+    fn close_recipe_uses_refunds_legacy_records_to_the_authority() {
+        let mut f = fixture(|recipe| recipe.open_uses = 1);
+        let recipe_use = f.add_use_v1();
+        let stranger = f.env.add_wallet(0);
+
+        assert_eq!(
+            f.close_recipe_uses(&[recipe_use, stranger]),
+            Err(error(ForgeError::RecipeUsePayerMismatch))
+        );
+
+        let before = f.env.lamports(&f.authority);
+        f.close_recipe_uses(&[recipe_use, f.authority]).unwrap();
+        assert_eq!(
+            f.env.lamports(&f.authority),
+            before + rent_exempt(RecipeUseV1::SIZE)
+        );
+        assert_eq!(f.env.owner(&recipe_use), anchor_lang::system_program::ID);
+        assert!(f.env.data(&recipe_use).is_empty());
+    }
+
+    #[test]
+    fn close_recipe_keeps_the_slug_and_version_reserved() {
+        let mut f = fixture(|recipe| recipe.minted = 4);
+        let recipe_rent = f.env.lamports(&f.recipe);
+        let before = f.env.lamports(&f.authority);
+
+        f.close_recipe().unwrap();
+
+        let closed: ClosedRecipe = f.env.load(&f.recipe);
+        assert_eq!(closed.forge_config, f.forge_config);
+        assert_eq!(closed.minted, 4);
+        assert_eq!(f.env.owner(&f.recipe), crate::ID);
+        assert_eq!(f.env.lamports(&f.recipe), rent_exempt(ClosedRecipe::SIZE));
+        assert_eq!(
+            f.env.lamports(&f.authority),
+            before + recipe_rent - rent_exempt(ClosedRecipe::SIZE)
+        );
+        assert!(f.close_recipe().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::ForgeError,
+    events::{ForgeCommitted, EVENT_SCHEMA_VERSION},
+    state::{
+        constants::{FORGE_COMMIT_SEED, FORGE_CONFIG_SEED, RECIPE_SEED},
+        ForgeCommit, ForgeConfig, Recipe, RecipeStatus,
+    },
+};
+
+#[derive(Accounts)]
+pub struct CommitForge<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    #[account(
+        init,
+        payer = forger,
+        space = ForgeCommit::SIZE,
+        seeds = [FORGE_COMMIT_SEED, recipe.key().as_ref(), forger.key().as_ref()],
+        bump
+    )]
+    pub forge_commit: Account<'info, ForgeCommit>,
+    #[account(mut)]
+    pub forger: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Commits the forger to a trait roll seeded by the hash of the current slot.
+///
+/// The roll is revealed by `forge_asset` in a later slot, which consumes the
+/// commit. A forger holds at most one commit per recipe.
+pub fn commit_forge(ctx: Context<CommitForge>) -> Result<()> {
+    let CommitForge {
+        forge_config,
+        recipe,
+        forge_commit,
+        forger,
+        ..
+    } = ctx.accounts;
+
+    let clock = Clock::get()?;
+    require!(
+        recipe.scheduled_status(clock.unix_timestamp) == RecipeStatus::Active,
+        ForgeError::RecipeInactive
+    );
+    require!(
+        !recipe.trait_table.is_empty(),
+        ForgeError::InvalidTraitTable
+    );
+
+    // Get bump from PDA derivation
+    let (_, bump) = Pubkey::find_program_address(
+        &[
+            FORGE_COMMIT_SEED,
+            recipe.key().as_ref(),
+            forger.key().as_ref(),
+        ],
+        ctx.program_id,
+    );
+    forge_commit.set_inner(ForgeCommit {
+        recipe: recipe.key(),
+        forger: forger.key(),
+        commit_slot: clock.slot,
+        bump,
+        _reserved: [0; 7],
+    });
+
+    emit!(ForgeCommitted {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe.key(),
+        forger: forger.key(),
+        commit_slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelForgeCommit<'info> {
+    #[account(
+        mut,
+        close = forger,
+        has_one = forger,
+        seeds = [FORGE_COMMIT_SEED, forge_commit.recipe.as_ref(), forger.key().as_ref()],
+        bump = forge_commit.bump
+    )]
+    pub forge_commit: Account<'info, ForgeCommit>,
+    /// CHECK: the committed recipe; it may have been closed since, so it is
+    /// only deserialized when it still holds a `Recipe`.
+    #[account(address = forge_commit.recipe)]
+    pub recipe: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub forger: Signer<'info>,
+}
+
+/// Closes a commit on a recipe that can no longer be forged.
+///
+/// The roll is computable as soon as the commit slot passes, so while the
+/// recipe can still be forged the commit can only be revealed (as the
+/// fallback tier once expired); cancelling and re-committing would be a free
+/// reroll.
+pub fn cancel_forge_commit(ctx: Context<CancelForgeCommit>) -> Result<()> {
+    let info = ctx.accounts.recipe.to_account_info();
+    if info.owner == &crate::ID {
+        if let Ok(recipe) = Recipe::try_deserialize(&mut &info.data.borrow()[..]) {
+            require!(
+                recipe.scheduled_status(Clock::get()?.unix_timestamp) == RecipeStatus::Retired,
+                ForgeError::ForgeCommitNotExpired
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,12 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 
 use crate::{
     errors::ForgeError,
-    events::ForgeConfigUpdated,
-    state::{constants::FORGE_CONFIG_SEED, ForgeConfig},
+    events::{ForgeConfigUpdated, EVENT_SCHEMA_VERSION},
+    state::{constants::FORGE_CONFIG_SEED, ForgeConfig, ForgeConfigV1},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
@@ -58,6 +61,8 @@ pub fn set_forge_config(ctx: Context<SetForgeConfig>, args: SetForgeConfigArgs)
     }
 
     emit!(ForgeConfigUpdated {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         authority: authority.key(),
         collection_mint: forge_config.collection_mint,
@@ -68,3 +73,67 @@ pub fn set_forge_config(ctx: Context<SetForgeConfig>, args: SetForgeConfigArgs)
 
     Ok(())
 }
+
+#[derive(Accounts)]
+pub struct MigrateForgeConfig<'info> {
+    /// CHECK: a `ForgeConfigV1`, which `Account<ForgeConfig>` cannot decode;
+    /// checked in the handler.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [FORGE_CONFIG_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub forge_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a forge config created before `event_sequence` to the current
+/// layout, with the authority paying the extra rent. Its events are numbered
+/// from 0 afterwards.
+pub fn migrate_forge_config(ctx: Context<MigrateForgeConfig>) -> Result<()> {
+    let MigrateForgeConfig {
+        forge_config,
+        authority,
+        system_program,
+    } = ctx.accounts;
+
+    let info = forge_config.to_account_info();
+    require!(
+        info.data_len() == ForgeConfigV1::SIZE,
+        ForgeError::ForgeConfigCurrent
+    );
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.starts_with(ForgeConfig::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        ForgeConfigV1::deserialize(&mut &data[8..])
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?
+    };
+
+    let rent_due = Rent::get()?
+        .minimum_balance(ForgeConfig::SIZE)
+        .saturating_sub(info.lamports());
+    if rent_due > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    info.resize(ForgeConfig::SIZE)?;
+    legacy
+        .upgrade()
+        .try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
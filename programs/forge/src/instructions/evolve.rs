@@ -0,0 +1,238 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    cpi::ingredients::{burn_consumed_assets, IngredientVerifier},
+    cpi::minting::update_asset_metadata,
+    errors::ForgeError,
+    events::{AssetEvolved, EVENT_SCHEMA_VERSION},
+    state::{
+        constants::{
+            FORGED_ASSET_SEED, FORGE_CONFIG_SEED, HASH_BYTES, RECIPE_SEED, RECIPE_USE_SEED,
+        },
+        ForgeConfig, ForgedAsset, Recipe, RecipeMode, RecipeStatus, RecipeUse,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EvolveAssetArgs {
+    pub input_hash: [u8; HASH_BYTES],
+    /// Hashed only under `ReplayPolicy::Unlimited`; pass 0 otherwise.
+    pub nonce: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: EvolveAssetArgs)]
+pub struct EvolveAsset<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    #[account(
+        init,
+        payer = forger,
+        space = RecipeUse::SIZE,
+        seeds = [
+            RECIPE_USE_SEED,
+            recipe.key().as_ref(),
+            args.input_hash.as_ref()
+        ],
+        bump
+    )]
+    pub recipe_use: Account<'info, RecipeUse>,
+    #[account(mut)]
+    pub forger: Signer<'info>,
+
+    /// Provenance record of the asset being evolved.
+    #[account(
+        mut,
+        seeds = [FORGED_ASSET_SEED, forged_asset.mint.as_ref()],
+        bump = forged_asset.bump
+    )]
+    pub forged_asset: Account<'info, ForgedAsset>,
+
+    /// Forger's token account holding the asset.
+    #[account(
+        token::mint = forged_asset.mint,
+        token::authority = forger,
+        constraint = asset_token_account.amount == 1 @ ForgeError::ForgedOutputNotHeld
+    )]
+    pub asset_token_account: Account<'info, TokenAccount>,
+
+    /// Metaplex metadata PDA for the asset's mint.
+    /// CHECK: This is a PDA owned/managed by the Token Metadata program. We verify the PDA
+    /// address via seeds + `seeds::program = token_metadata_program.key()` and only use it for CPI.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            forged_asset.mint.as_ref()
+        ],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// Token Metadata program.
+    /// CHECK: The `address = mpl_token_metadata::ID` constraint ensures this is the canonical
+    /// Metaplex Token Metadata program; we only pass it as the CPI program handle.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Upgrades an asset this forge minted in place: after the ingredients
+/// check out, its metadata takes the recipe's name and URI and its
+/// `ForgedAsset` level goes up by one.
+pub fn evolve_asset<'info>(
+    ctx: Context<'_, '_, '_, 'info, EvolveAsset<'info>>,
+    args: EvolveAssetArgs,
+) -> Result<()> {
+    let EvolveAsset {
+        forge_config,
+        recipe,
+        recipe_use,
+        forger,
+        forged_asset,
+        metadata,
+        token_metadata_program,
+        token_program,
+        ..
+    } = ctx.accounts;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        recipe.scheduled_status(now) == RecipeStatus::Active,
+        ForgeError::RecipeInactive
+    );
+    let RecipeMode::Evolve { source_recipe } = recipe.mode else {
+        return err!(ForgeError::RecipeModeMismatch);
+    };
+
+    if let Some(go_live) = recipe.go_live_unix_time {
+        require!(now >= go_live, ForgeError::RecipeNotLive);
+    }
+
+    if let Some(cap) = recipe.supply_cap {
+        require!(recipe.minted < cap, ForgeError::SupplyCapReached);
+    }
+
+    require_keys_eq!(
+        forged_asset.forge_config,
+        forge_config.key(),
+        ForgeError::EvolveSourceMismatch
+    );
+    if let Some(source_recipe) = source_recipe {
+        require_keys_eq!(
+            forged_asset.recipe,
+            source_recipe,
+            ForgeError::EvolveSourceMismatch
+        );
+    }
+
+    // The asset and its current level lead the ingredient chunks, so under
+    // `OncePerInputSet` each level of an asset is evolved at most once.
+    let target_chunk =
+        forge_hash::chunk::evolve_target(&forged_asset.mint.to_bytes(), forged_asset.level);
+    let mut verifier =
+        IngredientVerifier::new(recipe.key(), forger.key(), now, ctx.remaining_accounts);
+    let computed_hash = verifier.input_hash(
+        &recipe.ingredient_constraints,
+        recipe.replay_policy,
+        args.nonce,
+        vec![target_chunk],
+    )?;
+    require!(
+        computed_hash == args.input_hash,
+        ForgeError::IngredientHashMismatch
+    );
+    // The evolving asset cannot also be spent as one of its own ingredients.
+    require!(
+        verifier
+            .consumed()
+            .iter()
+            .all(|consumed| consumed.mint != forged_asset.mint),
+        ForgeError::EvolveSourceMismatch
+    );
+
+    burn_consumed_assets(
+        verifier.consumed(),
+        &forger.to_account_info(),
+        &token_program.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    let forge_config_seeds: &[&[u8]] = &[
+        FORGE_CONFIG_SEED,
+        forge_config.authority.as_ref(),
+        &[forge_config.bump],
+    ];
+    // The recipe's URI replaces any trait-tier URI the asset was minted
+    // with; the rolled tier stays on record in `ForgedAsset.trait_tier`.
+    update_asset_metadata(
+        &token_metadata_program.to_account_info(),
+        &metadata.to_account_info(),
+        &forge_config.to_account_info(),
+        forge_config_seeds,
+        &recipe.slug,
+        &recipe.metadata_uri,
+    )?;
+
+    forged_asset.level = forged_asset
+        .level
+        .checked_add(1)
+        .ok_or(ForgeError::ArithmeticOverflow)?;
+    recipe.minted = recipe
+        .minted
+        .checked_add(1)
+        .ok_or(ForgeError::ArithmeticOverflow)?;
+
+    // Get bump from PDA derivation
+    let (_, bump) = Pubkey::find_program_address(
+        &[
+            RECIPE_USE_SEED,
+            recipe.key().as_ref(),
+            args.input_hash.as_ref(),
+        ],
+        ctx.program_id,
+    );
+    recipe_use.set_inner(RecipeUse {
+        recipe: recipe.key(),
+        input_hash: args.input_hash,
+        forged_at: now,
+        bump,
+        payer: forger.key(),
+        _reserved: [0; 7],
+    });
+    recipe.open_use()?;
+
+    emit!(AssetEvolved {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe.key(),
+        forger: forger.key(),
+        mint: forged_asset.mint,
+        level: forged_asset.level,
+        evolved_count: recipe.minted,
+        input_hash: args.input_hash,
+    });
+
+    Ok(())
+}
@@ -1,29 +1,43 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::{
+        spl_token::state::{Account as SplTokenAccount, Mint as SplMint},
+        Mint, Token, TokenAccount,
+    },
 };
 
 use crate::{
-    cpi::ingredients::{verify_allowlist, verify_collection_nft, verify_token_mint},
-    cpi::minting::mint_one_of_one,
+    cpi::escrow::{derive_vault_authority, escrow_held_tokens},
+    cpi::ingredients::{burn_consumed_assets, IngredientVerifier},
+    cpi::minting::{mint_one_of_one, mint_semi_fungible},
+    cpi::randomness::{slot_hash_at, trait_randomness},
+    cpi::token_metadata::{derive_master_edition_pda, derive_metadata_pda},
     errors::ForgeError,
-    events::AssetForged,
+    events::{AssetForged, OutputMinted, EVENT_SCHEMA_VERSION},
     state::{
-        constants::{FORGE_CONFIG_SEED, HASH_BYTES, RECIPE_SEED, RECIPE_USE_SEED},
-        ForgeConfig, IngredientConstraint, Recipe, RecipeStatus, RecipeUse,
+        constants::{
+            FORGED_ASSET_SEED, FORGE_COMMIT_SEED, FORGE_CONFIG_SEED, FORGE_ESCROW_SEED, HASH_BYTES,
+            OUTPUT_MINT_SEED, RECIPE_SEED, RECIPE_USE_SEED,
+        },
+        ForgeCommit, ForgeConfig, ForgeEscrow, ForgedAsset, OutputKind, Recipe, RecipeMode,
+        RecipeOutput, RecipeStatus, RecipeUse,
     },
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ForgeAssetArgs {
     pub input_hash: [u8; HASH_BYTES],
+    /// Hashed only under `ReplayPolicy::Unlimited`; pass 0 otherwise.
+    pub nonce: u64,
 }
 
 #[derive(Accounts)]
 #[instruction(args: ForgeAssetArgs)]
 pub struct ForgeAsset<'info> {
     #[account(
+        mut,
         seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
         bump = forge_config.bump
     )]
@@ -58,7 +72,6 @@ pub struct ForgeAsset<'info> {
     // Minting accounts (kept OUT of remaining_accounts to avoid breaking
     // ingredient verification heuristics).
     // ---------------------------------------------------------------------
-
     /// The newly-created mint for the forged NFT.
     #[account(
         init,
@@ -78,6 +91,42 @@ pub struct ForgeAsset<'info> {
     )]
     pub mint_ata: Account<'info, TokenAccount>,
 
+    /// Provenance record linking the new mint back to `recipe`.
+    #[account(
+        init,
+        payer = forger,
+        space = ForgedAsset::SIZE,
+        seeds = [FORGED_ASSET_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub forged_asset: Account<'info, ForgedAsset>,
+
+    /// Escrow record of the ingredients taken; required exactly when the
+    /// recipe is reversible.
+    #[account(
+        init,
+        payer = forger,
+        space = ForgeEscrow::space(recipe.token_ingredient_count()),
+        seeds = [FORGE_ESCROW_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub forge_escrow: Option<Account<'info, ForgeEscrow>>,
+
+    /// Trait roll commit; required when the recipe has a trait table and
+    /// closed back to the forger once revealed.
+    #[account(
+        mut,
+        close = forger,
+        seeds = [FORGE_COMMIT_SEED, recipe.key().as_ref(), forger.key().as_ref()],
+        bump = forge_commit.bump
+    )]
+    pub forge_commit: Option<Account<'info, ForgeCommit>>,
+
+    /// SlotHashes sysvar, read in place to reveal the committed roll.
+    /// CHECK: Address-constrained to the SlotHashes sysvar; only its raw data is read.
+    #[account(address = solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
     /// Token Metadata program.
     ///
     /// Checked by address constraint to avoid invoking an arbitrary program.
@@ -120,7 +169,10 @@ pub struct ForgeAsset<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()> {
+pub fn forge_asset<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForgeAsset<'info>>,
+    args: ForgeAssetArgs,
+) -> Result<()> {
     let ForgeAsset {
         forge_config,
         recipe,
@@ -128,6 +180,10 @@ pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()>
         forger,
         mint,
         mint_ata,
+        forged_asset,
+        forge_escrow,
+        forge_commit,
+        slot_hashes,
         token_metadata_program,
         metadata,
         master_edition,
@@ -137,84 +193,202 @@ pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()>
         ..
     } = ctx.accounts;
 
-    require!(
-        recipe.status == RecipeStatus::Active,
-        ForgeError::RecipeInactive
-    );
+    let recipe_key = recipe.key();
+    let forger_key = forger.key();
+    forge(
+        ForgeAccounts {
+            forge_config,
+            recipe,
+            forger,
+            mint,
+            mint_ata,
+            forged_asset,
+            forge_escrow,
+            forge_commit,
+            slot_hashes,
+            token_metadata_program,
+            metadata,
+            master_edition,
+            token_program,
+            system_program,
+            rent,
+        },
+        ctx.program_id,
+        ctx.remaining_accounts,
+        args.nonce,
+        |input_hash, now| {
+            require!(
+                *input_hash == args.input_hash,
+                ForgeError::IngredientHashMismatch
+            );
+            // Get bump from PDA derivation
+            let (_, bump) = Pubkey::find_program_address(
+                &[RECIPE_USE_SEED, recipe_key.as_ref(), input_hash.as_ref()],
+                ctx.program_id,
+            );
+            recipe_use.set_inner(RecipeUse {
+                recipe: recipe_key,
+                input_hash: *input_hash,
+                forged_at: now,
+                bump,
+                payer: forger_key,
+                _reserved: [0; 7],
+            });
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+/// Accounts a forge operates on, apart from its `RecipeUse` record.
+pub(crate) struct ForgeAccounts<'a, 'info> {
+    pub forge_config: &'a mut Account<'info, ForgeConfig>,
+    pub recipe: &'a mut Account<'info, Recipe>,
+    pub forger: &'a Signer<'info>,
+    pub mint: &'a Account<'info, Mint>,
+    pub mint_ata: &'a Account<'info, TokenAccount>,
+    pub forged_asset: &'a mut Account<'info, ForgedAsset>,
+    pub forge_escrow: &'a mut Option<Account<'info, ForgeEscrow>>,
+    pub forge_commit: &'a Option<Account<'info, ForgeCommit>>,
+    pub slot_hashes: &'a Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: &'a UncheckedAccount<'info>,
+    pub metadata: &'a UncheckedAccount<'info>,
+    pub master_edition: &'a UncheckedAccount<'info>,
+    pub token_program: &'a Program<'info, Token>,
+    pub system_program: &'a Program<'info, System>,
+    pub rent: &'a Sysvar<'info, Rent>,
+}
+
+/// Verifies ingredients, mints the recipe's outputs and records provenance,
+/// returning the computed input hash.
+///
+/// `record_use` receives the input hash and the forge time right after
+/// ingredient verification and must persist the `RecipeUse` record (or fail
+/// if the caller's expectations about it do not hold).
+pub(crate) fn forge<'info>(
+    accounts: ForgeAccounts<'_, 'info>,
+    program_id: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    nonce: u64,
+    record_use: impl FnOnce(&[u8; HASH_BYTES], i64) -> Result<()>,
+) -> Result<[u8; HASH_BYTES]> {
+    let ForgeAccounts {
+        forge_config,
+        recipe,
+        forger,
+        mint,
+        mint_ata,
+        forged_asset,
+        forge_escrow,
+        forge_commit,
+        slot_hashes,
+        token_metadata_program,
+        metadata,
+        master_edition,
+        token_program,
+        system_program,
+        rent,
+    } = accounts;
 
     let now = Clock::get()?.unix_timestamp;
-    if let Some(go_live) = recipe.go_live_unix_time {
-        require!(now >= go_live, ForgeError::RecipeNotLive);
-    }
+    require_mintable(recipe, now)?;
 
     if let Some(cap) = recipe.supply_cap {
         require!(recipe.minted < cap, ForgeError::SupplyCapReached);
     }
 
-    // Verify ingredients inline to avoid lifetime issues with Context
-    let computed_hash = {
-        let mut hash_chunks: Vec<Vec<u8>> = Vec::with_capacity(recipe.ingredient_constraints.len());
-
-        for constraint in &recipe.ingredient_constraints {
-            match constraint {
-                IngredientConstraint::Signer { authority } => {
-                    let mut matched = forger.key() == *authority;
-                    if !matched {
-                        matched = ctx
-                            .remaining_accounts
-                            .iter()
-                            .any(|acc| acc.is_signer && *acc.key == *authority);
-                    }
-                    require!(matched, ForgeError::MissingRequiredSigner);
-
-                    let mut chunk = Vec::with_capacity(1 + 32);
-                    chunk.push(0u8);
-                    chunk.extend_from_slice(authority.as_ref());
-                    hash_chunks.push(chunk);
-                }
-                IngredientConstraint::CustomSeeds { seeds } => {
-                    let mut chunk = Vec::with_capacity(1 + seeds.len());
-                    chunk.push(1u8);
-                    chunk.extend_from_slice(seeds);
-                    hash_chunks.push(chunk);
-                }
-                IngredientConstraint::TokenMint { mint, amount } => {
-                    let chunk =
-                        verify_token_mint(&forger.key(), mint, *amount, ctx.remaining_accounts)?;
-                    hash_chunks.push(chunk);
-                }
-                IngredientConstraint::CollectionNft { collection_mint } => {
-                    let chunk = verify_collection_nft(
-                        &forger.key(),
-                        collection_mint,
-                        ctx.remaining_accounts,
-                    )?;
-                    hash_chunks.push(chunk);
-                }
-                IngredientConstraint::Allowlist { merkle_root } => {
-                    let chunk = verify_allowlist(merkle_root, ctx.remaining_accounts)?;
-                    hash_chunks.push(chunk);
-                }
-            }
-        }
+    // Extra outputs take the trailing remaining accounts; everything before
+    // them is ingredient input.
+    let (ingredient_accounts, output_accounts) =
+        recipe.split_output_accounts(remaining_accounts)?;
 
-        use solana_program::hash::hashv;
-        if hash_chunks.is_empty() {
-            // For recipes with no ingredient constraints, include both the forger's pubkey
-            // and the mint pubkey in the hash. This allows each wallet to forge multiple times
-            // (each mint is unique) while preserving security (prevents replay attacks).
-            // Hash: hashv(&[forger_pubkey, mint_pubkey]) = SHA256(forger_pubkey || mint_pubkey)
-            hashv(&[forger.key().as_ref(), mint.key().as_ref()]).to_bytes()
-        } else {
-            let hash_inputs: Vec<&[u8]> =
-                hash_chunks.iter().map(|chunk| chunk.as_slice()).collect();
-            hashv(&hash_inputs).to_bytes()
-        }
-    };
+    // Verify ingredients and bind them, the forger and the nonce into the
+    // input hash as the recipe's replay policy dictates.
+    let mut verifier =
+        IngredientVerifier::new(recipe.key(), forger.key(), now, ingredient_accounts);
+    let input_hash = verifier.input_hash(
+        &recipe.ingredient_constraints,
+        recipe.replay_policy,
+        nonce,
+        Vec::new(),
+    )?;
+    record_use(&input_hash, now)?;
+    recipe.open_use()?;
+
+    // Burn forged inputs consumed by `ForgedOutput { burn: true }` ingredients.
+    burn_consumed_assets(
+        verifier.consumed(),
+        &forger.to_account_info(),
+        &token_program.to_account_info(),
+        ingredient_accounts,
+    )?;
+
+    // Reversible recipes escrow their token ingredients for `unforge`.
     require!(
-        computed_hash == args.input_hash,
-        ForgeError::IngredientHashMismatch
+        forge_escrow.is_some() == recipe.reversible,
+        ForgeError::ForgeEscrowMismatch
     );
+    if let Some(forge_escrow) = forge_escrow.as_mut() {
+        let (vault_authority, _) = derive_vault_authority(&forge_config.key());
+        let items = escrow_held_tokens(
+            verifier.held_tokens(),
+            &forger.to_account_info(),
+            &vault_authority,
+            &token_program.to_account_info(),
+            ingredient_accounts,
+        )?;
+        // Get bump from PDA derivation
+        let (_, escrow_bump) =
+            Pubkey::find_program_address(&[FORGE_ESCROW_SEED, mint.key().as_ref()], program_id);
+        forge_escrow.set_inner(ForgeEscrow {
+            recipe: recipe.key(),
+            mint: mint.key(),
+            items,
+            bump: escrow_bump,
+            _reserved: [0; 7],
+        });
+    }
+
+    // Reveal the committed trait roll, if the recipe has a trait table. A
+    // commit whose slot hash aged out gets the fallback tier instead, so a
+    // forger who has seen a bad roll gains nothing by letting it expire.
+    let trait_tier = if recipe.trait_table.is_empty() {
+        None
+    } else {
+        let (Some(forge_commit), Some(slot_hashes)) = (forge_commit.as_ref(), slot_hashes.as_ref())
+        else {
+            return err!(ForgeError::ForgeCommitRequired);
+        };
+        require!(
+            Clock::get()?.slot > forge_commit.commit_slot,
+            ForgeError::RevealTooEarly
+        );
+        match slot_hash_at(&slot_hashes.try_borrow_data()?, forge_commit.commit_slot) {
+            Some(slot_hash) => {
+                let randomness = trait_randomness(
+                    &slot_hash,
+                    &recipe.key(),
+                    &forger.key(),
+                    forge_commit.commit_slot,
+                );
+                recipe.roll_trait(&randomness)
+            }
+            None => recipe.fallback_trait(),
+        }
+    };
+    let metadata_uri = match trait_tier {
+        Some(tier) => recipe.trait_table[usize::from(tier)].metadata_uri.clone(),
+        None => recipe.metadata_uri.clone(),
+    };
+
+    // The forge config PDA signs as update authority of minted assets and
+    // mint authority of semi-fungible output mints.
+    let forge_authority = forge_config.authority;
+    let forge_config_seeds: &[&[u8]] = &[
+        FORGE_CONFIG_SEED,
+        forge_authority.as_ref(),
+        &[forge_config.bump],
+    ];
 
     // ---------------------------------------------------------------------
     // Mint the output asset (Step 2 MVP: OneOfOne only).
@@ -223,7 +397,7 @@ pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()>
         crate::state::OutputKind::OneOfOne => mint_one_of_one(
             recipe,
             &recipe.creators,
-            &recipe.metadata_uri,
+            &metadata_uri,
             forge_config.default_royalty_bps,
             // Step 2 choice: derive name from slug, constant symbol.
             &recipe.slug,
@@ -234,6 +408,8 @@ pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()>
             &mint.to_account_info(),
             &mint_ata.to_account_info(),
             &forger.to_account_info(),
+            &forge_config.to_account_info(),
+            forge_config_seeds,
             &token_program.to_account_info(),
             &system_program.to_account_info(),
             &rent.to_account_info(),
@@ -247,32 +423,226 @@ pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()>
         .ok_or(ForgeError::ArithmeticOverflow)?;
     recipe.minted = new_minted;
 
+    // Mint extra outputs, each from its own slice of the trailing accounts.
+    let shared = ExtraOutputAccounts {
+        forge_config: forge_config.to_account_info(),
+        forge_config_seeds,
+        forger: forger.to_account_info(),
+        token_metadata_program: token_metadata_program.to_account_info(),
+        token_program: token_program.to_account_info(),
+        system_program: system_program.to_account_info(),
+        rent: rent.to_account_info(),
+    };
+    let mut output_accounts = output_accounts;
+    for index in 0..recipe.extra_outputs.len() {
+        let output = recipe.extra_outputs[index].clone();
+        let new_output_minted = output
+            .minted
+            .checked_add(output.quantity)
+            .ok_or(ForgeError::ArithmeticOverflow)?;
+        if let Some(cap) = output.supply_cap {
+            require!(new_output_minted <= cap, ForgeError::OutputSupplyCapReached);
+        }
+
+        let (accounts, rest) = output_accounts.split_at(output.account_count());
+        output_accounts = rest;
+        let output_index = index as u8;
+        let output_mint = mint_extra_output(
+            recipe,
+            forge_config.default_royalty_bps,
+            output_index,
+            &output,
+            accounts,
+            &shared,
+        )?;
+        recipe.extra_outputs[index].minted = new_output_minted;
+
+        emit!(OutputMinted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: forge_config.next_event_sequence()?,
+            forge_config: forge_config.key(),
+            recipe: recipe.key(),
+            forger: forger.key(),
+            output_index,
+            mint: output_mint,
+            quantity: output.quantity,
+            minted_count: new_output_minted,
+        });
+    }
+
     // Get bump from PDA derivation
-    let (_, bump) = Pubkey::find_program_address(
-        &[
-            RECIPE_USE_SEED,
-            recipe.key().as_ref(),
-            args.input_hash.as_ref(),
-        ],
-        ctx.program_id,
-    );
-    recipe_use.set_inner(RecipeUse {
+    let (_, forged_asset_bump) =
+        Pubkey::find_program_address(&[FORGED_ASSET_SEED, mint.key().as_ref()], program_id);
+    forged_asset.set_inner(ForgedAsset {
+        forge_config: forge_config.key(),
         recipe: recipe.key(),
-        input_hash: args.input_hash,
+        version: recipe.version,
+        forger: forger.key(),
+        mint: minted_mint,
+        input_hash,
+        ordinal: recipe.minted,
         forged_at: now,
-        bump,
-        _reserved: [0; 7],
+        trait_tier,
+        level: 0,
+        bump: forged_asset_bump,
+        escrowed: recipe.reversible,
+        _reserved: [0; 4],
     });
 
     emit!(AssetForged {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         recipe: recipe.key(),
         forger: forger.key(),
         mint: minted_mint,
         minted_count: recipe.minted,
         supply_cap: recipe.supply_cap,
-        input_hash: args.input_hash,
+        input_hash,
+        trait_tier,
     });
 
+    Ok(input_hash)
+}
+
+/// Requires `recipe` to be a live, active mint recipe at `now`.
+pub(crate) fn require_mintable(recipe: &Recipe, now: i64) -> Result<()> {
+    // Scheduled pauses/retirements take effect here even before they are
+    // persisted by the next status change.
+    require!(
+        recipe.scheduled_status(now) == RecipeStatus::Active,
+        ForgeError::RecipeInactive
+    );
+    require!(
+        recipe.mode == RecipeMode::Mint,
+        ForgeError::RecipeModeMismatch
+    );
+
+    if let Some(go_live) = recipe.go_live_unix_time {
+        require!(now >= go_live, ForgeError::RecipeNotLive);
+    }
+    Ok(())
+}
+
+/// Accounts shared by every extra output minted in one forge.
+struct ExtraOutputAccounts<'a, 'info> {
+    forge_config: AccountInfo<'info>,
+    forge_config_seeds: &'a [&'a [u8]],
+    forger: AccountInfo<'info>,
+    token_metadata_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    rent: AccountInfo<'info>,
+}
+
+/// Mints one extra output from its slice of trailing remaining accounts,
+/// returning the mint that received the tokens.
+fn mint_extra_output<'info>(
+    recipe: &Account<'info, Recipe>,
+    royalty_bps: u16,
+    output_index: u8,
+    output: &RecipeOutput,
+    accounts: &[AccountInfo<'info>],
+    shared: &ExtraOutputAccounts<'_, 'info>,
+) -> Result<Pubkey> {
+    match output.output_kind {
+        OutputKind::OneOfOne => {
+            let [mint, token_account, metadata, master_edition] = accounts else {
+                return err!(ForgeError::InvalidOutputAccounts);
+            };
+            // The forger creates a fresh mint in the same transaction, exactly
+            // like the primary output's `mint`.
+            require_keys_eq!(
+                *mint.owner,
+                anchor_spl::token::ID,
+                ForgeError::InvalidOutputAccounts
+            );
+            let mint_state = SplMint::unpack(&mint.try_borrow_data()?)
+                .map_err(|_| error!(ForgeError::InvalidOutputAccounts))?;
+            require!(
+                mint_state.supply == 0
+                    && mint_state.decimals == 0
+                    && mint_state.mint_authority == Some(shared.forger.key()).into(),
+                ForgeError::InvalidOutputAccounts
+            );
+            verify_output_token_account(token_account, &mint.key(), &shared.forger.key())?;
+            require_keys_eq!(
+                metadata.key(),
+                derive_metadata_pda(&mint.key()).0,
+                ForgeError::InvalidOutputAccounts
+            );
+            require_keys_eq!(
+                master_edition.key(),
+                derive_master_edition_pda(&mint.key()).0,
+                ForgeError::InvalidOutputAccounts
+            );
+
+            mint_one_of_one(
+                recipe,
+                &recipe.creators,
+                &output.metadata_uri,
+                royalty_bps,
+                &recipe.slug,
+                "FORGE",
+                &shared.token_metadata_program,
+                metadata,
+                master_edition,
+                mint,
+                token_account,
+                &shared.forger,
+                &shared.forge_config,
+                shared.forge_config_seeds,
+                &shared.token_program,
+                &shared.system_program,
+                &shared.rent,
+            )
+        }
+        OutputKind::SemiFungible => {
+            let [output_mint, token_account] = accounts else {
+                return err!(ForgeError::InvalidOutputAccounts);
+            };
+            let (expected_mint, _) = Pubkey::find_program_address(
+                &[OUTPUT_MINT_SEED, recipe.key().as_ref(), &[output_index]],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                output_mint.key(),
+                expected_mint,
+                ForgeError::InvalidOutputAccounts
+            );
+            verify_output_token_account(token_account, &expected_mint, &shared.forger.key())?;
+
+            mint_semi_fungible(
+                output_mint,
+                token_account,
+                &shared.forge_config,
+                shared.forge_config_seeds,
+                &shared.token_program,
+                output.quantity,
+            )
+        }
+        OutputKind::Edition { .. } | OutputKind::Compressed { .. } => {
+            err!(ForgeError::MintingNotImplemented)
+        }
+    }
+}
+
+/// Requires `token_account` to be an SPL token account for `mint` owned by `owner`.
+fn verify_output_token_account(
+    token_account: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        *token_account.owner,
+        anchor_spl::token::ID,
+        ForgeError::InvalidOutputAccounts
+    );
+    let state = SplTokenAccount::unpack(&token_account.try_borrow_data()?)
+        .map_err(|_| error!(ForgeError::InvalidOutputAccounts))?;
+    require!(
+        state.mint == *mint && state.owner == *owner,
+        ForgeError::InvalidOutputAccounts
+    );
     Ok(())
 }
@@ -0,0 +1,382 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use super::forge::require_mintable;
+use crate::{
+    cpi::bubblegum::{
+        derive_tree_config_pda, mint_compressed, CompressedMintAccounts,
+        ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+    },
+    cpi::ingredients::{burn_consumed_assets, IngredientVerifier},
+    cpi::minting::mint_semi_fungible,
+    errors::ForgeError,
+    events::{BatchForged, EVENT_SCHEMA_VERSION},
+    state::{
+        constants::{
+            FORGE_CONFIG_SEED, HASH_BYTES, OUTPUT_MINT_SEED, PRIMARY_OUTPUT_INDEX, RECIPE_SEED,
+            RECIPE_USE_SEED,
+        },
+        ForgeConfig, OutputKind, Recipe, RecipeUse,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ForgeBatchArgs {
+    pub input_hash: [u8; HASH_BYTES],
+    /// Hashed only under `ReplayPolicy::Unlimited`; pass 0 otherwise.
+    pub nonce: u64,
+    /// Units of the primary output to mint.
+    pub count: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ForgeBatchArgs)]
+pub struct ForgeBatch<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    #[account(
+        init,
+        payer = forger,
+        space = RecipeUse::SIZE,
+        seeds = [
+            RECIPE_USE_SEED,
+            recipe.key().as_ref(),
+            args.input_hash.as_ref()
+        ],
+        bump
+    )]
+    pub recipe_use: Account<'info, RecipeUse>,
+    #[account(mut)]
+    pub forger: Signer<'info>,
+    /// Shared mint of the recipe's primary output, created by
+    /// `init_output_mint` with `PRIMARY_OUTPUT_INDEX`; semi-fungible
+    /// recipes only.
+    #[account(
+        mut,
+        seeds = [OUTPUT_MINT_SEED, recipe.key().as_ref(), &[PRIMARY_OUTPUT_INDEX]],
+        bump
+    )]
+    pub output_mint: Option<Account<'info, Mint>>,
+    /// The forger's token account receiving the batch; semi-fungible
+    /// recipes only. Its mint is checked against `output_mint` in the handler.
+    #[account(mut, token::authority = forger)]
+    pub forger_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// Bubblegum tree config of `merkle_tree`; compressed recipes only.
+    /// CHECK: derived from `merkle_tree` in the handler.
+    #[account(mut)]
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// The recipe's Bubblegum tree; compressed recipes only.
+    /// CHECK: compared with the recipe in the handler, validated by Bubblegum.
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: Bubblegum program; compressed recipes only.
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Noop program; compressed recipes only.
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: SPL Account Compression program; compressed recipes only.
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+}
+
+/// Mints `count` units of a recipe's primary output against a single
+/// ingredient verification and `RecipeUse` record.
+///
+/// `SemiFungible` recipes mint `count` tokens of the primary output mint and
+/// need `output_mint` and `forger_token_account`. `Compressed` recipes mint
+/// `count` compressed NFTs owned by the forger into the recipe's Merkle tree
+/// and need `tree_config`, `merkle_tree` and the Bubblegum, Noop and Account
+/// Compression programs. Accounts of the other path are omitted.
+///
+/// All remaining accounts are ingredient inputs. The whole batch counts
+/// against `supply_cap` and fails as a whole if it does not fit.
+pub fn forge_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForgeBatch<'info>>,
+    args: ForgeBatchArgs,
+) -> Result<()> {
+    let ForgeBatch {
+        forge_config,
+        recipe,
+        recipe_use,
+        forger,
+        output_mint,
+        forger_token_account,
+        token_program,
+        system_program,
+        tree_config,
+        merkle_tree,
+        bubblegum_program,
+        log_wrapper,
+        compression_program,
+    } = ctx.accounts;
+
+    let now = Clock::get()?.unix_timestamp;
+    require_mintable(recipe, now)?;
+    let new_minted = recipe.batch_minted(args.count)?;
+
+    // Verify ingredients once for the whole batch.
+    let mut verifier =
+        IngredientVerifier::new(recipe.key(), forger.key(), now, ctx.remaining_accounts);
+    let input_hash = verifier.input_hash(
+        &recipe.ingredient_constraints,
+        recipe.replay_policy,
+        args.nonce,
+        Vec::new(),
+    )?;
+    require!(
+        input_hash == args.input_hash,
+        ForgeError::IngredientHashMismatch
+    );
+
+    // Get bump from PDA derivation
+    let (_, bump) = Pubkey::find_program_address(
+        &[RECIPE_USE_SEED, recipe.key().as_ref(), input_hash.as_ref()],
+        ctx.program_id,
+    );
+    recipe_use.set_inner(RecipeUse {
+        recipe: recipe.key(),
+        input_hash,
+        forged_at: now,
+        bump,
+        payer: forger.key(),
+        _reserved: [0; 7],
+    });
+    recipe.open_use()?;
+
+    burn_consumed_assets(
+        verifier.consumed(),
+        &forger.to_account_info(),
+        &token_program.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    let forge_config_seeds: &[&[u8]] = &[
+        FORGE_CONFIG_SEED,
+        forge_config.authority.as_ref(),
+        &[forge_config.bump],
+    ];
+    // For compressed recipes the event's `mint` is the Merkle tree.
+    let mint = match recipe.output_kind {
+        OutputKind::SemiFungible => {
+            let (Some(output_mint), Some(forger_token_account)) =
+                (output_mint, forger_token_account)
+            else {
+                return err!(ForgeError::InvalidOutputAccounts);
+            };
+            require_keys_eq!(
+                forger_token_account.mint,
+                output_mint.key(),
+                ForgeError::InvalidOutputAccounts
+            );
+            mint_semi_fungible(
+                &output_mint.to_account_info(),
+                &forger_token_account.to_account_info(),
+                &forge_config.to_account_info(),
+                forge_config_seeds,
+                &token_program.to_account_info(),
+                args.count,
+            )?
+        }
+        OutputKind::Compressed {
+            merkle_tree: recipe_tree,
+        } => {
+            let (
+                Some(tree_config),
+                Some(merkle_tree),
+                Some(bubblegum_program),
+                Some(log_wrapper),
+                Some(compression_program),
+            ) = (
+                tree_config,
+                merkle_tree,
+                bubblegum_program,
+                log_wrapper,
+                compression_program,
+            )
+            else {
+                return err!(ForgeError::InvalidOutputAccounts);
+            };
+            require_keys_eq!(
+                merkle_tree.key(),
+                recipe_tree,
+                ForgeError::InvalidOutputAccounts
+            );
+            require_keys_eq!(
+                tree_config.key(),
+                derive_tree_config_pda(&recipe_tree).0,
+                ForgeError::InvalidOutputAccounts
+            );
+            for _ in 0..args.count {
+                mint_compressed(
+                    recipe,
+                    forge_config.default_royalty_bps,
+                    recipe.collection_mint.or(forge_config.collection_mint),
+                    CompressedMintAccounts {
+                        bubblegum_program,
+                        tree_config,
+                        leaf_owner: forger,
+                        merkle_tree,
+                        payer: forger,
+                        tree_delegate: &forge_config.to_account_info(),
+                        log_wrapper,
+                        compression_program,
+                        system_program,
+                    },
+                    forge_config_seeds,
+                )?;
+            }
+            recipe_tree
+        }
+        // `batch_minted` rejects every other output kind.
+        _ => return err!(ForgeError::BatchNotSupported),
+    };
+    recipe.minted = new_minted;
+
+    emit!(BatchForged {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe.key(),
+        forger: forger.key(),
+        mint,
+        count: args.count,
+        minted_count: new_minted,
+        supply_cap: recipe.supply_cap,
+        input_hash,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::ReplayPolicy,
+        testing::{self, TestEnv},
+    };
+
+    /// Runs `forge_batch` for `count` units of a compressed recipe minting
+    /// into `recipe_tree`, passing `merkle_tree` and `tree_config` (or no
+    /// compressed accounts at all). Every case fails before the first CPI.
+    fn forge_compressed(
+        recipe_tree: Pubkey,
+        compressed_accounts: Option<(Pubkey, Pubkey)>,
+        count: u64,
+    ) -> Result<()> {
+        let mut env = TestEnv::new();
+        let (_, forge_config) = env.add_forge_config();
+        let mut recipe = testing::recipe(forge_config);
+        recipe.output_kind = OutputKind::Compressed {
+            merkle_tree: recipe_tree,
+        };
+        let recipe = env.add_recipe(recipe);
+        let forger = env.add_wallet(1_000_000_000);
+        let recipe_use = Pubkey::new_unique();
+        env.add_account(recipe_use, 0, &[0; RecipeUse::SIZE], crate::ID, false);
+        for program in [
+            anchor_spl::token::ID,
+            BUBBLEGUM_PROGRAM_ID,
+            NOOP_PROGRAM_ID,
+            ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ] {
+            env.add_program(program);
+        }
+
+        let input_hash = IngredientVerifier::new(recipe, forger, 0, &[])
+            .input_hash(&[], ReplayPolicy::Unlimited, 1, Vec::new())
+            .unwrap();
+        let unchecked = |env: &mut TestEnv, key: &Pubkey, is_writable: bool| {
+            Some(UncheckedAccount::try_from(env.info(
+                key,
+                false,
+                is_writable,
+            )))
+        };
+        let (tree_config, merkle_tree, bubblegum_program, log_wrapper, compression_program) =
+            match compressed_accounts {
+                Some((merkle_tree, tree_config)) => (
+                    unchecked(&mut env, &tree_config, true),
+                    unchecked(&mut env, &merkle_tree, true),
+                    unchecked(&mut env, &BUBBLEGUM_PROGRAM_ID, false),
+                    unchecked(&mut env, &NOOP_PROGRAM_ID, false),
+                    unchecked(&mut env, &ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                ),
+                None => (None, None, None, None, None),
+            };
+        let mut accounts = ForgeBatch {
+            forge_config: Account::try_from(env.info(&forge_config, false, true))?,
+            recipe: Account::try_from(env.info(&recipe, false, true))?,
+            recipe_use: Account::try_from_unchecked(env.info(&recipe_use, false, true))?,
+            forger: Signer::try_from(env.info(&forger, true, true))?,
+            output_mint: None,
+            forger_token_account: None,
+            token_program: Program::try_from(env.info(&anchor_spl::token::ID, false, false))?,
+            system_program: Program::try_from(env.info(&system_program::ID, false, false))?,
+            tree_config,
+            merkle_tree,
+            bubblegum_program,
+            log_wrapper,
+            compression_program,
+        };
+
+        forge_batch(
+            Context::new(&crate::ID, &mut accounts, &[], ForgeBatchBumps::default()),
+            ForgeBatchArgs {
+                input_hash,
+                nonce: 1,
+                count,
+            },
+        )
+    }
+
+    #[test]
+    fn compressed_batches_need_the_recipe_tree() {
+        let recipe_tree = Pubkey::new_unique();
+        let tree_config = derive_tree_config_pda(&recipe_tree).0;
+        let invalid = Err(error!(ForgeError::InvalidOutputAccounts));
+
+        assert_eq!(forge_compressed(recipe_tree, None, 1), invalid);
+        let other_tree = Pubkey::new_unique();
+        assert_eq!(
+            forge_compressed(recipe_tree, Some((other_tree, tree_config)), 1),
+            invalid
+        );
+        assert_eq!(
+            forge_compressed(recipe_tree, Some((recipe_tree, Pubkey::new_unique())), 1),
+            invalid
+        );
+    }
+
+    #[test]
+    fn compressed_batches_are_capped() {
+        let recipe_tree = Pubkey::new_unique();
+        let accounts = Some((recipe_tree, derive_tree_config_pda(&recipe_tree).0));
+        assert_eq!(
+            forge_compressed(
+                recipe_tree,
+                accounts,
+                crate::state::constants::MAX_COMPRESSED_BATCH + 1
+            ),
+            Err(error!(ForgeError::InvalidBatchCount))
+        );
+    }
+}
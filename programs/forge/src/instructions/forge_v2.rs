@@ -0,0 +1,304 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use super::forge::{forge, ForgeAccounts};
+use crate::{
+    cpi::realloc::create_program_account,
+    errors::ForgeError,
+    state::{
+        constants::{
+            FORGED_ASSET_SEED, FORGE_COMMIT_SEED, FORGE_CONFIG_SEED, FORGE_ESCROW_SEED, HASH_BYTES,
+            RECIPE_SEED, RECIPE_USE_SEED,
+        },
+        ForgeCommit, ForgeConfig, ForgeEscrow, ForgedAsset, Recipe, RecipeUse,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ForgeAssetV2Args {
+    /// Hashed only under `ReplayPolicy::Unlimited`; pass 0 otherwise.
+    pub nonce: u64,
+}
+
+#[derive(Accounts)]
+pub struct ForgeAssetV2<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    /// `RecipeUse` PDA of the input hash the program computes; created by the
+    /// handler once ingredients are verified.
+    /// CHECK: Checked against `[RECIPE_USE_SEED, recipe, input_hash]` before
+    /// it is created.
+    #[account(mut)]
+    pub recipe_use: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub forger: Signer<'info>,
+
+    // ---------------------------------------------------------------------
+    // Minting accounts (kept OUT of remaining_accounts to avoid breaking
+    // ingredient verification heuristics).
+    // ---------------------------------------------------------------------
+    /// The newly-created mint for the forged NFT.
+    #[account(
+        init,
+        payer = forger,
+        mint::decimals = 0,
+        mint::authority = forger,
+        mint::freeze_authority = forger,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// The forger's ATA for the newly-created mint (receives 1 token).
+    #[account(
+        init,
+        payer = forger,
+        associated_token::mint = mint,
+        associated_token::authority = forger,
+    )]
+    pub mint_ata: Account<'info, TokenAccount>,
+
+    /// Provenance record linking the new mint back to `recipe`.
+    #[account(
+        init,
+        payer = forger,
+        space = ForgedAsset::SIZE,
+        seeds = [FORGED_ASSET_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub forged_asset: Account<'info, ForgedAsset>,
+
+    /// Escrow record of the ingredients taken; required exactly when the
+    /// recipe is reversible.
+    #[account(
+        init,
+        payer = forger,
+        space = ForgeEscrow::space(recipe.token_ingredient_count()),
+        seeds = [FORGE_ESCROW_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub forge_escrow: Option<Account<'info, ForgeEscrow>>,
+
+    /// Trait roll commit; required when the recipe has a trait table and
+    /// closed back to the forger once revealed.
+    #[account(
+        mut,
+        close = forger,
+        seeds = [FORGE_COMMIT_SEED, recipe.key().as_ref(), forger.key().as_ref()],
+        bump = forge_commit.bump
+    )]
+    pub forge_commit: Option<Account<'info, ForgeCommit>>,
+
+    /// SlotHashes sysvar, read in place to reveal the committed roll.
+    /// CHECK: Address-constrained to the SlotHashes sysvar; only its raw data is read.
+    #[account(address = solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+
+    /// Token Metadata program.
+    ///
+    /// Checked by address constraint to avoid invoking an arbitrary program.
+    /// CHECK: The `address = mpl_token_metadata::ID` constraint ensures this is the canonical
+    /// Metaplex Token Metadata program; we only pass it as the CPI program handle.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// Metaplex metadata PDA for `mint`.
+    /// Derived with the Token Metadata program as the PDA program.
+    /// CHECK: This is a PDA owned/managed by the Token Metadata program. We verify the PDA
+    /// address via seeds + `seeds::program = token_metadata_program.key()` and only use it for CPI.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// Metaplex master edition PDA for `mint`.
+    /// CHECK: This is a PDA owned/managed by the Token Metadata program. We verify the PDA
+    /// address via seeds + `seeds::program = token_metadata_program.key()` and only use it for CPI.
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Forges like `forge_asset`, but derives the `RecipeUse` record from the
+/// input hash computed on-chain instead of taking the hash as an argument.
+///
+/// The computed hash is returned (Anchor sets it as return data), so clients
+/// can read it back from the transaction or a simulation.
+pub fn forge_asset_v2<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForgeAssetV2<'info>>,
+    args: ForgeAssetV2Args,
+) -> Result<[u8; HASH_BYTES]> {
+    let ForgeAssetV2 {
+        forge_config,
+        recipe,
+        recipe_use,
+        forger,
+        mint,
+        mint_ata,
+        forged_asset,
+        forge_escrow,
+        forge_commit,
+        slot_hashes,
+        token_metadata_program,
+        metadata,
+        master_edition,
+        token_program,
+        system_program,
+        rent,
+        ..
+    } = ctx.accounts;
+
+    let recipe_key = recipe.key();
+    forge(
+        ForgeAccounts {
+            forge_config,
+            recipe,
+            forger,
+            mint,
+            mint_ata,
+            forged_asset,
+            forge_escrow,
+            forge_commit,
+            slot_hashes,
+            token_metadata_program,
+            metadata,
+            master_edition,
+            token_program,
+            system_program,
+            rent,
+        },
+        ctx.program_id,
+        ctx.remaining_accounts,
+        args.nonce,
+        |input_hash, now| {
+            let (expected, bump) = Pubkey::find_program_address(
+                &[RECIPE_USE_SEED, recipe_key.as_ref(), input_hash.as_ref()],
+                ctx.program_id,
+            );
+            if recipe_use.key() != expected {
+                // Lets clients find the right account by simulating first.
+                msg!("Expected recipe_use {}", expected);
+                return err!(ForgeError::RecipeUseAddressMismatch);
+            }
+            require!(recipe_use.data_is_empty(), ForgeError::DuplicateRecipeUse);
+
+            create_program_account(
+                &recipe_use.to_account_info(),
+                RecipeUse::SIZE,
+                &forger.to_account_info(),
+                &system_program.to_account_info(),
+                &[
+                    RECIPE_USE_SEED,
+                    recipe_key.as_ref(),
+                    input_hash.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            let record = RecipeUse {
+                recipe: recipe_key,
+                input_hash: *input_hash,
+                forged_at: now,
+                bump,
+                payer: forger.key(),
+                _reserved: [0; 7],
+            };
+            record.try_serialize(&mut &mut recipe_use.try_borrow_mut_data()?[..])?;
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cpi::ingredients::IngredientVerifier,
+        state::ReplayPolicy,
+        testing::{self, TestEnv},
+    };
+
+    /// A forge with the `sword` recipe and a funded forger, returning
+    /// `(env, recipe, forger)`.
+    fn setup() -> (TestEnv, Pubkey, Pubkey) {
+        let mut env = TestEnv::new();
+        let (_, forge_config) = env.add_forge_config();
+        let recipe = env.add_recipe(testing::recipe(forge_config));
+        let forger = env.add_wallet(1_000_000_000);
+        (env, recipe, forger)
+    }
+
+    #[test]
+    fn recipe_use_must_be_the_pda_of_the_computed_hash() {
+        let (mut env, recipe, forger) = setup();
+        assert_eq!(
+            env.forge_asset_v2(&recipe, &forger, &Pubkey::new_unique(), 1),
+            Err(error!(ForgeError::RecipeUseAddressMismatch))
+        );
+    }
+
+    #[test]
+    fn replayed_inputs_are_rejected() {
+        let (mut env, recipe, forger) = setup();
+        let input_hash = IngredientVerifier::new(recipe, forger, 0, &[])
+            .input_hash(&[], ReplayPolicy::Unlimited, 1, Vec::new())
+            .unwrap();
+        let (recipe_use, bump) = Pubkey::find_program_address(
+            &[RECIPE_USE_SEED, recipe.as_ref(), input_hash.as_ref()],
+            &crate::ID,
+        );
+        env.add_anchor_account(
+            recipe_use,
+            &RecipeUse {
+                recipe,
+                input_hash,
+                forged_at: 0,
+                bump,
+                payer: forger,
+                _reserved: [0; 7],
+            },
+        );
+
+        assert_eq!(
+            env.forge_asset_v2(&recipe, &forger, &recipe_use, 1),
+            Err(error!(ForgeError::DuplicateRecipeUse))
+        );
+        // Another nonce is another input set, with a record of its own.
+        assert_eq!(
+            env.forge_asset_v2(&recipe, &forger, &recipe_use, 2),
+            Err(error!(ForgeError::RecipeUseAddressMismatch))
+        );
+    }
+}
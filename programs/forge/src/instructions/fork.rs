@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    cpi::realloc_to_space,
+    errors::ForgeError,
+    events::{RecipeCreated, RecipeStatusChanged, EVENT_SCHEMA_VERSION},
+    instructions::{check_activation, UpdateRecipeArgs},
+    state::{
+        constants::{FORGE_CONFIG_SEED, RECIPE_SEED},
+        ForgeConfig, Recipe, RecipeOutput, RecipeStatus,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ForkRecipeVersionArgs {
+    /// Fields to change on the new version; unset fields are copied, except
+    /// `pause_at` and `retire_at`, which start unset.
+    pub overrides: UpdateRecipeArgs,
+    /// Initial status of the new version (`Draft` or `Active`).
+    pub status: RecipeStatus,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ForkRecipeVersionArgs)]
+pub struct ForkRecipeVersion<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            previous_recipe.slug.as_bytes(),
+            &previous_recipe.version.to_le_bytes()
+        ],
+        bump = previous_recipe.bump
+    )]
+    pub previous_recipe: Account<'info, Recipe>,
+    #[account(
+        init,
+        payer = authority,
+        space = args.overrides.resulting_space(&previous_recipe),
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            previous_recipe.slug.as_bytes(),
+            &previous_recipe.version.wrapping_add(1).to_le_bytes()
+        ],
+        bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates `slug`/`version + 1` pre-filled from the previous version, links
+/// the two versions both ways and retires the previous version in the same
+/// instruction.
+pub fn fork_recipe_version(
+    ctx: Context<ForkRecipeVersion>,
+    args: ForkRecipeVersionArgs,
+) -> Result<()> {
+    let ForkRecipeVersion {
+        forge_config,
+        previous_recipe,
+        recipe,
+        authority,
+        system_program,
+    } = ctx.accounts;
+
+    require!(
+        forge_config.recipe_creation_enabled,
+        ForgeError::RecipeCreationDisabled
+    );
+    require_keys_eq!(
+        authority.key(),
+        forge_config.authority,
+        ForgeError::UnauthorizedAuthority
+    );
+    require!(
+        args.status.is_valid_initial(),
+        ForgeError::RecipeInvalidInitialStatus
+    );
+    let version = previous_recipe
+        .version
+        .checked_add(1)
+        .ok_or(ForgeError::ArithmeticOverflow)?;
+    previous_recipe.validate_successor(&forge_config.key(), &previous_recipe.slug, version)?;
+
+    // Get bump from PDA derivation
+    let (_, bump) = Pubkey::find_program_address(
+        &[
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            previous_recipe.slug.as_bytes(),
+            &version.to_le_bytes(),
+        ],
+        ctx.program_id,
+    );
+    let mut next = Recipe {
+        forge_config: forge_config.key(),
+        slug: previous_recipe.slug.clone(),
+        version,
+        output_kind: previous_recipe.output_kind,
+        supply_cap: previous_recipe.supply_cap,
+        minted: 0,
+        metadata_uri: previous_recipe.metadata_uri.clone(),
+        creators: previous_recipe.creators.clone(),
+        collection_mint: previous_recipe.collection_mint,
+        go_live_unix_time: previous_recipe.go_live_unix_time,
+        // The previous schedule may already have fired; a fork only gets one
+        // through its overrides.
+        pause_at: None,
+        retire_at: None,
+        ingredient_constraints: previous_recipe.ingredient_constraints.clone(),
+        trait_table: previous_recipe.trait_table.clone(),
+        extra_outputs: previous_recipe
+            .extra_outputs
+            .iter()
+            .cloned()
+            .map(|output| RecipeOutput {
+                minted: 0,
+                ..output
+            })
+            .collect(),
+        mode: previous_recipe.mode,
+        reversible: previous_recipe.reversible,
+        unforged: 0,
+        replay_policy: previous_recipe.replay_policy,
+        status: args.status,
+        previous_version: Some(previous_recipe.key()),
+        next_version: None,
+        bump,
+        open_uses: 0,
+        _reserved: [0; 3],
+    };
+    args.overrides.apply(&mut next)?;
+    recipe.set_inner(next);
+
+    if recipe.status == RecipeStatus::Active {
+        check_activation(recipe, forge_config, ctx.remaining_accounts)?;
+    }
+
+    previous_recipe.next_version = Some(recipe.key());
+    if previous_recipe.status != RecipeStatus::Retired {
+        let previous = previous_recipe.status;
+        previous_recipe.status = RecipeStatus::Retired;
+        previous_recipe.pause_at = None;
+        previous_recipe.retire_at = None;
+
+        emit!(RecipeStatusChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: forge_config.next_event_sequence()?,
+            forge_config: forge_config.key(),
+            recipe: previous_recipe.key(),
+            previous,
+            next: RecipeStatus::Retired,
+        });
+    }
+
+    realloc_to_space(
+        &previous_recipe.to_account_info(),
+        previous_recipe.current_space(),
+        &authority.to_account_info(),
+        &system_program.to_account_info(),
+    )?;
+
+    emit!(RecipeCreated {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe.key(),
+        slug: recipe.slug.clone(),
+        version: recipe.version,
+        status: recipe.status,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, TestEnv};
+
+    #[test]
+    fn forks_need_recipe_creation_enabled() {
+        let mut env = TestEnv::new();
+        let (authority, forge_config) = env.add_forge_config();
+        let mut config: ForgeConfig = env.load(&forge_config);
+        config.recipe_creation_enabled = false;
+        env.add_anchor_account(forge_config, &config);
+        let previous_recipe = env.add_recipe(testing::recipe(forge_config));
+        // Stands in for the account `init` would create.
+        let recipe = Pubkey::new_unique();
+        env.add_anchor_account(recipe, &testing::recipe(forge_config));
+
+        let mut accounts = ForkRecipeVersion {
+            forge_config: Account::try_from(env.info(&forge_config, false, true)).unwrap(),
+            previous_recipe: Account::try_from(env.info(&previous_recipe, false, true)).unwrap(),
+            recipe: Account::try_from(env.info(&recipe, false, true)).unwrap(),
+            authority: Signer::try_from(env.info(&authority, true, true)).unwrap(),
+            system_program: Program::try_from(env.info(&system_program::ID, false, false)).unwrap(),
+        };
+        let res = fork_recipe_version(
+            Context::new(
+                &crate::ID,
+                &mut accounts,
+                &[],
+                ForkRecipeVersionBumps::default(),
+            ),
+            ForkRecipeVersionArgs {
+                overrides: UpdateRecipeArgs::default(),
+                status: RecipeStatus::Draft,
+            },
+        );
+        assert_eq!(res, Err(error!(ForgeError::RecipeCreationDisabled)));
+    }
+}
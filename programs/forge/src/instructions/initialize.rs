@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::{
     errors::ForgeError,
-    events::ForgeInitialized,
+    events::{ForgeInitialized, EVENT_SCHEMA_VERSION},
     state::{constants::FORGE_CONFIG_SEED, ForgeConfig},
 };
 
@@ -53,10 +53,13 @@ pub fn initialize_forge(ctx: Context<InitializeForge>, args: InitializeForgeArgs
         default_royalty_bps: args.default_royalty_bps,
         recipe_creation_enabled: args.recipe_creation_enabled,
         bump,
+        event_sequence: 0,
         _reserved: [0; 5],
     });
 
     emit!(ForgeInitialized {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         authority: authority.key(),
         collection_mint: args.collection_mint,
@@ -1,9 +1,25 @@
+pub mod close;
+pub mod commit;
 pub mod config;
+pub mod evolve;
 pub mod forge;
+pub mod forge_batch;
+pub mod forge_v2;
+pub mod fork;
 pub mod initialize;
+pub mod outputs;
 pub mod recipes;
+pub mod unforge;
 
+pub use close::*;
+pub use commit::*;
 pub use config::*;
+pub use evolve::*;
 pub use forge::*;
+pub use forge_batch::*;
+pub use forge_v2::*;
+pub use fork::*;
 pub use initialize::*;
+pub use outputs::*;
 pub use recipes::*;
+pub use unforge::*;
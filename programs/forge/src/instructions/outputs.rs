@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+use crate::{
+    cpi::minting::{create_output_metadata, OutputMetadataAccounts},
+    errors::ForgeError,
+    state::{
+        constants::{FORGE_CONFIG_SEED, OUTPUT_MINT_SEED, PRIMARY_OUTPUT_INDEX, RECIPE_SEED},
+        ForgeConfig, OutputKind, Recipe,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InitOutputMintArgs {
+    /// Index into `recipe.extra_outputs` of a `SemiFungible` output, or
+    /// `PRIMARY_OUTPUT_INDEX` for the primary output of a `SemiFungible`
+    /// recipe.
+    pub output_index: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(args: InitOutputMintArgs)]
+pub struct InitOutputMint<'info> {
+    #[account(
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    /// Shared mint for the output; the forge PDA mints it on every forge.
+    #[account(
+        init,
+        payer = authority,
+        seeds = [OUTPUT_MINT_SEED, recipe.key().as_ref(), &[args.output_index]],
+        bump,
+        mint::decimals = 0,
+        mint::authority = forge_config,
+        mint::freeze_authority = forge_config,
+    )]
+    pub output_mint: Account<'info, Mint>,
+    /// CHECK: This is a PDA owned/managed by the Token Metadata program. We verify the PDA
+    /// address via seeds + `seeds::program = token_metadata_program.key()` and only use it for CPI.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), output_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: The `address = mpl_token_metadata::ID` constraint ensures this is the canonical
+    /// Metaplex Token Metadata program; we only pass it as the CPI program handle.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Creates the shared mint and metadata of a `SemiFungible` output.
+///
+/// Must run once per such output before the recipe can forge.
+pub fn init_output_mint(ctx: Context<InitOutputMint>, args: InitOutputMintArgs) -> Result<()> {
+    let InitOutputMint {
+        forge_config,
+        recipe,
+        output_mint,
+        metadata,
+        token_metadata_program,
+        authority,
+        system_program,
+        rent,
+        ..
+    } = ctx.accounts;
+
+    require_keys_eq!(
+        authority.key(),
+        forge_config.authority,
+        ForgeError::UnauthorizedAuthority
+    );
+    let (output_kind, metadata_uri) = if args.output_index == PRIMARY_OUTPUT_INDEX {
+        (&recipe.output_kind, &recipe.metadata_uri)
+    } else {
+        let output = recipe
+            .extra_outputs
+            .get(usize::from(args.output_index))
+            .ok_or(ForgeError::InvalidRecipeOutputs)?;
+        (&output.output_kind, &output.metadata_uri)
+    };
+    require!(
+        *output_kind == OutputKind::SemiFungible,
+        ForgeError::InvalidRecipeOutputs
+    );
+
+    let forge_config_seeds: &[&[u8]] = &[
+        FORGE_CONFIG_SEED,
+        forge_config.authority.as_ref(),
+        &[forge_config.bump],
+    ];
+    create_output_metadata(
+        &recipe.creators,
+        metadata_uri,
+        forge_config.default_royalty_bps,
+        &recipe.slug,
+        "FORGE",
+        OutputMetadataAccounts {
+            token_metadata_program: &token_metadata_program.to_account_info(),
+            metadata: &metadata.to_account_info(),
+            mint: &output_mint.to_account_info(),
+            mint_authority: &forge_config.to_account_info(),
+            payer: &authority.to_account_info(),
+            system_program: &system_program.to_account_info(),
+            rent: &rent.to_account_info(),
+        },
+        forge_config_seeds,
+    )
+}
@@ -1,11 +1,15 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::{IsInitialized, Pack};
+use anchor_spl::token::{self, spl_token::state::Mint as SplMint};
 
 use crate::{
+    cpi::realloc_to_space,
     errors::ForgeError,
-    events::{RecipeCreated, RecipeStatusChanged, RecipeUpdated},
+    events::{RecipeCreated, RecipeStatusChanged, RecipeUpdated, EVENT_SCHEMA_VERSION},
     state::{
         constants::{FORGE_CONFIG_SEED, RECIPE_SEED},
-        CreatorShare, ForgeConfig, IngredientConstraint, OutputKind, Recipe, RecipeStatus,
+        CreatorShare, ForgeConfig, IngredientConstraint, OutputKind, Recipe, RecipeMode,
+        RecipeOutput, RecipeStatus, RecipeV1, ReplayPolicy, TraitTier,
     },
 };
 
@@ -19,7 +23,14 @@ pub struct CreateRecipeArgs {
     pub creators: Vec<CreatorShare>,
     pub collection_mint: Option<Pubkey>,
     pub go_live_unix_time: Option<i64>,
+    pub pause_at: Option<i64>,
+    pub retire_at: Option<i64>,
     pub ingredient_constraints: Vec<IngredientConstraint>,
+    pub trait_table: Vec<TraitTier>,
+    pub extra_outputs: Vec<RecipeOutput>,
+    pub mode: RecipeMode,
+    pub reversible: bool,
+    pub replay_policy: ReplayPolicy,
     pub status: RecipeStatus,
     pub previous_version: Option<Pubkey>,
 }
@@ -41,7 +52,9 @@ pub struct CreateRecipe<'info> {
             &args.output_kind,
             args.metadata_uri.len(),
             &args.creators,
-            &args.ingredient_constraints
+            &args.ingredient_constraints,
+            &args.trait_table,
+            &args.extra_outputs
         ),
         seeds = [
             RECIPE_SEED,
@@ -52,6 +65,9 @@ pub struct CreateRecipe<'info> {
         bump
     )]
     pub recipe: Account<'info, Recipe>,
+    /// Recipe referenced by `args.previous_version`; required when it is set.
+    #[account(mut)]
+    pub previous_recipe: Option<Account<'info, Recipe>>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -61,8 +77,9 @@ pub fn create_recipe(ctx: Context<CreateRecipe>, args: CreateRecipeArgs) -> Resu
     let CreateRecipe {
         forge_config,
         recipe,
+        previous_recipe,
         authority,
-        ..
+        system_program,
     } = ctx.accounts;
 
     require!(
@@ -75,17 +92,49 @@ pub fn create_recipe(ctx: Context<CreateRecipe>, args: CreateRecipeArgs) -> Resu
         ForgeError::UnauthorizedAuthority
     );
     require!(
-        args.status != RecipeStatus::Retired,
+        args.status.is_valid_initial(),
         ForgeError::RecipeInvalidInitialStatus
     );
 
     Recipe::validate_lengths(&args.slug, &args.metadata_uri, &args.creators)?;
     Recipe::validate_ingredients(&args.ingredient_constraints)?;
+    Recipe::validate_trait_table(&args.trait_table)?;
+    Recipe::validate_extra_outputs(&args.extra_outputs)?;
+    Recipe::validate_mode(&args.mode, &args.trait_table, &args.extra_outputs)?;
+    Recipe::validate_reversible(
+        args.reversible,
+        &args.mode,
+        &args.ingredient_constraints,
+        &args.trait_table,
+        &args.extra_outputs,
+    )?;
+    Recipe::validate_replay_policy(args.replay_policy, &args.mode, &args.ingredient_constraints)?;
+    Recipe::validate_schedule(args.pause_at, args.retire_at)?;
 
     if let Some(cap) = args.supply_cap {
         require!(cap > 0, ForgeError::SupplyCapReached);
     }
 
+    match (args.previous_version, previous_recipe.as_mut()) {
+        (Some(previous_key), Some(previous)) => {
+            require_keys_eq!(
+                previous.key(),
+                previous_key,
+                ForgeError::PreviousVersionMissing
+            );
+            previous.validate_successor(&forge_config.key(), &args.slug, args.version)?;
+            previous.next_version = Some(recipe.key());
+            realloc_to_space(
+                &previous.to_account_info(),
+                previous.current_space(),
+                &authority.to_account_info(),
+                &system_program.to_account_info(),
+            )?;
+        }
+        (None, None) => {}
+        _ => return err!(ForgeError::PreviousVersionMissing),
+    }
+
     // Get bump from PDA derivation - Anchor finds it automatically during init
     let (_, bump) = Pubkey::find_program_address(
         &[
@@ -107,14 +156,30 @@ pub fn create_recipe(ctx: Context<CreateRecipe>, args: CreateRecipeArgs) -> Resu
         creators: args.creators.clone(),
         collection_mint: args.collection_mint,
         go_live_unix_time: args.go_live_unix_time,
+        pause_at: args.pause_at,
+        retire_at: args.retire_at,
         ingredient_constraints: args.ingredient_constraints.clone(),
+        trait_table: args.trait_table.clone(),
+        extra_outputs: args.extra_outputs.clone(),
+        mode: args.mode,
+        reversible: args.reversible,
+        unforged: 0,
+        replay_policy: args.replay_policy,
         status: args.status,
         previous_version: args.previous_version,
+        next_version: None,
         bump,
-        _reserved: [0; 7],
+        open_uses: 0,
+        _reserved: [0; 3],
     });
 
+    if recipe.status == RecipeStatus::Active {
+        check_activation(recipe, forge_config, ctx.remaining_accounts)?;
+    }
+
     emit!(RecipeCreated {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         recipe: recipe.key(),
         slug: args.slug,
@@ -125,7 +190,7 @@ pub fn create_recipe(ctx: Context<CreateRecipe>, args: CreateRecipeArgs) -> Resu
     Ok(())
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct UpdateRecipeArgs {
     pub metadata_uri: Option<String>,
     pub creators: Option<Vec<CreatorShare>>,
@@ -134,12 +199,252 @@ pub struct UpdateRecipeArgs {
     pub ingredient_constraints: Option<Vec<IngredientConstraint>>,
     pub supply_cap: Option<Option<u64>>,
     pub output_kind: Option<OutputKind>,
+    pub pause_at: Option<Option<i64>>,
+    pub retire_at: Option<Option<i64>>,
+    pub trait_table: Option<Vec<TraitTier>>,
+    pub extra_outputs: Option<Vec<RecipeOutput>>,
+    pub mode: Option<RecipeMode>,
+    pub reversible: Option<bool>,
+    pub replay_policy: Option<ReplayPolicy>,
+}
+
+impl UpdateRecipeArgs {
+    /// Account size `recipe` needs once these overrides are applied.
+    pub fn resulting_space(&self, recipe: &Recipe) -> usize {
+        Recipe::space(
+            recipe.slug.len(),
+            self.output_kind.as_ref().unwrap_or(&recipe.output_kind),
+            self.metadata_uri
+                .as_ref()
+                .map_or(recipe.metadata_uri.len(), |uri| uri.len()),
+            self.creators.as_deref().unwrap_or(&recipe.creators),
+            self.ingredient_constraints
+                .as_deref()
+                .unwrap_or(&recipe.ingredient_constraints),
+            self.trait_table.as_deref().unwrap_or(&recipe.trait_table),
+            self.extra_outputs
+                .as_deref()
+                .unwrap_or(&recipe.extra_outputs),
+        )
+    }
+
+    /// Rejects changes to fields that give past `input_hash` values and
+    /// minted assets their meaning.
+    pub fn ensure_frozen_fields_unchanged(&self, recipe: &Recipe) -> Result<()> {
+        if let Some(ingredients) = &self.ingredient_constraints {
+            require!(
+                *ingredients == recipe.ingredient_constraints,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        if let Some(output_kind) = self.output_kind {
+            require!(
+                output_kind == recipe.output_kind,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        if let Some(supply_cap) = self.supply_cap {
+            require!(
+                supply_cap == recipe.supply_cap,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        if let Some(trait_table) = &self.trait_table {
+            require!(
+                *trait_table == recipe.trait_table,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        if let Some(mode) = self.mode {
+            require!(mode == recipe.mode, ForgeError::RecipeFieldFrozen);
+        }
+        if let Some(reversible) = self.reversible {
+            require!(
+                reversible == recipe.reversible,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        if let Some(replay_policy) = self.replay_policy {
+            require!(
+                replay_policy == recipe.replay_policy,
+                ForgeError::RecipeFieldFrozen
+            );
+        }
+        // Outputs carry their own mint counters, so they are replaced
+        // wholesale and only before the recipe has minted.
+        require!(self.extra_outputs.is_none(), ForgeError::RecipeFieldFrozen);
+        Ok(())
+    }
+
+    /// The overrides that change `recipe`, as `(before, after)`: `recipe`'s
+    /// current values of those fields and the values they are set to. Fields
+    /// left unset or set to their current value are unset in both.
+    pub fn changes(&self, recipe: &Recipe) -> (Self, Self) {
+        fn diff<T: Clone + PartialEq>(
+            target: &Option<T>,
+            current: &T,
+            before: &mut Option<T>,
+            after: &mut Option<T>,
+        ) {
+            if let Some(target) = target.as_ref().filter(|target| *target != current) {
+                *before = Some(current.clone());
+                *after = Some(target.clone());
+            }
+        }
+
+        let (mut before, mut after) = (Self::default(), Self::default());
+        diff(
+            &self.metadata_uri,
+            &recipe.metadata_uri,
+            &mut before.metadata_uri,
+            &mut after.metadata_uri,
+        );
+        diff(
+            &self.creators,
+            &recipe.creators,
+            &mut before.creators,
+            &mut after.creators,
+        );
+        diff(
+            &self.collection_mint,
+            &recipe.collection_mint,
+            &mut before.collection_mint,
+            &mut after.collection_mint,
+        );
+        diff(
+            &self.go_live_unix_time,
+            &recipe.go_live_unix_time,
+            &mut before.go_live_unix_time,
+            &mut after.go_live_unix_time,
+        );
+        diff(
+            &self.ingredient_constraints,
+            &recipe.ingredient_constraints,
+            &mut before.ingredient_constraints,
+            &mut after.ingredient_constraints,
+        );
+        diff(
+            &self.supply_cap,
+            &recipe.supply_cap,
+            &mut before.supply_cap,
+            &mut after.supply_cap,
+        );
+        diff(
+            &self.output_kind,
+            &recipe.output_kind,
+            &mut before.output_kind,
+            &mut after.output_kind,
+        );
+        diff(
+            &self.pause_at,
+            &recipe.pause_at,
+            &mut before.pause_at,
+            &mut after.pause_at,
+        );
+        diff(
+            &self.retire_at,
+            &recipe.retire_at,
+            &mut before.retire_at,
+            &mut after.retire_at,
+        );
+        diff(
+            &self.trait_table,
+            &recipe.trait_table,
+            &mut before.trait_table,
+            &mut after.trait_table,
+        );
+        diff(
+            &self.extra_outputs,
+            &recipe.extra_outputs,
+            &mut before.extra_outputs,
+            &mut after.extra_outputs,
+        );
+        diff(&self.mode, &recipe.mode, &mut before.mode, &mut after.mode);
+        diff(
+            &self.reversible,
+            &recipe.reversible,
+            &mut before.reversible,
+            &mut after.reversible,
+        );
+        diff(
+            &self.replay_policy,
+            &recipe.replay_policy,
+            &mut before.replay_policy,
+            &mut after.replay_policy,
+        );
+        (before, after)
+    }
+
+    /// Validates the overrides and writes them onto `recipe`.
+    pub fn apply(self, recipe: &mut Recipe) -> Result<()> {
+        let new_metadata_uri = self
+            .metadata_uri
+            .unwrap_or_else(|| recipe.metadata_uri.clone());
+        let new_creators = self.creators.unwrap_or_else(|| recipe.creators.clone());
+        let new_collection = self.collection_mint.unwrap_or(recipe.collection_mint);
+        let new_go_live = self.go_live_unix_time.unwrap_or(recipe.go_live_unix_time);
+        let new_ingredients = self
+            .ingredient_constraints
+            .unwrap_or_else(|| recipe.ingredient_constraints.clone());
+        let new_output_kind = self.output_kind.unwrap_or(recipe.output_kind);
+        let new_supply_cap = self.supply_cap.unwrap_or(recipe.supply_cap);
+        let new_pause_at = self.pause_at.unwrap_or(recipe.pause_at);
+        let new_retire_at = self.retire_at.unwrap_or(recipe.retire_at);
+        let new_trait_table = self
+            .trait_table
+            .unwrap_or_else(|| recipe.trait_table.clone());
+        let new_extra_outputs = match self.extra_outputs {
+            Some(extra_outputs) => {
+                Recipe::validate_extra_outputs(&extra_outputs)?;
+                extra_outputs
+            }
+            None => recipe.extra_outputs.clone(),
+        };
+        let new_mode = self.mode.unwrap_or(recipe.mode);
+        let new_reversible = self.reversible.unwrap_or(recipe.reversible);
+        let new_replay_policy = self.replay_policy.unwrap_or(recipe.replay_policy);
+
+        Recipe::validate_lengths(&recipe.slug, &new_metadata_uri, &new_creators)?;
+        Recipe::validate_ingredients(&new_ingredients)?;
+        Recipe::validate_trait_table(&new_trait_table)?;
+        Recipe::validate_mode(&new_mode, &new_trait_table, &new_extra_outputs)?;
+        Recipe::validate_reversible(
+            new_reversible,
+            &new_mode,
+            &new_ingredients,
+            &new_trait_table,
+            &new_extra_outputs,
+        )?;
+        Recipe::validate_replay_policy(new_replay_policy, &new_mode, &new_ingredients)?;
+        Recipe::validate_schedule(new_pause_at, new_retire_at)?;
+
+        if let Some(cap) = new_supply_cap {
+            require!(cap >= recipe.minted, ForgeError::SupplyCapBelowMinted);
+        }
+
+        recipe.metadata_uri = new_metadata_uri;
+        recipe.creators = new_creators;
+        recipe.collection_mint = new_collection;
+        recipe.go_live_unix_time = new_go_live;
+        recipe.ingredient_constraints = new_ingredients;
+        recipe.output_kind = new_output_kind;
+        recipe.supply_cap = new_supply_cap;
+        recipe.pause_at = new_pause_at;
+        recipe.retire_at = new_retire_at;
+        recipe.trait_table = new_trait_table;
+        recipe.extra_outputs = new_extra_outputs;
+        recipe.mode = new_mode;
+        recipe.reversible = new_reversible;
+        recipe.replay_policy = new_replay_policy;
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
 #[instruction(args: UpdateRecipeArgs)]
 pub struct UpdateRecipe<'info> {
     #[account(
+        mut,
         seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
         bump = forge_config.bump
     )]
@@ -165,7 +470,7 @@ pub fn update_recipe(ctx: Context<UpdateRecipe>, args: UpdateRecipeArgs) -> Resu
         forge_config,
         recipe,
         authority,
-        ..
+        system_program,
     } = ctx.accounts;
 
     require_keys_eq!(
@@ -174,50 +479,33 @@ pub fn update_recipe(ctx: Context<UpdateRecipe>, args: UpdateRecipeArgs) -> Resu
         ForgeError::UnauthorizedAuthority
     );
 
-    let new_metadata_uri = args
-        .metadata_uri
-        .unwrap_or_else(|| recipe.metadata_uri.clone());
-    let new_creators = args.creators.unwrap_or_else(|| recipe.creators.clone());
-    let new_collection = args.collection_mint.unwrap_or(recipe.collection_mint);
-    let new_go_live = args.go_live_unix_time.unwrap_or(recipe.go_live_unix_time);
-    let new_ingredients = args
-        .ingredient_constraints
-        .unwrap_or_else(|| recipe.ingredient_constraints.clone());
-    let new_output_kind = args.output_kind.unwrap_or(recipe.output_kind);
-    let new_supply_cap = args.supply_cap.unwrap_or(recipe.supply_cap);
-
-    Recipe::validate_lengths(&recipe.slug, &new_metadata_uri, &new_creators)?;
-    Recipe::validate_ingredients(&new_ingredients)?;
-
-    if let Some(cap) = new_supply_cap {
-        require!(cap >= recipe.minted, ForgeError::SupplyCapBelowMinted);
+    if recipe.is_frozen() {
+        args.ensure_frozen_fields_unchanged(recipe)?;
     }
 
-    let new_space = Recipe::space(
-        recipe.slug.len(),
-        &new_output_kind,
-        new_metadata_uri.len(),
-        &new_creators,
-        &new_ingredients,
-    );
-    let account_info = recipe.to_account_info();
-    if account_info.data_len() < new_space {
-        account_info.resize(new_space)?;
-    }
+    let (before, after) = args.changes(recipe);
+    args.apply(recipe)?;
+    realloc_to_space(
+        &recipe.to_account_info(),
+        recipe.current_space(),
+        &authority.to_account_info(),
+        &system_program.to_account_info(),
+    )?;
 
-    recipe.metadata_uri = new_metadata_uri;
-    recipe.creators = new_creators;
-    recipe.collection_mint = new_collection;
-    recipe.go_live_unix_time = new_go_live;
-    recipe.ingredient_constraints = new_ingredients;
-    recipe.output_kind = new_output_kind;
-    recipe.supply_cap = new_supply_cap;
+    // A live recipe must stay activatable after edits.
+    if recipe.status == RecipeStatus::Active {
+        check_activation(recipe, forge_config, ctx.remaining_accounts)?;
+    }
 
     emit!(RecipeUpdated {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         recipe: recipe.key(),
         slug: recipe.slug.clone(),
         version: recipe.version,
+        before,
+        after,
     });
 
     Ok(())
@@ -231,6 +519,7 @@ pub struct SetRecipeStatusArgs {
 #[derive(Accounts)]
 pub struct SetRecipeStatus<'info> {
     #[account(
+        mut,
         seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
         bump = forge_config.bump
     )]
@@ -246,7 +535,9 @@ pub struct SetRecipeStatus<'info> {
         bump = recipe.bump
     )]
     pub recipe: Account<'info, Recipe>,
+    #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn set_recipe_status(ctx: Context<SetRecipeStatus>, args: SetRecipeStatusArgs) -> Result<()> {
@@ -254,6 +545,7 @@ pub fn set_recipe_status(ctx: Context<SetRecipeStatus>, args: SetRecipeStatusArg
         forge_config,
         recipe,
         authority,
+        system_program,
     } = ctx.accounts;
 
     require_keys_eq!(
@@ -261,19 +553,50 @@ pub fn set_recipe_status(ctx: Context<SetRecipeStatus>, args: SetRecipeStatusArg
         forge_config.authority,
         ForgeError::UnauthorizedAuthority
     );
+
+    // Persist any scheduled transition first so the requested change is
+    // validated against the status the recipe actually holds right now.
+    let now = Clock::get()?.unix_timestamp;
+    if let Some(previous) = recipe.apply_schedule(now) {
+        emit!(RecipeStatusChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: forge_config.next_event_sequence()?,
+            forge_config: forge_config.key(),
+            recipe: recipe.key(),
+            previous,
+            next: recipe.status,
+        });
+    }
+
     require!(
         recipe.status != args.status,
         ForgeError::RecipeStatusUnchanged
     );
     require!(
-        recipe.status != RecipeStatus::Retired || args.status == RecipeStatus::Retired,
+        recipe.status != RecipeStatus::Retired,
         ForgeError::RecipeRetiredImmutable
     );
+    require!(
+        recipe.status.can_transition_to(args.status),
+        ForgeError::RecipeStatusTransitionInvalid
+    );
 
     let previous = recipe.status;
     recipe.status = args.status;
 
+    if recipe.status == RecipeStatus::Active {
+        check_activation(recipe, forge_config, ctx.remaining_accounts)?;
+    }
+    realloc_to_space(
+        &recipe.to_account_info(),
+        recipe.current_space(),
+        &authority.to_account_info(),
+        &system_program.to_account_info(),
+    )?;
+
     emit!(RecipeStatusChanged {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
         forge_config: forge_config.key(),
         recipe: recipe.key(),
         previous,
@@ -282,3 +605,104 @@ pub fn set_recipe_status(ctx: Context<SetRecipeStatus>, args: SetRecipeStatusArg
 
     Ok(())
 }
+
+#[derive(Accounts)]
+pub struct MigrateRecipe<'info> {
+    #[account(
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    /// CHECK: a `RecipeV1`, which `Account<Recipe>` cannot decode; its PDA
+    /// is checked in the handler from the decoded slug and version.
+    #[account(mut, owner = crate::ID)]
+    pub recipe: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrites a recipe created before `open_uses` in the current layout,
+/// resizing it with the authority settling the rent difference, so it can
+/// be updated, forged and eventually closed again. See `RecipeV1::upgrade`
+/// for the values the new fields start with.
+pub fn migrate_recipe(ctx: Context<MigrateRecipe>) -> Result<()> {
+    let MigrateRecipe {
+        forge_config,
+        recipe,
+        authority,
+        system_program,
+    } = ctx.accounts;
+
+    require_keys_eq!(
+        authority.key(),
+        forge_config.authority,
+        ForgeError::UnauthorizedAuthority
+    );
+
+    let info = recipe.to_account_info();
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        require!(
+            Recipe::try_deserialize(&mut &data[..]).is_err(),
+            ForgeError::RecipeCurrent
+        );
+        RecipeV1::try_from_data(&data)?
+    };
+    let expected = Pubkey::create_program_address(
+        &[
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            legacy.slug.as_bytes(),
+            &legacy.version.to_le_bytes(),
+            &[legacy.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(anchor_lang::error::ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(
+        info.key(),
+        expected,
+        anchor_lang::error::ErrorCode::ConstraintSeeds
+    );
+
+    let upgraded = legacy.upgrade()?;
+    realloc_to_space(
+        &info,
+        upgraded.current_space(),
+        &authority.to_account_info(),
+        &system_program.to_account_info(),
+    )?;
+    upgraded.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+/// Runs the activation checks for a recipe about to accept forges.
+///
+/// Edition recipes must pass their parent mint as a remaining account so the
+/// program can confirm it is an initialized SPL mint.
+pub(crate) fn check_activation(
+    recipe: &Recipe,
+    forge_config: &ForgeConfig,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    recipe.validate_activation(forge_config.collection_mint)?;
+
+    if let OutputKind::Edition { parent_mint } = recipe.output_kind {
+        let parent_info = remaining_accounts
+            .iter()
+            .find(|acc| acc.key() == parent_mint)
+            .ok_or(ForgeError::EditionParentMissing)?;
+        require!(
+            parent_info.owner == &token::ID,
+            ForgeError::EditionParentMissing
+        );
+        let data = parent_info.data.borrow();
+        let parent = SplMint::unpack_unchecked(&data)
+            .map_err(|_| error!(ForgeError::EditionParentMissing))?;
+        require!(parent.is_initialized(), ForgeError::EditionParentMissing);
+    }
+
+    Ok(())
+}
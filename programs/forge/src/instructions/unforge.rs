@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::{
+    cpi::escrow::release_escrowed_tokens,
+    errors::ForgeError,
+    events::{AssetUnforged, EVENT_SCHEMA_VERSION},
+    state::{
+        constants::{
+            FORGED_ASSET_SEED, FORGE_CONFIG_SEED, FORGE_ESCROW_SEED, FORGE_VAULT_SEED, RECIPE_SEED,
+        },
+        ForgeConfig, ForgeEscrow, ForgedAsset, Recipe,
+    },
+};
+
+#[derive(Accounts)]
+pub struct Unforge<'info> {
+    #[account(
+        mut,
+        seeds = [FORGE_CONFIG_SEED, forge_config.authority.as_ref()],
+        bump = forge_config.bump
+    )]
+    pub forge_config: Account<'info, ForgeConfig>,
+    #[account(
+        mut,
+        seeds = [
+            RECIPE_SEED,
+            forge_config.key().as_ref(),
+            recipe.slug.as_bytes(),
+            &recipe.version.to_le_bytes()
+        ],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    /// Provenance record of the asset; closed once the asset is burned.
+    #[account(
+        mut,
+        close = holder,
+        has_one = recipe,
+        has_one = mint,
+        seeds = [FORGED_ASSET_SEED, mint.key().as_ref()],
+        bump = forged_asset.bump
+    )]
+    pub forged_asset: Account<'info, ForgedAsset>,
+    /// Ingredients escrowed when the asset was forged.
+    #[account(
+        mut,
+        close = holder,
+        seeds = [FORGE_ESCROW_SEED, mint.key().as_ref()],
+        bump = forge_escrow.bump
+    )]
+    pub forge_escrow: Account<'info, ForgeEscrow>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// Holder's token account for the asset; its single token is burned.
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = holder,
+        constraint = holder_token_account.amount == 1 @ ForgeError::ForgedOutputNotHeld
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA signer owning the vault token accounts; verified via seeds.
+    #[account(seeds = [FORGE_VAULT_SEED, forge_config.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burns a forged asset and returns its escrowed ingredients to the holder.
+///
+/// Remaining accounts: for each escrowed token, the vault's associated token
+/// account and a token account of the holder for the same mint.
+pub fn unforge<'info>(ctx: Context<'_, '_, '_, 'info, Unforge<'info>>) -> Result<()> {
+    let Unforge {
+        forge_config,
+        recipe,
+        forge_escrow,
+        mint,
+        holder_token_account,
+        vault_authority,
+        holder,
+        token_program,
+        ..
+    } = ctx.accounts;
+
+    require!(recipe.reversible, ForgeError::RecipeNotReversible);
+
+    token::burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Burn {
+                mint: mint.to_account_info(),
+                from: holder_token_account.to_account_info(),
+                authority: holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let forge_config_key = forge_config.key();
+    let vault_seeds: &[&[u8]] = &[
+        FORGE_VAULT_SEED,
+        forge_config_key.as_ref(),
+        &[ctx.bumps.vault_authority],
+    ];
+    release_escrowed_tokens(
+        &forge_escrow.items,
+        &holder.key(),
+        &vault_authority.to_account_info(),
+        vault_seeds,
+        &token_program.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+
+    // `minted` keeps counting every asset ever forged so ordinals and the
+    // supply cap stay meaningful; unforges are tracked separately.
+    recipe.unforged = recipe
+        .unforged
+        .checked_add(1)
+        .ok_or(ForgeError::ArithmeticOverflow)?;
+
+    emit!(AssetUnforged {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: forge_config.next_event_sequence()?,
+        forge_config: forge_config.key(),
+        recipe: recipe.key(),
+        holder: holder.key(),
+        mint: mint.key(),
+        returned: forge_escrow.items.clone(),
+        unforged_count: recipe.unforged,
+    });
+
+    Ok(())
+}
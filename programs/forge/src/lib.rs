@@ -1,15 +1,21 @@
 use anchor_lang::prelude::*;
 
 pub mod cpi;
+pub mod eligibility;
 pub mod errors;
 pub mod events;
 pub mod instructions;
 pub mod state;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 use instructions::*;
 pub use instructions::{
-    CreateRecipe, CreateRecipeArgs, ForgeAsset, ForgeAssetArgs, InitializeForge,
-    InitializeForgeArgs, SetForgeConfig, SetForgeConfigArgs, SetRecipeStatus, SetRecipeStatusArgs,
+    CancelForgeCommit, CloseRecipe, CloseRecipeUses, CommitForge, CreateRecipe, CreateRecipeArgs,
+    EvolveAsset, EvolveAssetArgs, ForgeAsset, ForgeAssetArgs, ForgeAssetV2, ForgeAssetV2Args,
+    ForgeBatch, ForgeBatchArgs, ForkRecipeVersion, ForkRecipeVersionArgs, InitOutputMint,
+    InitOutputMintArgs, InitializeForge, InitializeForgeArgs, MigrateForgeConfig, MigrateRecipe,
+    SetForgeConfig, SetForgeConfigArgs, SetRecipeStatus, SetRecipeStatusArgs, Unforge,
     UpdateRecipe, UpdateRecipeArgs,
 };
 
@@ -30,10 +36,18 @@ pub mod forge {
         instructions::set_forge_config(ctx, args)
     }
 
+    pub fn migrate_forge_config(ctx: Context<MigrateForgeConfig>) -> Result<()> {
+        instructions::migrate_forge_config(ctx)
+    }
+
     pub fn create_recipe(ctx: Context<CreateRecipe>, args: CreateRecipeArgs) -> Result<()> {
         instructions::create_recipe(ctx, args)
     }
 
+    pub fn migrate_recipe(ctx: Context<MigrateRecipe>) -> Result<()> {
+        instructions::migrate_recipe(ctx)
+    }
+
     pub fn update_recipe(ctx: Context<UpdateRecipe>, args: UpdateRecipeArgs) -> Result<()> {
         instructions::update_recipe(ctx, args)
     }
@@ -45,7 +59,64 @@ pub mod forge {
         instructions::set_recipe_status(ctx, args)
     }
 
-    pub fn forge_asset(ctx: Context<ForgeAsset>, args: ForgeAssetArgs) -> Result<()> {
+    pub fn forge_asset<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForgeAsset<'info>>,
+        args: ForgeAssetArgs,
+    ) -> Result<()> {
         instructions::forge_asset(ctx, args)
     }
+
+    pub fn forge_asset_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForgeAssetV2<'info>>,
+        args: ForgeAssetV2Args,
+    ) -> Result<[u8; 32]> {
+        instructions::forge_asset_v2(ctx, args)
+    }
+
+    pub fn forge_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForgeBatch<'info>>,
+        args: ForgeBatchArgs,
+    ) -> Result<()> {
+        instructions::forge_batch(ctx, args)
+    }
+
+    pub fn fork_recipe_version(
+        ctx: Context<ForkRecipeVersion>,
+        args: ForkRecipeVersionArgs,
+    ) -> Result<()> {
+        instructions::fork_recipe_version(ctx, args)
+    }
+
+    pub fn close_recipe(ctx: Context<CloseRecipe>) -> Result<()> {
+        instructions::close_recipe(ctx)
+    }
+
+    pub fn close_recipe_uses<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseRecipeUses<'info>>,
+    ) -> Result<()> {
+        instructions::close_recipe_uses(ctx)
+    }
+
+    pub fn commit_forge(ctx: Context<CommitForge>) -> Result<()> {
+        instructions::commit_forge(ctx)
+    }
+
+    pub fn cancel_forge_commit(ctx: Context<CancelForgeCommit>) -> Result<()> {
+        instructions::cancel_forge_commit(ctx)
+    }
+
+    pub fn init_output_mint(ctx: Context<InitOutputMint>, args: InitOutputMintArgs) -> Result<()> {
+        instructions::init_output_mint(ctx, args)
+    }
+
+    pub fn evolve_asset<'info>(
+        ctx: Context<'_, '_, '_, 'info, EvolveAsset<'info>>,
+        args: EvolveAssetArgs,
+    ) -> Result<()> {
+        instructions::evolve_asset(ctx, args)
+    }
+
+    pub fn unforge<'info>(ctx: Context<'_, '_, '_, 'info, Unforge<'info>>) -> Result<()> {
+        instructions::unforge(ctx)
+    }
 }
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+/// What `close_recipe` leaves at a recipe's address.
+///
+/// Keeping the address occupied means the recipe's slug and version can
+/// never be created again, so the input hashes of its closed `RecipeUse`
+/// records cannot be replayed under a new recipe at the same PDA.
+#[account]
+#[derive(Debug)]
+pub struct ClosedRecipe {
+    /// Parent forge configuration PDA.
+    pub forge_config: Pubkey,
+    /// Number of successful mints the recipe recorded.
+    pub minted: u64,
+}
+
+impl ClosedRecipe {
+    pub const SIZE: usize = 8 // discriminator
+        + 32 // forge_config
+        + 8; // minted
+}
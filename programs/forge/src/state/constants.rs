@@ -12,9 +12,30 @@ pub const MAX_METADATA_URI_LENGTH: usize = 200;
 /// Maximum number of ingredient constraints supported by a recipe.
 pub const MAX_INGREDIENTS: usize = 10;
 
+/// Maximum nesting depth of composite ingredient constraints (top level = 1).
+pub const MAX_INGREDIENT_DEPTH: usize = 3;
+
+/// Maximum number of constraint nodes across all nesting levels of a recipe.
+pub const MAX_INGREDIENT_NODES: usize = 24;
+
+/// Maximum length of the `CustomSeeds` ingredient seed (Solana's per-seed limit).
+pub const MAX_PDA_SEED_LENGTH: usize = 32;
+
+/// Maximum number of rarity tiers in a recipe's trait table.
+pub const MAX_TRAIT_TIERS: usize = 8;
+
+/// Maximum length of a trait tier name (in bytes).
+pub const MAX_TRAIT_NAME_LENGTH: usize = 16;
+
+/// Maximum number of outputs a recipe mints in addition to its primary one.
+pub const MAX_EXTRA_OUTPUTS: usize = 3;
+
 /// Size of a 32-byte hash (used for recipe-use records).
 pub const HASH_BYTES: usize = 32;
 
+/// Size of an SPL token account (`spl_token::state::Account::LEN`).
+pub(crate) const TOKEN_ACCOUNT_LEN: usize = 165;
+
 /// Seed prefix used when deriving the `ForgeConfig` PDA.
 pub const FORGE_CONFIG_SEED: &[u8] = b"forge";
 
@@ -23,3 +44,26 @@ pub const RECIPE_SEED: &[u8] = b"recipe";
 
 /// Seed prefix used when deriving a `RecipeUse` PDA.
 pub const RECIPE_USE_SEED: &[u8] = b"recipe-use";
+
+/// Seed prefix used when deriving a `ForgedAsset` PDA.
+pub const FORGED_ASSET_SEED: &[u8] = b"forged-asset";
+
+/// Seed prefix used when deriving a `ForgeCommit` PDA.
+pub const FORGE_COMMIT_SEED: &[u8] = b"forge-commit";
+
+/// Seed prefix used when deriving a recipe's semi-fungible output mint PDA.
+pub const OUTPUT_MINT_SEED: &[u8] = b"output-mint";
+
+/// Output mint index reserved for the primary output of a `SemiFungible`
+/// recipe, which `forge_batch` mints.
+pub const PRIMARY_OUTPUT_INDEX: u8 = u8::MAX;
+
+/// Maximum units of a `Compressed` recipe one `forge_batch` mints; each unit
+/// is a separate Bubblegum CPI.
+pub const MAX_COMPRESSED_BATCH: u64 = 8;
+
+/// Seed prefix used when deriving a `ForgeEscrow` PDA.
+pub const FORGE_ESCROW_SEED: &[u8] = b"forge-escrow";
+
+/// Seed prefix used when deriving the forge vault authority PDA.
+pub const FORGE_VAULT_SEED: &[u8] = b"forge-vault";
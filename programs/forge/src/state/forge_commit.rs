@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+/// Pending trait roll for a forger on a recipe with a trait table.
+///
+/// The roll uses the hash of `commit_slot`, which does not exist yet when
+/// the commit is made. Once that slot has passed the forger can compute the
+/// roll before revealing, so a commit cannot be discarded while the recipe
+/// can be forged, and one left to expire reveals as the fallback tier.
+#[account]
+#[derive(Debug)]
+pub struct ForgeCommit {
+    /// Recipe PDA the roll is committed for.
+    pub recipe: Pubkey,
+    /// Wallet that committed and will forge.
+    pub forger: Pubkey,
+    /// Slot the commit landed in; its slot hash seeds the roll.
+    pub commit_slot: u64,
+    /// Bump seed for PDA derivation.
+    pub bump: u8,
+    /// Reserved padding.
+    pub _reserved: [u8; 7],
+}
+
+impl ForgeCommit {
+    pub const SIZE: usize = 8 // discriminator
+        + 32 // recipe
+        + 32 // forger
+        + 8 // commit_slot
+        + 1 // bump
+        + 7; // reserved padding
+}
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 
 /// Global configuration PDA for the Forge program.
 #[account]
+#[derive(Debug)]
 pub struct ForgeConfig {
     /// Authority permitted to manage configuration and recipes.
     pub authority: Pubkey,
@@ -15,6 +16,8 @@ pub struct ForgeConfig {
     pub recipe_creation_enabled: bool,
     /// Bump seed used to derive the PDA.
     pub bump: u8,
+    /// Sequence number the next event of this forge carries.
+    pub event_sequence: u64,
     /// Reserved for future expansion / padding to 8-byte alignment.
     pub _reserved: [u8; 5],
 }
@@ -28,5 +31,105 @@ impl ForgeConfig {
         + 2 // default_royalty_bps
         + 1 // recipe_creation_enabled
         + 1 // bump
+        + 8 // event_sequence
         + 5; // reserved padding
+
+    /// Claims the sequence number of the next event this forge emits.
+    pub fn next_event_sequence(&mut self) -> Result<u64> {
+        let sequence = self.event_sequence;
+        self.event_sequence = sequence
+            .checked_add(1)
+            .ok_or(crate::errors::ForgeError::ArithmeticOverflow)?;
+        Ok(sequence)
+    }
+}
+
+/// `ForgeConfig` layout from before `event_sequence`, 8 bytes shorter and
+/// under the same discriminator; `migrate_forge_config` upgrades it.
+#[derive(AnchorDeserialize, Debug)]
+pub struct ForgeConfigV1 {
+    pub authority: Pubkey,
+    pub collection_mint: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub default_royalty_bps: u16,
+    pub recipe_creation_enabled: bool,
+    pub bump: u8,
+}
+
+impl ForgeConfigV1 {
+    /// Number of bytes a `ForgeConfigV1` account was allocated with.
+    pub const SIZE: usize = ForgeConfig::SIZE - 8;
+
+    /// The same config in the current layout, with events numbered from 0.
+    pub fn upgrade(self) -> ForgeConfig {
+        ForgeConfig {
+            authority: self.authority,
+            collection_mint: self.collection_mint,
+            freeze_authority: self.freeze_authority,
+            default_royalty_bps: self.default_royalty_bps,
+            recipe_creation_enabled: self.recipe_creation_enabled,
+            bump: self.bump,
+            event_sequence: 0,
+            _reserved: [0; 5],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(event_sequence: u64) -> ForgeConfig {
+        ForgeConfig {
+            authority: Pubkey::new_unique(),
+            collection_mint: Some(Pubkey::new_unique()),
+            freeze_authority: Some(Pubkey::new_unique()),
+            default_royalty_bps: 500,
+            recipe_creation_enabled: true,
+            bump: 255,
+            event_sequence,
+            _reserved: [0; 5],
+        }
+    }
+
+    #[test]
+    fn size_matches_serialized_len() {
+        let mut data = Vec::new();
+        config(0).try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ForgeConfig::SIZE);
+    }
+
+    #[test]
+    fn v1_layout_upgrades_in_place() {
+        let current = config(0);
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+        // A v1 account is the same bytes without `event_sequence`.
+        let event_sequence_at = ForgeConfig::SIZE - 5 - 8;
+        data.drain(event_sequence_at..event_sequence_at + 8);
+        assert_eq!(data.len(), ForgeConfigV1::SIZE);
+
+        let upgraded = ForgeConfigV1::deserialize(&mut &data[8..])
+            .unwrap()
+            .upgrade();
+        assert_eq!(upgraded.authority, current.authority);
+        assert_eq!(upgraded.collection_mint, current.collection_mint);
+        assert_eq!(upgraded.freeze_authority, current.freeze_authority);
+        assert_eq!(upgraded.default_royalty_bps, 500);
+        assert!(upgraded.recipe_creation_enabled);
+        assert_eq!(upgraded.bump, 255);
+        assert_eq!(upgraded.event_sequence, 0);
+    }
+
+    #[test]
+    fn event_sequence_counts_up_until_it_would_overflow() {
+        let mut forge_config = config(0);
+        assert_eq!(forge_config.next_event_sequence().unwrap(), 0);
+        assert_eq!(forge_config.next_event_sequence().unwrap(), 1);
+        assert_eq!(forge_config.event_sequence, 2);
+
+        let mut forge_config = config(u64::MAX);
+        assert!(forge_config.next_event_sequence().is_err());
+        assert_eq!(forge_config.event_sequence, u64::MAX);
+    }
 }
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+/// Ingredient tokens held in the forge vault for one forged asset.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EscrowedToken {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl EscrowedToken {
+    pub const SIZE: usize = 32 + 8;
+}
+
+/// Ingredients escrowed when a reversible recipe forged `mint`; `unforge`
+/// returns them to whoever burns the asset.
+#[account]
+#[derive(Debug)]
+pub struct ForgeEscrow {
+    /// Recipe PDA that forged the asset.
+    pub recipe: Pubkey,
+    /// Mint of the forged asset.
+    pub mint: Pubkey,
+    /// Tokens moved into the forge vault, in verification order.
+    pub items: Vec<EscrowedToken>,
+    /// Bump seed for PDA derivation.
+    pub bump: u8,
+    /// Reserved padding.
+    pub _reserved: [u8; 7],
+}
+
+impl ForgeEscrow {
+    /// Account size for an escrow holding up to `items` tokens.
+    pub fn space(items: usize) -> usize {
+        8 // discriminator
+        + 32 // recipe
+        + 32 // mint
+        + 4 + items * EscrowedToken::SIZE
+        + 1 // bump
+        + 7 // reserved padding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_matches_serialized_len() {
+        let escrow = ForgeEscrow {
+            recipe: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            items: vec![
+                EscrowedToken {
+                    mint: Pubkey::new_unique(),
+                    amount: 5,
+                };
+                3
+            ],
+            bump: 255,
+            _reserved: [0; 7],
+        };
+        let mut data = Vec::new();
+        escrow.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ForgeEscrow::space(3));
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use super::constants::HASH_BYTES;
+
+/// Provenance record written for every mint produced by `forge_asset`.
+///
+/// Only this program can create accounts it owns, so a `ForgedAsset` passed
+/// back in proves that `mint` was forged by `recipe`.
+#[account]
+#[derive(Debug)]
+pub struct ForgedAsset {
+    /// Forge configuration the recipe belongs to.
+    pub forge_config: Pubkey,
+    /// Recipe PDA that produced the mint.
+    pub recipe: Pubkey,
+    /// Recipe version at the time of forging.
+    pub version: u16,
+    /// Wallet that forged the asset.
+    pub forger: Pubkey,
+    /// Mint of the forged asset.
+    pub mint: Pubkey,
+    /// Ingredient hash recorded in the matching `RecipeUse`.
+    pub input_hash: [u8; HASH_BYTES],
+    /// 1-based position of this mint within the recipe's supply.
+    pub ordinal: u64,
+    /// Unix timestamp of the forge.
+    pub forged_at: i64,
+    /// Index into the recipe's trait table rolled for this asset, if any.
+    pub trait_tier: Option<u8>,
+    /// Times the asset has been evolved in place (0 when freshly minted).
+    pub level: u16,
+    /// Bump seed for PDA derivation.
+    pub bump: u8,
+    /// Whether a `ForgeEscrow` holds the ingredients `unforge` returns.
+    pub escrowed: bool,
+    /// Reserved padding.
+    pub _reserved: [u8; 4],
+}
+
+impl ForgedAsset {
+    pub const SIZE: usize = 8 // discriminator
+        + 32 // forge_config
+        + 32 // recipe
+        + 2 // version
+        + 32 // forger
+        + 32 // mint
+        + HASH_BYTES // input hash
+        + 8 // ordinal
+        + 8 // forged_at
+        + 1 + 1 // Option<u8> trait_tier
+        + 2 // level
+        + 1 // bump
+        + 1 // escrowed
+        + 4; // reserved padding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_matches_serialized_len() {
+        let asset = ForgedAsset {
+            forge_config: Pubkey::new_unique(),
+            recipe: Pubkey::new_unique(),
+            version: 1,
+            forger: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            input_hash: [7; HASH_BYTES],
+            ordinal: 1,
+            forged_at: 0,
+            trait_tier: Some(2),
+            level: 3,
+            bump: 255,
+            escrowed: true,
+            _reserved: [0; 4],
+        };
+        let mut data = Vec::new();
+        asset.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ForgedAsset::SIZE);
+    }
+}
@@ -1,11 +1,20 @@
 use anchor_lang::prelude::*;
 
+use super::constants::{MAX_INGREDIENTS, MAX_INGREDIENT_DEPTH, MAX_PDA_SEED_LENGTH};
+
 /// Represents the primary type of asset produced by a recipe.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OutputKind {
     OneOfOne,
-    Edition { parent_mint: Pubkey },
+    Edition {
+        parent_mint: Pubkey,
+    },
     SemiFungible,
+    /// Compressed NFTs minted into a Bubblegum Merkle tree whose creator or
+    /// delegate is the `ForgeConfig` PDA; only `forge_batch` mints these.
+    Compressed {
+        merkle_tree: Pubkey,
+    },
 }
 
 impl OutputKind {
@@ -13,7 +22,88 @@ impl OutputKind {
     pub fn size(&self) -> usize {
         match self {
             Self::OneOfOne | Self::SemiFungible => 1, // variant tag only
-            Self::Edition { .. } => 1 + 32,
+            Self::Edition { .. } | Self::Compressed { .. } => 1 + 32,
+        }
+    }
+}
+
+/// How a recipe produces its result.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecipeMode {
+    /// `forge_asset` mints a new asset.
+    Mint,
+    /// `evolve_asset` upgrades an asset this forge minted earlier in place,
+    /// optionally only assets minted by `source_recipe`.
+    Evolve { source_recipe: Option<Pubkey> },
+}
+
+impl RecipeMode {
+    /// Largest encoded size (variant tag plus `Some` source recipe).
+    pub const SIZE: usize = 1 + 1 + 32;
+}
+
+/// Which forges of a recipe collide on the same `RecipeUse` record.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayPolicy {
+    /// Each wallet forges the recipe at most once.
+    OncePerWallet,
+    /// Each concrete set of ingredient inputs is used at most once, by anyone.
+    OncePerInputSet,
+    /// No replay limit; each forge supplies a fresh nonce.
+    Unlimited,
+}
+
+impl ReplayPolicy {
+    pub const SIZE: usize = 1;
+}
+
+impl From<ReplayPolicy> for forge_hash::ReplayPolicy {
+    fn from(policy: ReplayPolicy) -> Self {
+        match policy {
+            ReplayPolicy::OncePerWallet => Self::OncePerWallet,
+            ReplayPolicy::OncePerInputSet => Self::OncePerInputSet,
+            ReplayPolicy::Unlimited => Self::Unlimited,
+        }
+    }
+}
+
+/// Additional asset minted alongside a recipe's primary output.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecipeOutput {
+    /// Asset semantics of this output.
+    pub output_kind: OutputKind,
+    /// Metadata URI of the minted asset.
+    pub metadata_uri: String,
+    /// Tokens minted per forge (always 1 for `OneOfOne`).
+    pub quantity: u64,
+    /// Optional cap on the total tokens minted for this output.
+    pub supply_cap: Option<u64>,
+    /// Tokens minted for this output so far.
+    pub minted: u64,
+}
+
+impl RecipeOutput {
+    pub fn size(&self) -> usize {
+        self.output_kind.size()
+            + 4 + self.metadata_uri.len()
+            + 8 // quantity
+            + 1 + 8 // Option<u64> supply_cap
+            + 8 // minted
+    }
+
+    /// Borsh size of a `Vec<RecipeOutput>`.
+    pub fn list_size(outputs: &[RecipeOutput]) -> usize {
+        4 + outputs.iter().map(|output| output.size()).sum::<usize>()
+    }
+
+    /// Number of trailing remaining accounts `forge_asset` expects for this
+    /// output: `[mint, token_account, metadata, master_edition]` for
+    /// `OneOfOne`, `[output_mint, token_account]` for `SemiFungible`.
+    pub fn account_count(&self) -> usize {
+        match self.output_kind {
+            OutputKind::OneOfOne => 4,
+            OutputKind::SemiFungible => 2,
+            OutputKind::Edition { .. } | OutputKind::Compressed { .. } => 0,
         }
     }
 }
@@ -39,6 +129,28 @@ impl CreatorShare {
     pub const SIZE: usize = 32 + 1 + 1;
 }
 
+/// Weighted rarity tier a forged asset can roll into.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TraitTier {
+    /// Display name of the tier (e.g. "legendary").
+    pub name: String,
+    /// Relative weight; the chance of this tier is `weight / total_weight`.
+    pub weight: u16,
+    /// Metadata URI used for assets that roll this tier.
+    pub metadata_uri: String,
+}
+
+impl TraitTier {
+    pub fn size(&self) -> usize {
+        4 + self.name.len() + 2 + 4 + self.metadata_uri.len()
+    }
+
+    /// Borsh size of a `Vec<TraitTier>`.
+    pub fn list_size(tiers: &[TraitTier]) -> usize {
+        4 + tiers.iter().map(|tier| tier.size()).sum::<usize>()
+    }
+}
+
 /// Constraint required to satisfy a recipe.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
 pub enum IngredientConstraint {
@@ -50,8 +162,91 @@ pub enum IngredientConstraint {
     Allowlist { merkle_root: [u8; 32] },
     /// Requires a specific signer to authorize the forge request.
     Signer { authority: Pubkey },
-    /// Requires derived seeds to match a deterministic recipe value.
-    CustomSeeds { seeds: Vec<u8> },
+    /// Requires the forger to supply an initialized account at the PDA derived
+    /// from `seeds` (followed by the forger pubkey when `include_forger` is
+    /// set) under `program_id`, e.g. a player profile in a game program.
+    CustomSeeds {
+        program_id: Pubkey,
+        seeds: Vec<u8>,
+        include_forger: bool,
+    },
+    /// Requires every nested constraint to pass.
+    AllOf { constraints: IngredientList },
+    /// Requires at least one nested constraint to pass; the first passing
+    /// branch (in order) is the one recorded in the input hash.
+    AnyOf { constraints: IngredientList },
+    /// Requires `threshold` of the nested constraints to pass (N-of-M); the
+    /// first `threshold` passing branches are recorded in the input hash.
+    AtLeast {
+        threshold: u8,
+        constraints: IngredientList,
+    },
+    /// Requires holding an asset minted by the upstream `recipe`, proven by its
+    /// `ForgedAsset` record; the asset is burned on forge when `burn` is set.
+    ForgedOutput { recipe: Pubkey, burn: bool },
+    /// Requires an Ed25519 authorization from `authority` (an off-chain
+    /// service, never the forger) over the recipe, forger, a nonce and an
+    /// expiry, checked through the Ed25519 precompile.
+    SignedAuthorization { authority: Pubkey },
+}
+
+/// Nested constraints of a composite ingredient.
+///
+/// Anchor's Borsh derives bound every field type, which never resolves for
+/// `Vec<IngredientConstraint>` inside `IngredientConstraint` itself. This
+/// wrapper has bound-free impls and encodes exactly like the inner `Vec`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IngredientList(pub Vec<IngredientConstraint>);
+
+impl std::ops::Deref for IngredientList {
+    type Target = Vec<IngredientConstraint>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<IngredientConstraint>> for IngredientList {
+    fn from(constraints: Vec<IngredientConstraint>) -> Self {
+        Self(constraints)
+    }
+}
+
+impl AnchorSerialize for IngredientList {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.0.serialize(writer)
+    }
+}
+
+impl AnchorDeserialize for IngredientList {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Vec::<IngredientConstraint>::deserialize_reader(reader).map(Self)
+    }
+}
+
+#[cfg(feature = "idl-build")]
+impl anchor_lang::idl::build::IdlBuild for IngredientList {
+    fn create_type() -> Option<anchor_lang::idl::types::IdlTypeDef> {
+        use anchor_lang::idl::types::{IdlType, IdlTypeDef, IdlTypeDefTy};
+
+        Some(IdlTypeDef {
+            name: Self::get_full_path(),
+            docs: vec!["Nested constraints of a composite ingredient.".into()],
+            serialization: Default::default(),
+            repr: None,
+            generics: vec![],
+            ty: IdlTypeDefTy::Type {
+                alias: IdlType::Vec(Box::new(IdlType::Defined {
+                    name: IngredientConstraint::get_full_path(),
+                    generics: vec![],
+                })),
+            },
+        })
+    }
+
+    fn get_full_path() -> String {
+        format!("{}::{}", module_path!(), "IngredientList")
+    }
 }
 
 impl IngredientConstraint {
@@ -62,11 +257,91 @@ impl IngredientConstraint {
             Self::CollectionNft { .. } => 1 + 32,
             Self::Allowlist { .. } => 1 + 32,
             Self::Signer { .. } => 1 + 32,
-            Self::CustomSeeds { seeds } => 1 + 4 + seeds.len(),
+            Self::CustomSeeds { seeds, .. } => 1 + 32 + 4 + seeds.len() + 1,
+            Self::ForgedOutput { .. } => 1 + 32 + 1,
+            Self::SignedAuthorization { .. } => 1 + 32,
+            Self::AllOf { constraints } | Self::AnyOf { constraints } => {
+                1 + Self::list_size(constraints)
+            }
+            Self::AtLeast { constraints, .. } => 1 + 1 + Self::list_size(constraints),
+        }
+    }
+
+    /// Borsh size of a `Vec<IngredientConstraint>`.
+    pub fn list_size(constraints: &[IngredientConstraint]) -> usize {
+        4 + constraints.iter().map(|c| c.size()).sum::<usize>()
+    }
+
+    /// Nested constraints of a composite, or `None` for a leaf.
+    pub fn children(&self) -> Option<&[IngredientConstraint]> {
+        match self {
+            Self::AllOf { constraints }
+            | Self::AnyOf { constraints }
+            | Self::AtLeast { constraints, .. } => Some(&constraints.0),
+            _ => None,
         }
     }
+
+    /// Validates composite shape and nesting, returning the number of nodes
+    /// in this subtree. `depth` is the level this constraint sits at (top = 1).
+    pub fn validate(&self, depth: usize) -> Result<usize> {
+        require!(
+            depth <= MAX_INGREDIENT_DEPTH,
+            crate::errors::ForgeError::IngredientNestingTooDeep
+        );
+        if let Self::CustomSeeds {
+            seeds,
+            include_forger,
+            ..
+        } = self
+        {
+            require!(
+                seeds.len() <= MAX_PDA_SEED_LENGTH && (!seeds.is_empty() || *include_forger),
+                crate::errors::ForgeError::InvalidCustomSeeds
+            );
+        }
+        let Some(children) = self.children() else {
+            return Ok(1);
+        };
+        require!(
+            !children.is_empty() && children.len() <= MAX_INGREDIENTS,
+            crate::errors::ForgeError::InvalidCompositeIngredient
+        );
+        if let Self::AtLeast { threshold, .. } = self {
+            require!(
+                *threshold >= 1 && usize::from(*threshold) <= children.len(),
+                crate::errors::ForgeError::InvalidCompositeIngredient
+            );
+        }
+        children.iter().try_fold(
+            1usize,
+            |nodes, child| Ok(nodes + child.validate(depth + 1)?),
+        )
+    }
 }
 
 impl RecipeStatus {
     pub const SIZE: usize = 1;
+
+    /// Whether a recipe may be created directly in this status.
+    pub fn is_valid_initial(&self) -> bool {
+        matches!(self, Self::Draft | Self::Active)
+    }
+
+    /// Whether the lifecycle permits moving from `self` to `next`.
+    ///
+    /// Drafts can be activated or abandoned, live recipes toggle between
+    /// `Active` and `Paused`, and every non-retired status may retire.
+    /// Nothing returns to `Draft` and nothing leaves `Retired`.
+    pub fn can_transition_to(&self, next: RecipeStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Draft, Self::Active)
+                | (Self::Draft, Self::Retired)
+                | (Self::Active, Self::Paused)
+                | (Self::Active, Self::Retired)
+                | (Self::Paused, Self::Active)
+                | (Self::Paused, Self::Retired)
+        )
+    }
 }
@@ -1,10 +1,18 @@
+pub mod closed_recipe;
 pub mod constants;
+pub mod forge_commit;
 pub mod forge_config;
+pub mod forge_escrow;
+pub mod forged_asset;
 pub mod ingredients;
 pub mod recipe;
 pub mod recipe_use;
 
+pub use closed_recipe::*;
+pub use forge_commit::*;
 pub use forge_config::*;
+pub use forge_escrow::*;
+pub use forged_asset::*;
 pub use ingredients::*;
 pub use recipe::*;
 pub use recipe_use::*;
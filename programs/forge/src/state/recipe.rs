@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
 
-use super::{constants::*, CreatorShare, IngredientConstraint, OutputKind, RecipeStatus};
+use super::{
+    constants::*, CreatorShare, IngredientConstraint, OutputKind, RecipeMode, RecipeOutput,
+    RecipeStatus, ReplayPolicy, TraitTier,
+};
 
 /// PDA storing the definition of a forgeable recipe.
 #[account]
+#[derive(Debug)]
 pub struct Recipe {
     /// Parent forge configuration PDA.
     pub forge_config: Pubkey,
@@ -25,16 +29,36 @@ pub struct Recipe {
     pub collection_mint: Option<Pubkey>,
     /// Optional go-live timestamp (Unix seconds).
     pub go_live_unix_time: Option<i64>,
+    /// Optional time (Unix seconds) after which an active recipe is paused.
+    pub pause_at: Option<i64>,
+    /// Optional time (Unix seconds) after which the recipe is retired.
+    pub retire_at: Option<i64>,
     /// Ingredient constraints that must be satisfied to forge.
     pub ingredient_constraints: Vec<IngredientConstraint>,
+    /// Weighted rarity tiers rolled at forge time (empty = no roll).
+    pub trait_table: Vec<TraitTier>,
+    /// Outputs minted in the same forge as the primary asset.
+    pub extra_outputs: Vec<RecipeOutput>,
+    /// Whether the recipe mints new assets or evolves existing ones.
+    pub mode: RecipeMode,
+    /// Whether `TokenMint` ingredients are escrowed so `unforge` can return them.
+    pub reversible: bool,
+    /// Number of assets unforged; `minted` is never decremented.
+    pub unforged: u64,
+    /// Which forges share a `RecipeUse` record (see `forge_hash`).
+    pub replay_policy: ReplayPolicy,
     /// Lifecycle status of the recipe.
     pub status: RecipeStatus,
     /// Optional pointer to a previous recipe version.
     pub previous_version: Option<Pubkey>,
+    /// Pointer to the version that superseded this one, if any.
+    pub next_version: Option<Pubkey>,
     /// Bump seed used when deriving the PDA.
     pub bump: u8,
+    /// Number of `RecipeUse` records of this recipe that are still open.
+    pub open_uses: u32,
     /// Reserved padding.
-    pub _reserved: [u8; 7],
+    pub _reserved: [u8; 3],
 }
 
 impl Recipe {
@@ -45,12 +69,11 @@ impl Recipe {
         metadata_uri_len: usize,
         creators: &[CreatorShare],
         ingredient_constraints: &[IngredientConstraint],
+        trait_table: &[TraitTier],
+        extra_outputs: &[RecipeOutput],
     ) -> usize {
         let creators_size = 4 + creators.len() * CreatorShare::SIZE;
-        let ingredient_size = 4 + ingredient_constraints
-            .iter()
-            .map(|c| c.size())
-            .sum::<usize>();
+        let ingredient_size = IngredientConstraint::list_size(ingredient_constraints);
 
         8 // discriminator
         + 32 // forge_config
@@ -63,11 +86,34 @@ impl Recipe {
         + creators_size
         + 1 + 32 // Option<Pubkey> collection_mint
         + 1 + 8 // Option<i64> go_live_unix_time
+        + 1 + 8 // Option<i64> pause_at
+        + 1 + 8 // Option<i64> retire_at
         + ingredient_size
+        + TraitTier::list_size(trait_table)
+        + RecipeOutput::list_size(extra_outputs)
+        + RecipeMode::SIZE
+        + 1 // reversible
+        + 8 // unforged
+        + ReplayPolicy::SIZE
         + RecipeStatus::SIZE
         + 1 + 32 // Option<Pubkey> previous_version
+        + 1 + 32 // Option<Pubkey> next_version
         + 1 // bump
-        + 7 // reserved padding
+        + 4 // open_uses
+        + 3 // reserved padding
+    }
+
+    /// Exact number of bytes the recipe occupies with its current contents.
+    pub fn current_space(&self) -> usize {
+        Self::space(
+            self.slug.len(),
+            &self.output_kind,
+            self.metadata_uri.len(),
+            &self.creators,
+            &self.ingredient_constraints,
+            &self.trait_table,
+            &self.extra_outputs,
+        )
     }
 
     /// Convenience helper to cap strings at a repository-defined limit.
@@ -91,14 +137,475 @@ impl Recipe {
         Ok(())
     }
 
-    /// Checks the total number of ingredient constraints against limits.
+    /// Checks ingredient constraints against count, nesting and node limits.
     pub fn validate_ingredients(ingredients: &[IngredientConstraint]) -> Result<()> {
         require!(
             ingredients.len() <= MAX_INGREDIENTS,
             crate::errors::ForgeError::TooManyIngredients
         );
+        let mut nodes = 0usize;
+        for constraint in ingredients {
+            nodes += constraint.validate(1)?;
+        }
+        require!(
+            nodes <= MAX_INGREDIENT_NODES,
+            crate::errors::ForgeError::TooManyIngredients
+        );
+        Ok(())
+    }
+
+    /// Checks tier count, name/URI lengths and that every tier has weight.
+    pub fn validate_trait_table(trait_table: &[TraitTier]) -> Result<()> {
+        require!(
+            trait_table.len() <= MAX_TRAIT_TIERS,
+            crate::errors::ForgeError::InvalidTraitTable
+        );
+        for tier in trait_table {
+            require!(
+                tier.weight > 0 && tier.name.len() <= MAX_TRAIT_NAME_LENGTH,
+                crate::errors::ForgeError::InvalidTraitTable
+            );
+            require!(
+                tier.metadata_uri.len() <= MAX_METADATA_URI_LENGTH,
+                crate::errors::ForgeError::MetadataUriTooLong
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the extra outputs a recipe is created or updated with.
+    ///
+    /// Counters must start at zero; `OneOfOne` outputs mint exactly one
+    /// token, and a cap must leave room for at least one forge.
+    pub fn validate_extra_outputs(extra_outputs: &[RecipeOutput]) -> Result<()> {
+        require!(
+            extra_outputs.len() <= MAX_EXTRA_OUTPUTS,
+            crate::errors::ForgeError::InvalidRecipeOutputs
+        );
+        for output in extra_outputs {
+            require!(
+                output.metadata_uri.len() <= MAX_METADATA_URI_LENGTH,
+                crate::errors::ForgeError::MetadataUriTooLong
+            );
+            let quantity_valid = match output.output_kind {
+                OutputKind::OneOfOne => output.quantity == 1,
+                OutputKind::SemiFungible => output.quantity > 0,
+                OutputKind::Edition { .. } | OutputKind::Compressed { .. } => false,
+            };
+            let cap_valid = match output.supply_cap {
+                Some(cap) => cap >= output.quantity,
+                None => true,
+            };
+            require!(
+                quantity_valid && cap_valid && output.minted == 0,
+                crate::errors::ForgeError::InvalidRecipeOutputs
+            );
+        }
+        Ok(())
+    }
+
+    /// Evolve recipes update an existing asset, so they cannot roll traits
+    /// or mint extra outputs.
+    pub fn validate_mode(
+        mode: &RecipeMode,
+        trait_table: &[TraitTier],
+        extra_outputs: &[RecipeOutput],
+    ) -> Result<()> {
+        if let RecipeMode::Evolve { .. } = mode {
+            require!(
+                trait_table.is_empty() && extra_outputs.is_empty(),
+                crate::errors::ForgeError::InvalidRecipeMode
+            );
+        }
+        Ok(())
+    }
+
+    /// Reversible recipes must be able to hand back everything they take and
+    /// nothing more: they mint a single new asset, have no trait roll to
+    /// re-try, and never burn `ForgedOutput` ingredients.
+    pub fn validate_reversible(
+        reversible: bool,
+        mode: &RecipeMode,
+        ingredients: &[IngredientConstraint],
+        trait_table: &[TraitTier],
+        extra_outputs: &[RecipeOutput],
+    ) -> Result<()> {
+        if !reversible {
+            return Ok(());
+        }
+        fn burns_inputs(constraint: &IngredientConstraint) -> bool {
+            match constraint {
+                IngredientConstraint::ForgedOutput { burn, .. } => *burn,
+                _ => constraint
+                    .children()
+                    .is_some_and(|children| children.iter().any(burns_inputs)),
+            }
+        }
+        require!(
+            *mode == RecipeMode::Mint
+                && trait_table.is_empty()
+                && extra_outputs.is_empty()
+                && !ingredients.iter().any(burns_inputs),
+            crate::errors::ForgeError::InvalidReversibleRecipe
+        );
+        Ok(())
+    }
+
+    /// `OncePerInputSet` needs inputs to key on: a mint recipe without
+    /// ingredients would let a single forge lock the recipe for everyone.
+    pub fn validate_replay_policy(
+        replay_policy: ReplayPolicy,
+        mode: &RecipeMode,
+        ingredients: &[IngredientConstraint],
+    ) -> Result<()> {
+        require!(
+            replay_policy != ReplayPolicy::OncePerInputSet
+                || !ingredients.is_empty()
+                || *mode != RecipeMode::Mint,
+            crate::errors::ForgeError::InvalidReplayPolicy
+        );
+        Ok(())
+    }
+
+    /// Upper bound on the tokens a forge can escrow: the number of
+    /// `TokenMint` leaves in the ingredient tree.
+    pub fn token_ingredient_count(&self) -> usize {
+        fn count(constraints: &[IngredientConstraint]) -> usize {
+            constraints
+                .iter()
+                .map(|constraint| match constraint {
+                    IngredientConstraint::TokenMint { .. } => 1,
+                    _ => constraint.children().map_or(0, count),
+                })
+                .sum()
+        }
+        count(&self.ingredient_constraints)
+    }
+
+    /// Total trailing remaining accounts taken by the extra outputs.
+    pub fn output_account_count(&self) -> usize {
+        self.extra_outputs
+            .iter()
+            .map(RecipeOutput::account_count)
+            .sum()
+    }
+
+    /// Splits remaining accounts into the ingredient inputs and the trailing
+    /// extra output accounts, as every forge reads them.
+    pub fn split_output_accounts<'a, T>(&self, accounts: &'a [T]) -> Result<(&'a [T], &'a [T])> {
+        let ingredient_count = accounts
+            .len()
+            .checked_sub(self.output_account_count())
+            .ok_or(crate::errors::ForgeError::InvalidOutputAccounts)?;
+        Ok(accounts.split_at(ingredient_count))
+    }
+
+    /// Counts a `RecipeUse` record created for this recipe.
+    pub fn open_use(&mut self) -> Result<()> {
+        self.open_uses = self
+            .open_uses
+            .checked_add(1)
+            .ok_or(crate::errors::ForgeError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Counts a `RecipeUse` record of this recipe being closed.
+    pub fn close_use(&mut self) -> Result<()> {
+        self.open_uses = self
+            .open_uses
+            .checked_sub(1)
+            .ok_or(crate::errors::ForgeError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Number of `ForgeEscrow` accounts of this recipe that `unforge` has yet
+    /// to release. Reversible recipes escrow once per minted asset.
+    pub fn open_escrows(&self) -> u64 {
+        if self.reversible {
+            self.minted.saturating_sub(self.unforged)
+        } else {
+            0
+        }
+    }
+
+    /// Checks that `count` units can be forged in one `forge_batch` and
+    /// returns the resulting `minted` counter.
+    ///
+    /// Batches mint the primary output only, so they are limited to
+    /// semi-fungible and compressed recipes without per-asset state: no
+    /// trait table, extra outputs or ingredient escrow. Compressed batches
+    /// mint at most `MAX_COMPRESSED_BATCH` units.
+    pub fn batch_minted(&self, count: u64) -> Result<u64> {
+        let max_count = match self.output_kind {
+            OutputKind::SemiFungible => u64::MAX,
+            OutputKind::Compressed { .. } => MAX_COMPRESSED_BATCH,
+            _ => 0,
+        };
+        require!(
+            max_count > 0
+                && self.trait_table.is_empty()
+                && self.extra_outputs.is_empty()
+                && !self.reversible,
+            crate::errors::ForgeError::BatchNotSupported
+        );
+        require!(
+            count > 0 && count <= max_count,
+            crate::errors::ForgeError::InvalidBatchCount
+        );
+        let minted = self
+            .minted
+            .checked_add(count)
+            .ok_or(crate::errors::ForgeError::ArithmeticOverflow)?;
+        if let Some(cap) = self.supply_cap {
+            require!(minted <= cap, crate::errors::ForgeError::SupplyCapReached);
+        }
+        Ok(minted)
+    }
+
+    /// Picks a trait tier index from 32 bytes of randomness, weighted by
+    /// `TraitTier::weight`. Returns `None` when the recipe has no trait table.
+    pub fn roll_trait(&self, randomness: &[u8; 32]) -> Option<u8> {
+        let total: u64 = self.trait_table.iter().map(|t| u64::from(t.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&randomness[..8]);
+        let mut roll = u64::from_le_bytes(seed) % total;
+        for (index, tier) in self.trait_table.iter().enumerate() {
+            let weight = u64::from(tier.weight);
+            if roll < weight {
+                return Some(index as u8);
+            }
+            roll -= weight;
+        }
+        None
+    }
+
+    /// Tier an expired commit reveals as: the most common one (highest
+    /// weight, earliest on ties), so waiting out a bad roll never pays off.
+    pub fn fallback_trait(&self) -> Option<u8> {
+        self.trait_table
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, tier)| tier.weight)
+            .map(|(index, _)| index as u8)
+    }
+
+    /// Checks that a recipe `slug`/`version` under `forge_config` may record
+    /// `self` as its previous version.
+    ///
+    /// Both must share the forge and slug, the successor's version must be
+    /// higher, and `self` must not already have a successor so the chain
+    /// stays linear.
+    pub fn validate_successor(
+        &self,
+        forge_config: &Pubkey,
+        slug: &str,
+        version: u16,
+    ) -> Result<()> {
+        require!(
+            self.forge_config == *forge_config && self.slug == slug && self.version < version,
+            crate::errors::ForgeError::PreviousVersionMismatch
+        );
+        require!(
+            self.next_version.is_none(),
+            crate::errors::ForgeError::PreviousVersionAlreadyLinked
+        );
         Ok(())
     }
+
+    /// Whether the recipe has minted, locking its ingredients, output kind
+    /// and supply cap. Changing them requires forking a new version.
+    pub fn is_frozen(&self) -> bool {
+        self.minted > 0
+    }
+
+    /// Ensures a scheduled pause, when combined with a retirement, fires first.
+    pub fn validate_schedule(pause_at: Option<i64>, retire_at: Option<i64>) -> Result<()> {
+        if let (Some(pause), Some(retire)) = (pause_at, retire_at) {
+            require!(
+                pause < retire,
+                crate::errors::ForgeError::InvalidRecipeSchedule
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the recipe is complete enough to accept forges.
+    ///
+    /// Creator shares must sum to 100 when creators are listed, and a
+    /// collection must resolve either from the recipe or the forge default.
+    /// Edition parents are checked separately since they need the mint account.
+    pub fn validate_activation(&self, forge_collection_mint: Option<Pubkey>) -> Result<()> {
+        if !self.creators.is_empty() {
+            let total: u16 = self.creators.iter().map(|c| u16::from(c.share)).sum();
+            require!(
+                total == 100,
+                crate::errors::ForgeError::InvalidCreatorShares
+            );
+        }
+        require!(
+            self.collection_mint.or(forge_collection_mint).is_some(),
+            crate::errors::ForgeError::CollectionNotConfigured
+        );
+        Ok(())
+    }
+
+    /// Status the recipe holds at `now` once due scheduled transitions apply.
+    pub fn scheduled_status(&self, now: i64) -> RecipeStatus {
+        if self.status != RecipeStatus::Retired && self.retire_at.is_some_and(|t| now >= t) {
+            return RecipeStatus::Retired;
+        }
+        if self.status == RecipeStatus::Active && self.pause_at.is_some_and(|t| now >= t) {
+            return RecipeStatus::Paused;
+        }
+        self.status
+    }
+
+    /// Persists any due scheduled transition, clearing the schedule entries
+    /// that fired so a later manual reactivation is not immediately undone.
+    ///
+    /// Returns the previous status when a transition was applied.
+    pub fn apply_schedule(&mut self, now: i64) -> Option<RecipeStatus> {
+        let next = self.scheduled_status(now);
+        if next == self.status {
+            return None;
+        }
+        let previous = self.status;
+        self.status = next;
+        self.pause_at = None;
+        if next == RecipeStatus::Retired {
+            self.retire_at = None;
+        }
+        Some(previous)
+    }
+}
+
+/// `IngredientConstraint` as stored by `RecipeV1`, before `CustomSeeds`
+/// named the program that owns the seeded account.
+#[derive(AnchorDeserialize, Debug)]
+pub enum IngredientConstraintV1 {
+    TokenMint { mint: Pubkey, amount: u64 },
+    CollectionNft { collection_mint: Pubkey },
+    Allowlist { merkle_root: [u8; 32] },
+    Signer { authority: Pubkey },
+    CustomSeeds { seeds: Vec<u8> },
+}
+
+impl IngredientConstraintV1 {
+    /// The same constraint in the current layout.
+    ///
+    /// A v1 `CustomSeeds` only fed its seeds into the input hash and checked
+    /// no account, so there is no owning program to carry over. It becomes a
+    /// PDA of the System program, which can never hold data: the recipe stays
+    /// unforgeable until the authority replaces the constraint, rather than
+    /// silently accepting any forger.
+    pub fn upgrade(self) -> IngredientConstraint {
+        match self {
+            Self::TokenMint { mint, amount } => IngredientConstraint::TokenMint { mint, amount },
+            Self::CollectionNft { collection_mint } => {
+                IngredientConstraint::CollectionNft { collection_mint }
+            }
+            Self::Allowlist { merkle_root } => IngredientConstraint::Allowlist { merkle_root },
+            Self::Signer { authority } => IngredientConstraint::Signer { authority },
+            Self::CustomSeeds { seeds } => IngredientConstraint::CustomSeeds {
+                program_id: anchor_lang::system_program::ID,
+                seeds,
+                include_forger: false,
+            },
+        }
+    }
+}
+
+/// `Recipe` layout from before schedules, trait tables, extra outputs,
+/// modes, escrow, replay policies and `open_uses`, under the same
+/// discriminator; `migrate_recipe` upgrades it.
+#[derive(AnchorDeserialize, Debug)]
+pub struct RecipeV1 {
+    pub forge_config: Pubkey,
+    pub slug: String,
+    pub version: u16,
+    pub output_kind: OutputKind,
+    pub supply_cap: Option<u64>,
+    pub minted: u64,
+    pub metadata_uri: String,
+    pub creators: Vec<CreatorShare>,
+    pub collection_mint: Option<Pubkey>,
+    pub go_live_unix_time: Option<i64>,
+    pub ingredient_constraints: Vec<IngredientConstraintV1>,
+    pub status: RecipeStatus,
+    pub previous_version: Option<Pubkey>,
+    pub bump: u8,
+    pub _reserved: [u8; 7],
+}
+
+impl RecipeV1 {
+    /// Decodes a v1 recipe from account data including the discriminator.
+    ///
+    /// `update_recipe` only ever grew v1 accounts, so anything after the
+    /// encoded recipe must be zero padding.
+    pub fn try_from_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(Recipe::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        let mut rest = &data[8..];
+        let legacy = Self::deserialize(&mut rest)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?;
+        require!(
+            rest.iter().all(|byte| *byte == 0),
+            anchor_lang::error::ErrorCode::AccountDidNotDeserialize
+        );
+        Ok(legacy)
+    }
+
+    /// The same recipe in the current layout.
+    ///
+    /// Every v1 forge created one `RecipeUse`, so `open_uses` starts at
+    /// `minted`. v1 input hashes covered only the ingredients (or the forger
+    /// and mint when there were none), which the replay policies
+    /// `OncePerInputSet` and `Unlimited` match respectively.
+    pub fn upgrade(self) -> Result<Recipe> {
+        let replay_policy = if self.ingredient_constraints.is_empty() {
+            ReplayPolicy::Unlimited
+        } else {
+            ReplayPolicy::OncePerInputSet
+        };
+        Ok(Recipe {
+            forge_config: self.forge_config,
+            slug: self.slug,
+            version: self.version,
+            output_kind: self.output_kind,
+            supply_cap: self.supply_cap,
+            minted: self.minted,
+            metadata_uri: self.metadata_uri,
+            creators: self.creators,
+            collection_mint: self.collection_mint,
+            go_live_unix_time: self.go_live_unix_time,
+            pause_at: None,
+            retire_at: None,
+            ingredient_constraints: self
+                .ingredient_constraints
+                .into_iter()
+                .map(IngredientConstraintV1::upgrade)
+                .collect(),
+            trait_table: vec![],
+            extra_outputs: vec![],
+            mode: RecipeMode::Mint,
+            reversible: false,
+            unforged: 0,
+            replay_policy,
+            status: self.status,
+            previous_version: self.previous_version,
+            next_version: None,
+            bump: self.bump,
+            open_uses: u32::try_from(self.minted)
+                .map_err(|_| crate::errors::ForgeError::ArithmeticOverflow)?,
+            _reserved: [0; 3],
+        })
+    }
 }
 
 #[cfg(test)]
@@ -114,6 +621,36 @@ mod tests {
         }]
     }
 
+    fn sample_recipe(status: RecipeStatus) -> Recipe {
+        Recipe {
+            forge_config: Pubkey::new_unique(),
+            slug: "sword".to_string(),
+            version: 1,
+            output_kind: OutputKind::OneOfOne,
+            supply_cap: None,
+            minted: 0,
+            metadata_uri: "uri".to_string(),
+            creators: sample_creators(),
+            collection_mint: Some(Pubkey::new_unique()),
+            go_live_unix_time: None,
+            pause_at: None,
+            retire_at: None,
+            ingredient_constraints: vec![],
+            trait_table: vec![],
+            extra_outputs: vec![],
+            mode: RecipeMode::Mint,
+            reversible: false,
+            unforged: 0,
+            replay_policy: ReplayPolicy::Unlimited,
+            status,
+            previous_version: None,
+            next_version: None,
+            bump: 255,
+            open_uses: 0,
+            _reserved: [0; 3],
+        }
+    }
+
     #[test]
     fn recipe_space_scales_with_metadata() {
         let creators = sample_creators();
@@ -122,10 +659,20 @@ mod tests {
                 authority: Pubkey::new_unique(),
             },
             IngredientConstraint::CustomSeeds {
+                program_id: Pubkey::new_unique(),
                 seeds: vec![1, 2, 3],
+                include_forger: true,
             },
         ];
-        let size = Recipe::space(4, &OutputKind::OneOfOne, 32, &creators, &ingredients);
+        let size = Recipe::space(
+            4,
+            &OutputKind::OneOfOne,
+            32,
+            &creators,
+            &ingredients,
+            &[],
+            &[],
+        );
         assert!(size > 0);
     }
 
@@ -146,10 +693,485 @@ mod tests {
 
         let too_many = vec![
             IngredientConstraint::CustomSeeds {
+                program_id: Pubkey::new_unique(),
                 seeds: vec![0u8; 1],
+                include_forger: false,
             };
             MAX_INGREDIENTS + 1
         ];
         assert!(Recipe::validate_ingredients(&too_many).is_err());
     }
+
+    #[test]
+    fn status_transitions_follow_lifecycle() {
+        use RecipeStatus::*;
+
+        assert!(Draft.can_transition_to(Active));
+        assert!(Draft.can_transition_to(Retired));
+        assert!(Active.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Active));
+        assert!(Paused.can_transition_to(Retired));
+
+        assert!(!Active.can_transition_to(Draft));
+        assert!(!Paused.can_transition_to(Draft));
+        assert!(!Draft.can_transition_to(Paused));
+        assert!(!Retired.can_transition_to(Active));
+        assert!(!Retired.can_transition_to(Draft));
+    }
+
+    #[test]
+    fn validate_activation_checks_creators_and_collection() {
+        let mut recipe = sample_recipe(RecipeStatus::Draft);
+        assert!(recipe.validate_activation(None).is_ok());
+
+        recipe.creators[0].share = 60;
+        assert!(recipe.validate_activation(None).is_err());
+        recipe.creators.clear();
+        assert!(recipe.validate_activation(None).is_ok());
+
+        recipe.collection_mint = None;
+        assert!(recipe.validate_activation(None).is_err());
+        assert!(recipe
+            .validate_activation(Some(Pubkey::new_unique()))
+            .is_ok());
+    }
+
+    #[test]
+    fn schedule_pauses_then_retires() {
+        assert!(Recipe::validate_schedule(Some(20), Some(10)).is_err());
+        assert!(Recipe::validate_schedule(Some(10), Some(20)).is_ok());
+
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        recipe.pause_at = Some(10);
+        recipe.retire_at = Some(20);
+
+        assert_eq!(recipe.scheduled_status(5), RecipeStatus::Active);
+        assert_eq!(recipe.scheduled_status(10), RecipeStatus::Paused);
+        assert_eq!(recipe.scheduled_status(20), RecipeStatus::Retired);
+
+        assert_eq!(recipe.apply_schedule(12), Some(RecipeStatus::Active));
+        assert_eq!(recipe.status, RecipeStatus::Paused);
+        assert_eq!(recipe.pause_at, None);
+        assert_eq!(recipe.retire_at, Some(20));
+        assert_eq!(recipe.apply_schedule(15), None);
+
+        assert_eq!(recipe.apply_schedule(25), Some(RecipeStatus::Paused));
+        assert_eq!(recipe.status, RecipeStatus::Retired);
+        assert_eq!(recipe.retire_at, None);
+    }
+
+    #[test]
+    fn draft_recipes_ignore_scheduled_pause() {
+        let mut recipe = sample_recipe(RecipeStatus::Draft);
+        recipe.pause_at = Some(10);
+        assert_eq!(recipe.scheduled_status(50), RecipeStatus::Draft);
+    }
+
+    #[test]
+    fn validate_successor_requires_same_lineage() {
+        let previous = sample_recipe(RecipeStatus::Active);
+        let forge = previous.forge_config;
+
+        assert!(previous.validate_successor(&forge, "sword", 2).is_ok());
+        assert!(previous.validate_successor(&forge, "sword", 1).is_err());
+        assert!(previous.validate_successor(&forge, "shield", 2).is_err());
+        assert!(previous
+            .validate_successor(&Pubkey::new_unique(), "sword", 2)
+            .is_err());
+
+        let mut linked = previous.clone();
+        linked.next_version = Some(Pubkey::new_unique());
+        assert!(linked.validate_successor(&forge, "sword", 2).is_err());
+    }
+
+    #[test]
+    fn current_space_bounds_serialized_len() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        recipe.ingredient_constraints = vec![IngredientConstraint::CustomSeeds {
+            program_id: Pubkey::new_unique(),
+            seeds: vec![7; 5],
+            include_forger: true,
+        }];
+
+        let serialized_len = |recipe: &Recipe| {
+            let mut data = Vec::new();
+            recipe.serialize(&mut data).unwrap();
+            8 + data.len()
+        };
+        // Options are always sized as `Some`, so unset ones leave slack.
+        assert!(recipe.current_space() > serialized_len(&recipe));
+
+        recipe.supply_cap = Some(10);
+        recipe.go_live_unix_time = Some(1);
+        recipe.pause_at = Some(2);
+        recipe.retire_at = Some(3);
+        recipe.previous_version = Some(Pubkey::new_unique());
+        recipe.next_version = Some(Pubkey::new_unique());
+        recipe.mode = RecipeMode::Evolve {
+            source_recipe: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(recipe.current_space(), serialized_len(&recipe));
+    }
+
+    #[test]
+    fn composite_ingredients_size_and_nesting() {
+        let signer = || IngredientConstraint::Signer {
+            authority: Pubkey::new_unique(),
+        };
+        let nested = IngredientConstraint::AnyOf {
+            constraints: vec![
+                signer(),
+                IngredientConstraint::AtLeast {
+                    threshold: 2,
+                    constraints: vec![signer(), signer(), signer()].into(),
+                },
+            ]
+            .into(),
+        };
+
+        let mut data = Vec::new();
+        nested.serialize(&mut data).unwrap();
+        assert_eq!(nested.size(), data.len());
+        assert!(Recipe::validate_ingredients(std::slice::from_ref(&nested)).is_ok());
+
+        let too_deep = IngredientConstraint::AllOf {
+            constraints: vec![nested].into(),
+        };
+        assert!(Recipe::validate_ingredients(&[too_deep]).is_err());
+
+        let empty = IngredientConstraint::AnyOf {
+            constraints: vec![].into(),
+        };
+        assert!(Recipe::validate_ingredients(&[empty]).is_err());
+
+        let bad_threshold = IngredientConstraint::AtLeast {
+            threshold: 3,
+            constraints: vec![signer(), signer()].into(),
+        };
+        assert!(Recipe::validate_ingredients(&[bad_threshold]).is_err());
+    }
+
+    #[test]
+    fn custom_seeds_validation() {
+        let custom = |seeds: Vec<u8>, include_forger| IngredientConstraint::CustomSeeds {
+            program_id: Pubkey::new_unique(),
+            seeds,
+            include_forger,
+        };
+        assert!(Recipe::validate_ingredients(&[custom(b"profile".to_vec(), true)]).is_ok());
+        assert!(Recipe::validate_ingredients(&[custom(vec![], true)]).is_ok());
+        assert!(Recipe::validate_ingredients(&[custom(vec![], false)]).is_err());
+        assert!(Recipe::validate_ingredients(&[custom(vec![0; 33], false)]).is_err());
+    }
+
+    fn tier(name: &str, weight: u16) -> TraitTier {
+        TraitTier {
+            name: name.to_string(),
+            weight,
+            metadata_uri: format!("https://example.com/{name}.json"),
+        }
+    }
+
+    #[test]
+    fn trait_table_validation() {
+        assert!(Recipe::validate_trait_table(&[]).is_ok());
+        assert!(Recipe::validate_trait_table(&[tier("common", 90), tier("rare", 10)]).is_ok());
+        assert!(Recipe::validate_trait_table(&[tier("zero", 0)]).is_err());
+        assert!(Recipe::validate_trait_table(&[tier(&"n".repeat(17), 1)]).is_err());
+        assert!(Recipe::validate_trait_table(&vec![tier("t", 1); MAX_TRAIT_TIERS + 1]).is_err());
+    }
+
+    #[test]
+    fn roll_trait_follows_weights() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        let randomness = |value: u64| {
+            let mut bytes = [0u8; 32];
+            bytes[..8].copy_from_slice(&value.to_le_bytes());
+            bytes
+        };
+        assert_eq!(recipe.roll_trait(&randomness(0)), None);
+
+        recipe.trait_table = vec![tier("common", 6), tier("rare", 3), tier("epic", 1)];
+        assert_eq!(recipe.roll_trait(&randomness(0)), Some(0));
+        assert_eq!(recipe.roll_trait(&randomness(5)), Some(0));
+        assert_eq!(recipe.roll_trait(&randomness(6)), Some(1));
+        assert_eq!(recipe.roll_trait(&randomness(8)), Some(1));
+        assert_eq!(recipe.roll_trait(&randomness(9)), Some(2));
+        assert_eq!(recipe.roll_trait(&randomness(19)), Some(2));
+    }
+
+    #[test]
+    fn fallback_trait_is_the_most_common_tier() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        assert_eq!(recipe.fallback_trait(), None);
+
+        recipe.trait_table = vec![tier("rare", 3), tier("common", 6), tier("epic", 1)];
+        assert_eq!(recipe.fallback_trait(), Some(1));
+        recipe.trait_table = vec![tier("a", 2), tier("b", 2)];
+        assert_eq!(recipe.fallback_trait(), Some(0));
+    }
+
+    fn output(output_kind: OutputKind, quantity: u64, supply_cap: Option<u64>) -> RecipeOutput {
+        RecipeOutput {
+            output_kind,
+            metadata_uri: "https://example.com/output.json".to_string(),
+            quantity,
+            supply_cap,
+            minted: 0,
+        }
+    }
+
+    #[test]
+    fn extra_outputs_validation_and_accounts() {
+        let outputs = vec![
+            output(OutputKind::OneOfOne, 1, None),
+            output(OutputKind::SemiFungible, 5, Some(100)),
+        ];
+        assert!(Recipe::validate_extra_outputs(&outputs).is_ok());
+        assert!(Recipe::validate_extra_outputs(&[output(OutputKind::OneOfOne, 2, None)]).is_err());
+        assert!(
+            Recipe::validate_extra_outputs(&[output(OutputKind::SemiFungible, 0, None)]).is_err()
+        );
+        assert!(
+            Recipe::validate_extra_outputs(&[output(OutputKind::SemiFungible, 5, Some(4))])
+                .is_err()
+        );
+        let edition = OutputKind::Edition {
+            parent_mint: Pubkey::new_unique(),
+        };
+        assert!(Recipe::validate_extra_outputs(&[output(edition, 1, None)]).is_err());
+        assert!(Recipe::validate_extra_outputs(&vec![
+            output(OutputKind::OneOfOne, 1, None);
+            MAX_EXTRA_OUTPUTS + 1
+        ])
+        .is_err());
+
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        assert_eq!(recipe.output_account_count(), 0);
+        recipe.extra_outputs = outputs;
+        assert_eq!(recipe.output_account_count(), 6);
+        assert_eq!(
+            recipe.split_output_accounts(&[0u8; 8]).unwrap(),
+            (&[0u8; 2][..], &[0u8; 6][..])
+        );
+        assert!(recipe.split_output_accounts(&[0u8; 5]).is_err());
+        let mut data = Vec::new();
+        recipe.serialize(&mut data).unwrap();
+        assert!(recipe.current_space() >= 8 + data.len());
+    }
+
+    #[test]
+    fn evolve_mode_rejects_rolls_and_extra_outputs() {
+        let evolve = RecipeMode::Evolve {
+            source_recipe: Some(Pubkey::new_unique()),
+        };
+        assert!(Recipe::validate_mode(&evolve, &[], &[]).is_ok());
+        assert!(Recipe::validate_mode(&evolve, &[tier("rare", 1)], &[]).is_err());
+        assert!(
+            Recipe::validate_mode(&evolve, &[], &[output(OutputKind::OneOfOne, 1, None)]).is_err()
+        );
+        assert!(Recipe::validate_mode(&RecipeMode::Mint, &[tier("rare", 1)], &[]).is_ok());
+
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        recipe.mode = evolve;
+        let mut data = Vec::new();
+        recipe.serialize(&mut data).unwrap();
+        assert!(recipe.current_space() >= 8 + data.len());
+    }
+
+    #[test]
+    fn reversible_recipes_hand_back_what_they_take() {
+        let iron = IngredientConstraint::TokenMint {
+            mint: Pubkey::new_unique(),
+            amount: 3,
+        };
+        let burned_sword = IngredientConstraint::ForgedOutput {
+            recipe: Pubkey::new_unique(),
+            burn: true,
+        };
+        let nested = vec![IngredientConstraint::AnyOf {
+            constraints: vec![iron.clone(), burned_sword.clone()].into(),
+        }];
+        let mint = RecipeMode::Mint;
+        assert!(
+            Recipe::validate_reversible(true, &mint, std::slice::from_ref(&iron), &[], &[]).is_ok()
+        );
+        assert!(Recipe::validate_reversible(true, &mint, &nested, &[], &[]).is_err());
+        assert!(Recipe::validate_reversible(false, &mint, &nested, &[], &[]).is_ok());
+        assert!(Recipe::validate_reversible(true, &mint, &[], &[tier("rare", 1)], &[]).is_err());
+        let evolve = RecipeMode::Evolve {
+            source_recipe: None,
+        };
+        assert!(Recipe::validate_reversible(true, &evolve, &[], &[], &[]).is_err());
+
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        recipe.ingredient_constraints = vec![iron, nested[0].clone()];
+        assert_eq!(recipe.token_ingredient_count(), 2);
+    }
+
+    #[test]
+    fn once_per_input_set_needs_inputs() {
+        let signer = IngredientConstraint::Signer {
+            authority: Pubkey::new_unique(),
+        };
+        let per_set = ReplayPolicy::OncePerInputSet;
+        let mint = RecipeMode::Mint;
+        assert!(Recipe::validate_replay_policy(per_set, &mint, &[]).is_err());
+        assert!(Recipe::validate_replay_policy(per_set, &mint, &[signer]).is_ok());
+        assert!(Recipe::validate_replay_policy(ReplayPolicy::OncePerWallet, &mint, &[]).is_ok());
+        let evolve = RecipeMode::Evolve {
+            source_recipe: None,
+        };
+        assert!(Recipe::validate_replay_policy(per_set, &evolve, &[]).is_ok());
+    }
+
+    #[test]
+    fn batch_respects_supply_cap() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        assert!(recipe.batch_minted(1).is_err());
+
+        recipe.output_kind = OutputKind::SemiFungible;
+        recipe.supply_cap = Some(10);
+        recipe.minted = 4;
+        assert!(recipe.batch_minted(0).is_err());
+        assert_eq!(recipe.batch_minted(6).unwrap(), 10);
+        assert!(recipe.batch_minted(7).is_err());
+
+        recipe.reversible = true;
+        assert!(recipe.batch_minted(1).is_err());
+    }
+
+    #[test]
+    fn compressed_batches_are_bounded() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        recipe.output_kind = OutputKind::Compressed {
+            merkle_tree: Pubkey::new_unique(),
+        };
+        assert_eq!(
+            recipe.batch_minted(MAX_COMPRESSED_BATCH).unwrap(),
+            MAX_COMPRESSED_BATCH
+        );
+        assert!(recipe.batch_minted(MAX_COMPRESSED_BATCH + 1).is_err());
+    }
+
+    #[test]
+    fn open_uses_count_records_until_closed() {
+        let mut recipe = sample_recipe(RecipeStatus::Active);
+        assert!(recipe.close_use().is_err());
+        recipe.open_use().unwrap();
+        recipe.open_use().unwrap();
+        recipe.close_use().unwrap();
+        assert_eq!(recipe.open_uses, 1);
+
+        recipe.open_uses = u32::MAX;
+        assert!(recipe.open_use().is_err());
+    }
+
+    #[test]
+    fn open_escrows_count_reversible_forges_not_yet_unforged() {
+        let mut recipe = sample_recipe(RecipeStatus::Retired);
+        recipe.minted = 3;
+        recipe.unforged = 1;
+        assert_eq!(recipe.open_escrows(), 0);
+
+        recipe.reversible = true;
+        assert_eq!(recipe.open_escrows(), 2);
+        recipe.unforged = 3;
+        assert_eq!(recipe.open_escrows(), 0);
+    }
+
+    /// Account data of a v1 recipe, as the v1 program wrote it.
+    fn v1_data(minted: u64, ingredients: &[(u8, &[u8])], padding: usize) -> Vec<u8> {
+        let mut data = Recipe::DISCRIMINATOR.to_vec();
+        let forge_config = Pubkey::new_unique();
+        (forge_config, "sword".to_string(), 2u16)
+            .serialize(&mut data)
+            .unwrap();
+        OutputKind::SemiFungible.serialize(&mut data).unwrap();
+        (Some(10u64), minted, "uri".to_string(), sample_creators())
+            .serialize(&mut data)
+            .unwrap();
+        (None::<Pubkey>, Some(5i64)).serialize(&mut data).unwrap();
+        (ingredients.len() as u32).serialize(&mut data).unwrap();
+        for (tag, fields) in ingredients {
+            data.push(*tag);
+            data.extend_from_slice(fields);
+        }
+        RecipeStatus::Retired.serialize(&mut data).unwrap();
+        (None::<Pubkey>, 254u8, [0u8; 7])
+            .serialize(&mut data)
+            .unwrap();
+        data.resize(data.len() + padding, 0);
+        data
+    }
+
+    #[test]
+    fn v1_layout_upgrades_with_open_uses_for_every_mint() {
+        let authority = Pubkey::new_unique();
+        let data = v1_data(3, &[(3, authority.as_ref())], 16);
+        let upgraded = RecipeV1::try_from_data(&data).unwrap().upgrade().unwrap();
+
+        assert_eq!(upgraded.slug, "sword");
+        assert_eq!(upgraded.version, 2);
+        assert_eq!(upgraded.output_kind, OutputKind::SemiFungible);
+        assert_eq!(upgraded.supply_cap, Some(10));
+        assert_eq!(upgraded.minted, 3);
+        assert_eq!(upgraded.open_uses, 3);
+        assert_eq!(upgraded.go_live_unix_time, Some(5));
+        assert_eq!(
+            upgraded.ingredient_constraints,
+            vec![IngredientConstraint::Signer { authority }]
+        );
+        assert_eq!(upgraded.replay_policy, ReplayPolicy::OncePerInputSet);
+        assert_eq!(upgraded.mode, RecipeMode::Mint);
+        assert_eq!(upgraded.status, RecipeStatus::Retired);
+        assert_eq!(upgraded.bump, 254);
+
+        // The upgraded recipe round-trips through the current layout.
+        let mut current = Vec::new();
+        upgraded.try_serialize(&mut current).unwrap();
+        assert!(current.len() <= upgraded.current_space());
+        assert!(Recipe::try_deserialize(&mut &current[..]).is_ok());
+    }
+
+    #[test]
+    fn v1_custom_seeds_upgrade_to_an_unsatisfiable_constraint() {
+        let mut seeds = 2u32.to_le_bytes().to_vec();
+        seeds.extend_from_slice(&[7, 8]);
+        let upgraded = RecipeV1::try_from_data(&v1_data(0, &[(4, &seeds)], 0))
+            .unwrap()
+            .upgrade()
+            .unwrap();
+        assert_eq!(
+            upgraded.ingredient_constraints,
+            vec![IngredientConstraint::CustomSeeds {
+                program_id: anchor_lang::system_program::ID,
+                seeds: vec![7, 8],
+                include_forger: false,
+            }]
+        );
+        assert_eq!(upgraded.replay_policy, ReplayPolicy::OncePerInputSet);
+    }
+
+    #[test]
+    fn v1_decoding_rejects_current_recipes_and_trailing_data() {
+        let mut current = Vec::new();
+        sample_recipe(RecipeStatus::Active)
+            .try_serialize(&mut current)
+            .unwrap();
+        assert!(RecipeV1::try_from_data(&current).is_err());
+
+        let mut data = v1_data(0, &[], 8);
+        assert_eq!(
+            RecipeV1::try_from_data(&data)
+                .unwrap()
+                .upgrade()
+                .unwrap()
+                .replay_policy,
+            ReplayPolicy::Unlimited
+        );
+        let last = data.len() - 1;
+        data[last] = 1;
+        assert!(RecipeV1::try_from_data(&data).is_err());
+    }
 }
@@ -4,6 +4,7 @@ use super::constants::HASH_BYTES;
 
 /// PDA that tracks deterministic ingredient combinations already used.
 #[account]
+#[derive(Debug)]
 pub struct RecipeUse {
     /// Recipe PDA associated with this usage record.
     pub recipe: Pubkey,
@@ -13,6 +14,8 @@ pub struct RecipeUse {
     pub forged_at: i64,
     /// Bump seed for PDA derivation.
     pub bump: u8,
+    /// Account that paid rent for this record and receives it back on close.
+    pub payer: Pubkey,
     /// Reserved padding.
     pub _reserved: [u8; 7],
 }
@@ -23,5 +26,24 @@ impl RecipeUse {
         + HASH_BYTES // input hash
         + 8 // forged_at
         + 1 // bump
+        + 32 // payer
         + 7; // reserved padding
 }
+
+/// `RecipeUse` layout from before `payer`, under the same discriminator.
+///
+/// `close_recipe_uses` still closes these, refunding the forge authority
+/// since the payer was never recorded.
+#[derive(AnchorDeserialize, Debug)]
+pub struct RecipeUseV1 {
+    pub recipe: Pubkey,
+    pub input_hash: [u8; HASH_BYTES],
+    pub forged_at: i64,
+    pub bump: u8,
+    pub _reserved: [u8; 7],
+}
+
+impl RecipeUseV1 {
+    /// Number of bytes a `RecipeUseV1` account was allocated with.
+    pub const SIZE: usize = RecipeUse::SIZE - 32;
+}
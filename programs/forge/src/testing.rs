@@ -0,0 +1,449 @@
+//! In-process harness for instruction tests.
+//!
+//! Accounts live in memory laid out like the runtime's input buffer, so
+//! `resize` and `assign` behave as on-chain, and `Clock`/`Rent` are served by
+//! syscall stubs. Anchor's CPI helpers only run on-chain, so instructions that
+//! create accounts (`init`) or invoke other programs cannot complete here.
+//! Those are tested by calling the handler with accounts built through
+//! [`TestEnv::info`], up to the point where they would first invoke.
+
+use std::{cell::Cell, collections::HashMap, sync::Once};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program_error::ProgramError, program_option::COption, program_pack::Pack},
+    InstructionData,
+};
+use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, AccountState, Mint};
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+use crate::{
+    instructions::{forge_asset_v2, ForgeAssetV2, ForgeAssetV2Args, ForgeAssetV2Bumps},
+    state::{
+        constants::{FORGE_CONFIG_SEED, HASH_BYTES, RECIPE_SEED},
+        ForgeConfig, ForgedAsset, OutputKind, Recipe, RecipeMode, RecipeStatus, ReplayPolicy,
+    },
+};
+
+/// Bytes the runtime reserves after account data for in-place growth.
+const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+thread_local! {
+    static UNIX_TIMESTAMP: Cell<i64> = const { Cell::new(0) };
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: UNIX_TIMESTAMP.get(),
+            ..Clock::default()
+        };
+        // SAFETY: the stubbed syscall writes a `Clock` into its caller's `Clock`.
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the stubbed syscall writes a `Rent` into its caller's `Rent`.
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+}
+
+/// Rent-exempt minimum for `space` bytes.
+pub fn rent_exempt(space: usize) -> u64 {
+    Rent::default().minimum_balance(space)
+}
+
+/// The `ProgramError` an instruction fails with for `err`.
+pub fn error(err: impl Into<anchor_lang::error::Error>) -> ProgramError {
+    err.into().into()
+}
+
+/// An active one-of-one recipe `sword` v1 without ingredients; `bump` is
+/// filled in by [`TestEnv::add_recipe`].
+pub fn recipe(forge_config: Pubkey) -> Recipe {
+    Recipe {
+        forge_config,
+        slug: "sword".to_string(),
+        version: 1,
+        output_kind: OutputKind::OneOfOne,
+        supply_cap: None,
+        minted: 0,
+        metadata_uri: "uri".to_string(),
+        creators: vec![],
+        collection_mint: None,
+        go_live_unix_time: None,
+        pause_at: None,
+        retire_at: None,
+        ingredient_constraints: vec![],
+        trait_table: vec![],
+        extra_outputs: vec![],
+        mode: RecipeMode::Mint,
+        reversible: false,
+        unforged: 0,
+        replay_policy: ReplayPolicy::Unlimited,
+        status: RecipeStatus::Active,
+        previous_version: None,
+        next_version: None,
+        bump: 0,
+        open_uses: 0,
+        _reserved: [0; 3],
+    }
+}
+
+/// Accounts of one test, keyed by address.
+pub struct TestEnv {
+    accounts: HashMap<Pubkey, AccountInfo<'static>>,
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEnv {
+    /// Creates an empty environment with the clock at Unix time 0 and the
+    /// System program and Rent sysvar loaded.
+    pub fn new() -> Self {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+        UNIX_TIMESTAMP.set(0);
+
+        let mut env = Self {
+            accounts: HashMap::new(),
+        };
+        env.add_program(anchor_lang::system_program::ID);
+        let rent = Rent::default();
+        let mut rent_data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+        rent_data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+        rent_data.push(rent.burn_percent);
+        env.add_account(
+            anchor_lang::solana_program::sysvar::rent::ID,
+            1,
+            &rent_data,
+            anchor_lang::solana_program::sysvar::ID,
+            false,
+        );
+        env
+    }
+
+    /// Sets the Unix time `Clock::get` returns.
+    pub fn set_unix_timestamp(&self, unix_timestamp: i64) {
+        UNIX_TIMESTAMP.set(unix_timestamp);
+    }
+
+    /// Adds (or replaces) an account.
+    pub fn add_account(
+        &mut self,
+        key: Pubkey,
+        lamports: u64,
+        data: &[u8],
+        owner: Pubkey,
+        executable: bool,
+    ) {
+        self.accounts
+            .insert(key, leak_account(key, lamports, data, owner, executable));
+    }
+
+    /// Adds an executable account for `program_id`.
+    pub fn add_program(&mut self, program_id: Pubkey) {
+        self.add_account(
+            program_id,
+            1,
+            &[],
+            anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+            true,
+        );
+    }
+
+    /// Adds a System account holding `lamports` and returns its address.
+    pub fn add_wallet(&mut self, lamports: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.add_account(key, lamports, &[], anchor_lang::system_program::ID, false);
+        key
+    }
+
+    /// Adds `account` at `key`, owned by this program and rent-exempt for
+    /// its serialized size.
+    pub fn add_anchor_account<T: AccountSerialize>(&mut self, key: Pubkey, account: &T) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        self.add_account(key, rent_exempt(data.len()), &data, crate::ID, false);
+    }
+
+    /// Adds a funded authority wallet and its `ForgeConfig` PDA, returning
+    /// `(authority, forge_config)`.
+    pub fn add_forge_config(&mut self) -> (Pubkey, Pubkey) {
+        let authority = self.add_wallet(1_000_000_000);
+        let (forge_config, bump) =
+            Pubkey::find_program_address(&[FORGE_CONFIG_SEED, authority.as_ref()], &crate::ID);
+        self.add_anchor_account(
+            forge_config,
+            &ForgeConfig {
+                authority,
+                collection_mint: None,
+                freeze_authority: None,
+                default_royalty_bps: 500,
+                recipe_creation_enabled: true,
+                bump,
+                event_sequence: 0,
+                _reserved: [0; 5],
+            },
+        );
+        (authority, forge_config)
+    }
+
+    /// Adds `recipe` at its PDA, with the matching bump, and returns the
+    /// address.
+    pub fn add_recipe(&mut self, mut recipe: Recipe) -> Pubkey {
+        let (key, bump) = Pubkey::find_program_address(
+            &[
+                RECIPE_SEED,
+                recipe.forge_config.as_ref(),
+                recipe.slug.as_bytes(),
+                &recipe.version.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+        recipe.bump = bump;
+        self.add_anchor_account(key, &recipe);
+        key
+    }
+
+    /// Adds an initialized SPL mint with no decimals whose mint and freeze
+    /// authority is `authority`, and returns its address.
+    pub fn add_mint(&mut self, authority: &Pubkey) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            mint_authority: COption::Some(*authority),
+            supply: 0,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::Some(*authority),
+        }
+        .pack_into_slice(&mut data);
+        self.add_account(
+            key,
+            rent_exempt(Mint::LEN),
+            &data,
+            anchor_spl::token::ID,
+            false,
+        );
+        key
+    }
+
+    /// Adds an empty SPL token account of `mint` owned by `owner`, and
+    /// returns its address.
+    pub fn add_token_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; SplTokenAccount::LEN];
+        SplTokenAccount {
+            mint: *mint,
+            owner: *owner,
+            state: AccountState::Initialized,
+            ..SplTokenAccount::default()
+        }
+        .pack_into_slice(&mut data);
+        self.add_account(
+            key,
+            rent_exempt(SplTokenAccount::LEN),
+            &data,
+            anchor_spl::token::ID,
+            false,
+        );
+        key
+    }
+
+    /// Runs `forge_asset_v2` of `recipe` for `forger`, without ingredients,
+    /// by calling its handler: its `init` accounts cannot be created here,
+    /// so the new mint and its token account already exist and the
+    /// provenance record is blank. Fails at the latest where the handler
+    /// would first invoke another program.
+    pub fn forge_asset_v2(
+        &mut self,
+        recipe: &Pubkey,
+        forger: &Pubkey,
+        recipe_use: &Pubkey,
+        nonce: u64,
+    ) -> Result<[u8; HASH_BYTES]> {
+        let forge_config = self.load::<Recipe>(recipe).forge_config;
+        let mint = self.add_mint(forger);
+        let mint_ata = self.add_token_account(&mint, forger);
+        let forged_asset = Pubkey::new_unique();
+        self.add_account(
+            forged_asset,
+            rent_exempt(ForgedAsset::SIZE),
+            &[0; ForgedAsset::SIZE],
+            crate::ID,
+            false,
+        );
+        for program in [
+            anchor_spl::token::ID,
+            anchor_spl::associated_token::ID,
+            mpl_token_metadata::ID,
+        ] {
+            self.add_program(program);
+        }
+
+        let mut accounts = ForgeAssetV2 {
+            forge_config: Account::try_from(self.info(&forge_config, false, true))?,
+            recipe: Account::try_from(self.info(recipe, false, true))?,
+            recipe_use: UncheckedAccount::try_from(self.info(recipe_use, false, true)),
+            forger: Signer::try_from(self.info(forger, true, true))?,
+            mint: Account::try_from(self.info(&mint, true, true))?,
+            mint_ata: Account::try_from(self.info(&mint_ata, false, true))?,
+            forged_asset: Account::try_from_unchecked(self.info(&forged_asset, false, true))?,
+            forge_escrow: None,
+            forge_commit: None,
+            slot_hashes: None,
+            token_metadata_program: UncheckedAccount::try_from(self.info(
+                &mpl_token_metadata::ID,
+                false,
+                false,
+            )),
+            metadata: UncheckedAccount::try_from(self.info(&Pubkey::new_unique(), false, true)),
+            master_edition: UncheckedAccount::try_from(self.info(
+                &Pubkey::new_unique(),
+                false,
+                true,
+            )),
+            token_program: Program::try_from(self.info(&anchor_spl::token::ID, false, false))?,
+            associated_token_program: Program::try_from(self.info(
+                &anchor_spl::associated_token::ID,
+                false,
+                false,
+            ))?,
+            rent: Sysvar::from_account_info(self.info(
+                &anchor_lang::solana_program::sysvar::rent::ID,
+                false,
+                false,
+            ))?,
+            system_program: Program::try_from(self.info(
+                &anchor_lang::system_program::ID,
+                false,
+                false,
+            ))?,
+        };
+        forge_asset_v2(
+            Context::new(&crate::ID, &mut accounts, &[], ForgeAssetV2Bumps::default()),
+            ForgeAssetV2Args { nonce },
+        )
+    }
+
+    /// A `'static` view of the account at `key` with the given privileges,
+    /// for building an instruction's accounts by hand. Unknown addresses are
+    /// empty System accounts.
+    pub fn info(
+        &mut self,
+        key: &Pubkey,
+        is_signer: bool,
+        is_writable: bool,
+    ) -> &'static AccountInfo<'static> {
+        let mut info = self.stored(key);
+        info.is_signer = is_signer;
+        info.is_writable = is_writable;
+        Box::leak(Box::new(info))
+    }
+
+    /// Lamports currently held by `key`.
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |info| info.lamports())
+    }
+
+    /// Owner of the account at `key`.
+    pub fn owner(&self, key: &Pubkey) -> Pubkey {
+        *self.accounts[key].owner
+    }
+
+    /// Data of the account at `key`.
+    pub fn data(&self, key: &Pubkey) -> Vec<u8> {
+        self.accounts[key].data.borrow().to_vec()
+    }
+
+    /// Decodes the Anchor account at `key`, checking its discriminator.
+    pub fn load<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        T::try_deserialize(&mut &self.data(key)[..]).unwrap()
+    }
+
+    /// Runs an instruction of this program through its entrypoint.
+    pub fn process(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        data: impl InstructionData,
+        remaining_accounts: &[AccountMeta],
+    ) -> std::result::Result<(), ProgramError> {
+        let mut metas = accounts.to_account_metas(None);
+        metas.extend_from_slice(remaining_accounts);
+        let infos: Vec<AccountInfo<'static>> = metas
+            .iter()
+            .map(|meta| {
+                self.info(&meta.pubkey, meta.is_signer, meta.is_writable)
+                    .clone()
+            })
+            .collect();
+        crate::entry(
+            &crate::ID,
+            Box::leak(infos.into_boxed_slice()),
+            &data.data(),
+        )
+    }
+
+    fn stored(&mut self, key: &Pubkey) -> AccountInfo<'static> {
+        self.accounts
+            .entry(*key)
+            .or_insert_with(|| leak_account(*key, 0, &[], anchor_lang::system_program::ID, false))
+            .clone()
+    }
+}
+
+/// Builds an account whose key and data sit in buffers shaped like the
+/// runtime's: the original data length in the 4 bytes before the key, the
+/// current length in the 8 bytes before the data, and room to grow after it.
+fn leak_account(
+    key: Pubkey,
+    lamports: u64,
+    data: &[u8],
+    owner: Pubkey,
+    executable: bool,
+) -> AccountInfo<'static> {
+    #[repr(C)]
+    struct KeyBuffer {
+        original_data_len: u32,
+        key: Pubkey,
+    }
+
+    let key_buffer = Box::leak(Box::new(KeyBuffer {
+        original_data_len: data.len() as u32,
+        key,
+    }));
+    // Whole words keep the length prefix and the data 8-byte aligned.
+    let words = Box::leak(
+        vec![0u64; 1 + (data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8)].into_boxed_slice(),
+    );
+    words[0] = data.len() as u64;
+    // SAFETY: the buffer outlives the program (it is leaked) and has room for
+    // `data` plus the growth padding after the length prefix.
+    let account_data =
+        unsafe { std::slice::from_raw_parts_mut(words[1..].as_mut_ptr().cast::<u8>(), data.len()) };
+    account_data.copy_from_slice(data);
+
+    AccountInfo::new(
+        &key_buffer.key,
+        false,
+        false,
+        Box::leak(Box::new(lamports)),
+        account_data,
+        Box::leak(Box::new(owner)),
+        executable,
+        0,
+    )
+}
@@ -0,0 +1,96 @@
+#!/usr/bin/env node
+
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Command } from "commander";
+import * as fs from "fs";
+import * as path from "path";
+import {
+  loadConfig,
+  deriveForgeCommitPDA,
+  deriveForgeConfigPDA,
+  deriveRecipePDA,
+} from "./utils/config";
+
+const program = new Command();
+
+program
+  .name("commit-forge")
+  .description("Commit to a trait roll before forging a recipe with a trait table")
+  .requiredOption("-s, --slug <slug>", "Recipe slug")
+  .requiredOption("-v, --version <number>", "Recipe version")
+  .option("--cancel", "Close a commit on a retired or closed recipe instead of creating one")
+  .option("-a, --authority <pubkey>", "Forge authority (defaults to wallet)")
+  .action(async (options) => {
+    try {
+      const config = loadConfig();
+      const { connection, programId, wallet } = config;
+
+      // Determine authority
+      const authority = options.authority
+        ? new PublicKey(options.authority)
+        : wallet.publicKey;
+
+      // Derive PDAs
+      const [forgeConfigPDA] = deriveForgeConfigPDA(programId, authority);
+      const version = parseInt(options.version, 10);
+      const [recipePDA] = deriveRecipePDA(
+        programId,
+        forgeConfigPDA,
+        options.slug,
+        version
+      );
+      const [forgeCommitPDA] = deriveForgeCommitPDA(programId, recipePDA, wallet.publicKey);
+
+      console.log(`\n🎲 ${options.cancel ? "Cancelling" : "Committing"} trait roll...`);
+      console.log(`   Recipe: ${recipePDA.toString()}`);
+      console.log(`   Forge Commit: ${forgeCommitPDA.toString()}\n`);
+
+      // Load program
+      const idlPath = path.resolve(__dirname, "../idl/forge.json");
+      if (!fs.existsSync(idlPath)) {
+        throw new Error(`IDL not found at ${idlPath}. Run 'anchor idl build' first.`);
+      }
+
+      const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+      const provider = new anchor.AnchorProvider(
+        connection,
+        new anchor.Wallet(wallet),
+        anchor.AnchorProvider.defaultOptions()
+      );
+      // @ts-ignore - Anchor Program constructor type inference issue
+      const forgeProgram = new anchor.Program(idl as anchor.Idl, provider);
+
+      console.log("📝 Sending transaction...");
+      const tx = options.cancel
+        ? await forgeProgram.methods
+            .cancelForgeCommit()
+            .accounts({
+              forgeCommit: forgeCommitPDA,
+              recipe: recipePDA,
+              forger: wallet.publicKey,
+            })
+            .rpc()
+        : await forgeProgram.methods
+            .commitForge()
+            .accounts({
+              forgeConfig: forgeConfigPDA,
+              recipe: recipePDA,
+              forgeCommit: forgeCommitPDA,
+              forger: wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+      console.log(`✅ Done`);
+      console.log(`   Transaction: ${tx}`);
+      if (!options.cancel) {
+        console.log("   Run forge-asset in a later slot to reveal the roll.\n");
+      }
+    } catch (error: any) {
+      console.error("❌ Error:", error.message);
+      process.exit(1);
+    }
+  });
+
+program.parse();
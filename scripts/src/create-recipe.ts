@@ -14,12 +14,33 @@ program
   .description("Create a new recipe")
   .requiredOption("-s, --slug <slug>", "Recipe slug (max 32 bytes)")
   .requiredOption("-v, --version <number>", "Recipe version")
-  .requiredOption("-k, --output-kind <kind>", "Output kind: one-of-one, edition, semi-fungible")
+  .requiredOption("-k, --output-kind <kind>", "Output kind: one-of-one, edition, semi-fungible, compressed")
   .option("-c, --supply-cap <number>", "Supply cap (optional)")
   .requiredOption("-u, --metadata-uri <uri>", "Metadata URI")
   .option("--collection <pubkey>", "Collection mint pubkey (optional)")
   .option("--go-live <timestamp>", "Go live unix timestamp (optional)")
-  .option("--status <status>", "Initial status: draft, active, paused, retired", "draft")
+  .option("--pause-at <timestamp>", "Unix timestamp after which the recipe pauses (optional)")
+  .option("--retire-at <timestamp>", "Unix timestamp after which the recipe retires (optional)")
+  .option("--parent-mint <pubkey>", "Parent mint for edition output kind")
+  .option("--merkle-tree <pubkey>", "Bubblegum tree for compressed output kind")
+  .option(
+    "--trait-table <path>",
+    "JSON file with rarity tiers: [{ name, weight, metadataUri }] (optional)"
+  )
+  .option(
+    "--extra-outputs <path>",
+    "JSON file with extra outputs: [{ kind, metadataUri, quantity, supplyCap? }] (optional)"
+  )
+  .option("--mode <mode>", "Recipe mode: mint or evolve", "mint")
+  .option("--source-recipe <pubkey>", "Evolve mode: only evolve assets minted by this recipe (optional)")
+  .option("--reversible", "Escrow token ingredients so holders can unforge (optional)", false)
+  .option(
+    "--replay-policy <policy>",
+    "Replay policy: once-per-wallet, once-per-input-set or unlimited",
+    "unlimited"
+  )
+  .option("--previous-version <number>", "Version of the same slug this recipe supersedes (optional)")
+  .option("--status <status>", "Initial status: draft or active", "draft")
   .option("-a, --authority <pubkey>", "Forge authority (defaults to wallet)")
   .action(async (options) => {
     try {
@@ -81,20 +102,93 @@ program
         outputKind = { edition: { parentMint: new PublicKey(options.parentMint) } };
       } else if (options.outputKind === "semi-fungible") {
         outputKind = { semiFungible: {} };
+      } else if (options.outputKind === "compressed") {
+        if (!options.merkleTree) {
+          throw new Error("Merkle tree required for compressed output kind");
+        }
+        outputKind = { compressed: { merkleTree: new PublicKey(options.merkleTree) } };
       } else {
-        throw new Error("Invalid output kind. Must be: one-of-one, edition, or semi-fungible");
+        throw new Error(
+          "Invalid output kind. Must be: one-of-one, edition, semi-fungible, or compressed"
+        );
       }
 
-      // Parse status
+      // Parse status (recipes can only start as draft or active)
       const statusMap: Record<string, any> = {
         draft: { draft: {} },
         active: { active: {} },
-        paused: { paused: {} },
-        retired: { retired: {} },
       };
       const status = statusMap[options.status.toLowerCase()];
       if (!status) {
-        throw new Error("Invalid status. Must be: draft, active, paused, or retired");
+        throw new Error("Invalid status. Must be: draft or active");
+      }
+
+      // Link to the previous version of the same slug, if requested
+      const previousRecipe = options.previousVersion
+        ? deriveRecipePDA(
+            programId,
+            forgeConfigPDA,
+            options.slug,
+            parseInt(options.previousVersion, 10)
+          )[0]
+        : null;
+
+      // Load the optional trait table (weighted rarity tiers)
+      const traitTable = options.traitTable
+        ? (JSON.parse(fs.readFileSync(options.traitTable, "utf-8")) as {
+            name: string;
+            weight: number;
+            metadataUri: string;
+          }[])
+        : [];
+
+      // Load the optional extra outputs minted alongside the primary asset
+      const extraOutputs = options.extraOutputs
+        ? (
+            JSON.parse(fs.readFileSync(options.extraOutputs, "utf-8")) as {
+              kind: string;
+              metadataUri: string;
+              quantity: number;
+              supplyCap?: number;
+            }[]
+          ).map((output) => {
+            if (output.kind !== "one-of-one" && output.kind !== "semi-fungible") {
+              throw new Error("Invalid extra output kind. Must be: one-of-one or semi-fungible");
+            }
+            return {
+              outputKind:
+                output.kind === "one-of-one" ? { oneOfOne: {} } : { semiFungible: {} },
+              metadataUri: output.metadataUri,
+              quantity: new anchor.BN(output.quantity),
+              supplyCap: output.supplyCap != null ? new anchor.BN(output.supplyCap) : null,
+              minted: new anchor.BN(0),
+            };
+          })
+        : [];
+
+      // Parse mode (evolve recipes upgrade existing forged assets in place)
+      let mode: any;
+      if (options.mode === "mint") {
+        mode = { mint: {} };
+      } else if (options.mode === "evolve") {
+        mode = {
+          evolve: {
+            sourceRecipe: options.sourceRecipe ? new PublicKey(options.sourceRecipe) : null,
+          },
+        };
+      } else {
+        throw new Error("Invalid mode. Must be: mint or evolve");
+      }
+
+      // Parse replay policy (which forges share a RecipeUse record)
+      const replayPolicies: Record<string, any> = {
+        "once-per-wallet": { oncePerWallet: {} },
+        "once-per-input-set": { oncePerInputSet: {} },
+        unlimited: { unlimited: {} },
+      };
+      const replayPolicy = replayPolicies[options.replayPolicy];
+      if (!replayPolicy) {
+        throw new Error("Invalid replay policy. Must be: once-per-wallet, once-per-input-set or unlimited");
       }
 
       // Prepare args
@@ -107,9 +201,16 @@ program
         creators: [], // TODO: Add creator parsing
         collectionMint: options.collection ? new PublicKey(options.collection) : null,
         goLiveUnixTime: options.goLive ? parseInt(options.goLive, 10) : null,
+        pauseAt: options.pauseAt ? parseInt(options.pauseAt, 10) : null,
+        retireAt: options.retireAt ? parseInt(options.retireAt, 10) : null,
         ingredientConstraints: [], // TODO: Add constraint parsing
+        traitTable,
+        extraOutputs,
+        mode,
+        reversible: options.reversible,
+        replayPolicy,
         status,
-        previousVersion: null,
+        previousVersion: previousRecipe,
       };
 
       // Create recipe
@@ -119,9 +220,16 @@ program
         .accounts({
           forgeConfig: forgeConfigPDA,
           recipe: recipePDA,
+          previousRecipe,
           authority: authority,
           systemProgram: SystemProgram.programId,
         })
+        // Activating an edition recipe requires the parent mint account.
+        .remainingAccounts(
+          options.parentMint
+            ? [{ pubkey: new PublicKey(options.parentMint), isSigner: false, isWritable: false }]
+            : []
+        )
         .rpc();
 
       console.log(`✅ Recipe created successfully!`);
@@ -0,0 +1,133 @@
+#!/usr/bin/env node
+
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { Command } from "commander";
+import * as fs from "fs";
+import * as path from "path";
+import {
+  computeInputHashV1,
+  deriveForgeConfigPDA,
+  deriveForgedAssetPDA,
+  deriveRecipePDA,
+  deriveRecipeUsePDA,
+  loadConfig,
+  randomNonce,
+} from "./utils/config";
+
+// Program IDs (canonical)
+const TOKEN_PROGRAM_ID = new PublicKey(
+  "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+);
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+  "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+);
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+const program = new Command();
+
+program
+  .name("evolve-asset")
+  .description("Evolve a forged asset in place by calling evolve_asset on an evolve-mode recipe.")
+  .requiredOption("-s, --slug <slug>", "Evolve recipe slug")
+  .requiredOption("-v, --version <number>", "Evolve recipe version (u16)")
+  .requiredOption("-m, --mint <pubkey>", "Mint of the forged asset to evolve")
+  .option(
+    "-a, --authority <pubkey>",
+    "Forge authority (defaults to wallet public key)"
+  )
+  .action(async (options) => {
+    try {
+      const cfg = loadConfig();
+      const { connection, programId, wallet } = cfg;
+
+      const authority = options.authority
+        ? new PublicKey(options.authority)
+        : wallet.publicKey;
+      const version = parseInt(options.version, 10);
+      const mint = new PublicKey(options.mint);
+
+      const [forgeConfigPDA] = deriveForgeConfigPDA(programId, authority);
+      const [recipePDA] = deriveRecipePDA(programId, forgeConfigPDA, options.slug, version);
+      const [forgedAssetPDA] = deriveForgedAssetPDA(programId, mint);
+      const [assetTokenAccount] = PublicKey.findProgramAddressSync(
+        [wallet.publicKey.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM_ID
+      );
+      const [metadata] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+
+      // Load IDL/program (Anchor 0.32 signature: (idl, provider, coder?))
+      const idlPath = path.resolve(__dirname, "../idl/forge.json");
+      if (!fs.existsSync(idlPath)) {
+        throw new Error(`IDL not found at ${idlPath}. Run 'anchor idl build' first.`);
+      }
+      const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+      const provider = new anchor.AnchorProvider(
+        connection,
+        new anchor.Wallet(wallet),
+        anchor.AnchorProvider.defaultOptions()
+      );
+      // @ts-ignore - Anchor Program constructor type inference issue
+      const forgeProgram = new anchor.Program(idl as anchor.Idl, provider);
+
+      const recipeAccount = await (forgeProgram.account as any).recipe.fetch(recipePDA);
+      if ((recipeAccount.ingredientConstraints?.length ?? 0) !== 0) {
+        throw new Error("This CLI currently supports only evolve recipes with 0 ingredient constraints.");
+      }
+      const forgedAsset = await (forgeProgram.account as any).forgedAsset.fetch(forgedAssetPDA);
+
+      // The evolving asset leads the hashed chunks: [10, mint, level (u16 LE)].
+      const targetChunk = Buffer.alloc(1 + 32 + 2);
+      targetChunk[0] = 10;
+      mint.toBuffer().copy(targetChunk, 1);
+      targetChunk.writeUInt16LE(forgedAsset.level, 33);
+      const nonce = randomNonce();
+      const inputHash = computeInputHashV1(
+        recipePDA,
+        recipeAccount.replayPolicy,
+        wallet.publicKey,
+        nonce,
+        [targetChunk],
+        []
+      );
+      const [recipeUsePDA] = deriveRecipeUsePDA(programId, recipePDA, inputHash);
+
+      console.log(`\n🧬 Evolving ${mint.toBase58()} from level ${forgedAsset.level}...`);
+      console.log(`   Recipe: ${recipePDA.toBase58()}`);
+      console.log(`   ForgedAsset: ${forgedAssetPDA.toBase58()}\n`);
+
+      console.log("📝 Sending transaction...");
+      const sig = await forgeProgram.methods
+        .evolveAsset({ inputHash, nonce: new anchor.BN(nonce.toString()) })
+        .accounts({
+          forgeConfig: forgeConfigPDA,
+          recipe: recipePDA,
+          recipeUse: recipeUsePDA,
+          forger: wallet.publicKey,
+          forgedAsset: forgedAssetPDA,
+          assetTokenAccount,
+          metadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("\n✅ evolve_asset succeeded");
+      console.log(`   Signature: ${sig}\n`);
+    } catch (err: any) {
+      console.error("❌ Error:", err?.message ?? String(err));
+      if (err?.logs) {
+        console.error("   Logs:");
+        for (const l of err.logs) console.error("   ", l);
+      }
+      process.exit(1);
+    }
+  });
+
+program.parse();
@@ -6,17 +6,24 @@ import {
   PublicKey,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
   ComputeBudgetProgram,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import { Command } from "commander";
-import * as crypto from "crypto";
 import * as fs from "fs";
 import * as path from "path";
 import {
+  computeInputHashV1,
   deriveForgeConfigPDA,
+  deriveForgeCommitPDA,
+  deriveForgedAssetPDA,
+  deriveForgeEscrowPDA,
+  deriveOutputMintPDA,
   deriveRecipePDA,
   deriveRecipeUsePDA,
   loadConfig,
+  randomNonce,
 } from "./utils/config";
 
 // Program IDs (canonical)
@@ -30,11 +37,6 @@ const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
   "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
 );
 
-function sha256(bytes: Buffer | Uint8Array): Uint8Array {
-  const h = crypto.createHash("sha256").update(bytes).digest();
-  return new Uint8Array(h);
-}
-
 function parseHex32(hex: string): Uint8Array {
   const cleaned = hex.startsWith("0x") ? hex.slice(2) : hex;
   if (!/^[0-9a-fA-F]{64}$/.test(cleaned)) {
@@ -74,6 +76,27 @@ function deriveAta(owner: PublicKey, mint: PublicKey): [PublicKey, number] {
   );
 }
 
+function createAtaIdempotentIx(
+  payer: PublicKey,
+  ata: PublicKey,
+  owner: PublicKey,
+  mint: PublicKey
+): TransactionInstruction {
+  // Associated Token program `CreateIdempotent` (instruction index 1).
+  return new TransactionInstruction({
+    programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: ata, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: false, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from([1]),
+  });
+}
+
 const program = new Command();
 
 program
@@ -89,7 +112,11 @@ program
   )
   .option(
     "--input-hash <hex>",
-    "32-byte input hash (hex). If omitted and recipe has 0 ingredient constraints, uses sha256(forger_pubkey)"
+    "32-byte input hash (hex). If omitted and recipe has 0 ingredient constraints, it is computed under the recipe's replay policy"
+  )
+  .option(
+    "--nonce <u64>",
+    "Nonce hashed by unlimited recipes (defaults to a random one)"
   )
   .option("--dry-run", "Build the transaction but do not send", false)
   .action(async (options) => {
@@ -147,13 +174,11 @@ program
         `   Minted: ${recipeAccount.minted}/${recipeAccount.supplyCap ?? "unlimited"}\n`
       );
 
-      // Generate mint first (needed for hash computation if no inputHash provided)
-      let mint: Keypair;
+      const mint = Keypair.generate();
+      const nonce = options.nonce ? BigInt(options.nonce) : randomNonce();
       let inputHash: Uint8Array;
       if (options.inputHash) {
         inputHash = parseHex32(options.inputHash);
-        // Generate mint after hash (hash doesn't include mint when provided manually)
-        mint = Keypair.generate();
       } else {
         if (ingredientCount !== 0) {
           throw new Error(
@@ -161,32 +186,62 @@ program
               "Either use a 0-ingredient recipe for now, or tell me which constraints you want to support next and I’ll extend this script."
           );
         }
-        // On-chain logic for empty constraints: hashv(&[forger_pubkey, mint_pubkey]) == sha256(forger_pubkey || mint_pubkey)
-        // This allows each wallet to forge multiple times (each mint is unique) while preserving security
-        // Generate mint first so we can include it in the hash
-        mint = Keypair.generate();
-        const forgerBytes = wallet.publicKey.toBuffer();
-        const mintBytes = mint.publicKey.toBuffer();
-        const combined = Buffer.concat([forgerBytes, mintBytes]);
-        inputHash = sha256(combined);
+        inputHash = computeInputHashV1(
+          recipePDA,
+          recipeAccount.replayPolicy,
+          wallet.publicKey,
+          nonce,
+          [],
+          []
+        );
       }
 
       const [recipeUsePDA] = deriveRecipeUsePDA(programId, recipePDA, inputHash);
 
-      // Mint + PDAs (mint already generated above)
+      // Mint + PDAs
       const [mintAta] = deriveAta(wallet.publicKey, mint.publicKey);
       const [metadata] = deriveMetadataPda(mint.publicKey);
       const [masterEdition] = deriveMasterEditionPda(mint.publicKey);
+      const [forgedAsset] = deriveForgedAssetPDA(programId, mint.publicKey);
+      // Reversible recipes record their escrowed ingredients for `unforge`.
+      const [forgeEscrow] = deriveForgeEscrowPDA(programId, mint.publicKey);
+
+      // Recipes with a trait table reveal the roll committed by `commit-forge`.
+      const rollsTraits = (recipeAccount.traitTable?.length ?? 0) > 0;
+      const [forgeCommit] = deriveForgeCommitPDA(programId, recipePDA, wallet.publicKey);
 
-      console.log(`   Input hash: ${Buffer.from(inputHash).toString("hex")}`);
+      console.log(`   Replay policy: ${Object.keys(recipeAccount.replayPolicy)[0]}`);
+      console.log(`   Input hash: ${Buffer.from(inputHash).toString("hex")} (nonce ${nonce})`);
       console.log(`   RecipeUse: ${recipeUsePDA.toBase58()}`);
       console.log(`   Mint: ${mint.publicKey.toBase58()}`);
       console.log(`   Mint ATA: ${mintAta.toBase58()}`);
       console.log(`   Metadata: ${metadata.toBase58()}`);
-      console.log(`   MasterEdition: ${masterEdition.toBase58()}\n`);
+      console.log(`   MasterEdition: ${masterEdition.toBase58()}`);
+      console.log(`   ForgedAsset: ${forgedAsset.toBase58()}\n`);
+
+      // Extra outputs append their accounts after the ingredient accounts.
+      // Semi-fungible outputs mint into the forger's ATA for the shared output mint.
+      const outputAccounts: anchor.web3.AccountMeta[] = [];
+      const outputAtaIxs: TransactionInstruction[] = [];
+      (recipeAccount.extraOutputs ?? []).forEach((output: any, index: number) => {
+        if (!output.outputKind.semiFungible) {
+          throw new Error(
+            "This CLI currently supports only semi-fungible extra outputs; one-of-one outputs need a fresh mint per forge."
+          );
+        }
+        const [outputMint] = deriveOutputMintPDA(programId, recipePDA, index);
+        const [outputAta] = deriveAta(wallet.publicKey, outputMint);
+        outputAccounts.push(
+          { pubkey: outputMint, isSigner: false, isWritable: true },
+          { pubkey: outputAta, isSigner: false, isWritable: true }
+        );
+        outputAtaIxs.push(
+          createAtaIdempotentIx(wallet.publicKey, outputAta, wallet.publicKey, outputMint)
+        );
+      });
 
       // Build instruction args: Anchor expects [u8;32] as number[] or Uint8Array.
-      const args = { inputHash };
+      const args = { inputHash, nonce: new anchor.BN(nonce.toString()) };
 
       // Add compute budget instructions to prevent CU exhaustion
       // NFT minting with metadata requires more than the default 200,000 CUs
@@ -208,6 +263,10 @@ program
           forger: wallet.publicKey,
           mint: mint.publicKey,
           mintAta,
+          forgedAsset,
+          forgeEscrow: recipeAccount.reversible ? forgeEscrow : null,
+          forgeCommit: rollsTraits ? forgeCommit : null,
+          slotHashes: rollsTraits ? SYSVAR_SLOT_HASHES_PUBKEY : null,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           metadata,
           masterEdition,
@@ -216,8 +275,8 @@ program
           rent: SYSVAR_RENT_PUBKEY,
           systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts([]) // ingredient accounts only; empty for 0-constraint recipes
-        .preInstructions([addPriorityFee, modifyComputeUnits]) // Add compute budget before forge instruction
+        .remainingAccounts(outputAccounts) // no ingredient accounts for 0-constraint recipes, then extra outputs
+        .preInstructions([addPriorityFee, modifyComputeUnits, ...outputAtaIxs]) // Compute budget and output ATAs before forge instruction
         .signers([mint]);
 
       if (options.dryRun) {
@@ -0,0 +1,200 @@
+#!/usr/bin/env node
+
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram, TransactionInstruction } from "@solana/web3.js";
+import { Command } from "commander";
+import * as fs from "fs";
+import * as path from "path";
+import {
+  computeInputHashV1,
+  deriveForgeConfigPDA,
+  deriveOutputMintPDA,
+  deriveRecipePDA,
+  deriveRecipeUsePDA,
+  loadConfig,
+  PRIMARY_OUTPUT_INDEX,
+  randomNonce,
+} from "./utils/config";
+
+// Program IDs (canonical)
+const TOKEN_PROGRAM_ID = new PublicKey(
+  "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+);
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+  "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+);
+const BUBBLEGUM_PROGRAM_ID = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+const NOOP_PROGRAM_ID = new PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+const ACCOUNT_COMPRESSION_PROGRAM_ID = new PublicKey(
+  "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+);
+
+function deriveAta(owner: PublicKey, mint: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  );
+}
+
+function createAtaIdempotentIx(
+  payer: PublicKey,
+  ata: PublicKey,
+  owner: PublicKey,
+  mint: PublicKey
+): TransactionInstruction {
+  // Associated Token program `CreateIdempotent` (instruction index 1).
+  return new TransactionInstruction({
+    programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: ata, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: false, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from([1]),
+  });
+}
+
+const program = new Command();
+
+program
+  .name("forge-batch")
+  .description(
+    "Forge several units of a semi-fungible or compressed recipe in one forge_batch transaction"
+  )
+  .requiredOption("-s, --slug <slug>", "Recipe slug")
+  .requiredOption("-v, --version <number>", "Recipe version (u16)")
+  .requiredOption("-n, --count <number>", "Units to mint")
+  .option("-a, --authority <pubkey>", "Forge authority (defaults to wallet public key)")
+  .option("--nonce <u64>", "Nonce hashed by unlimited recipes (defaults to a random one)")
+  .action(async (options) => {
+    try {
+      const cfg = loadConfig();
+      const { connection, programId, wallet } = cfg;
+
+      const authority = options.authority
+        ? new PublicKey(options.authority)
+        : wallet.publicKey;
+      const version = parseInt(options.version, 10);
+      const count = BigInt(options.count);
+      if (count < 1n) {
+        throw new Error("count must be at least 1");
+      }
+
+      const [forgeConfigPDA] = deriveForgeConfigPDA(programId, authority);
+      const [recipePDA] = deriveRecipePDA(programId, forgeConfigPDA, options.slug, version);
+
+      const idlPath = path.resolve(__dirname, "../idl/forge.json");
+      if (!fs.existsSync(idlPath)) {
+        throw new Error(`IDL not found at ${idlPath}. Run 'anchor idl build' first.`);
+      }
+      const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+      const provider = new anchor.AnchorProvider(
+        connection,
+        new anchor.Wallet(wallet),
+        anchor.AnchorProvider.defaultOptions()
+      );
+      // @ts-ignore - Anchor Program constructor type inference issue
+      const forgeProgram = new anchor.Program(idl as anchor.Idl, provider);
+
+      const recipeAccount = await (forgeProgram.account as any).recipe.fetch(recipePDA);
+      const compressed = recipeAccount.outputKind.compressed;
+      if (!recipeAccount.outputKind.semiFungible && !compressed) {
+        throw new Error("forge_batch only supports semi-fungible and compressed recipes");
+      }
+      if ((recipeAccount.ingredientConstraints?.length ?? 0) !== 0) {
+        throw new Error("This CLI currently supports only recipes with 0 ingredient constraints.");
+      }
+
+      const nonce = options.nonce ? BigInt(options.nonce) : randomNonce();
+      const inputHash = computeInputHashV1(
+        recipePDA,
+        recipeAccount.replayPolicy,
+        wallet.publicKey,
+        nonce,
+        [],
+        []
+      );
+      const [recipeUsePDA] = deriveRecipeUsePDA(programId, recipePDA, inputHash);
+
+      // Accounts of the other output path are passed as null.
+      let outputAccounts: Record<string, PublicKey | null>;
+      const preInstructions: TransactionInstruction[] = [];
+      if (compressed) {
+        const merkleTree: PublicKey = compressed.merkleTree;
+        const [treeConfig] = PublicKey.findProgramAddressSync(
+          [merkleTree.toBuffer()],
+          BUBBLEGUM_PROGRAM_ID
+        );
+        outputAccounts = {
+          outputMint: null,
+          forgerTokenAccount: null,
+          treeConfig,
+          merkleTree,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          logWrapper: NOOP_PROGRAM_ID,
+          compressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        };
+      } else {
+        const [outputMint] = deriveOutputMintPDA(programId, recipePDA, PRIMARY_OUTPUT_INDEX);
+        const [forgerTokenAccount] = deriveAta(wallet.publicKey, outputMint);
+        outputAccounts = {
+          outputMint,
+          forgerTokenAccount,
+          treeConfig: null,
+          merkleTree: null,
+          bubblegumProgram: null,
+          logWrapper: null,
+          compressionProgram: null,
+        };
+        preInstructions.push(
+          createAtaIdempotentIx(wallet.publicKey, forgerTokenAccount, wallet.publicKey, outputMint)
+        );
+      }
+
+      console.log(`\n🔥 forge-batch`);
+      console.log(`   Recipe: ${recipePDA.toBase58()}`);
+      if (compressed) {
+        console.log(`   Merkle tree: ${compressed.merkleTree.toBase58()}`);
+      } else {
+        console.log(`   Output mint: ${outputAccounts.outputMint!.toBase58()}`);
+      }
+      console.log(`   Count: ${count}`);
+      console.log(
+        `   Minted: ${recipeAccount.minted}/${recipeAccount.supplyCap ?? "unlimited"}\n`
+      );
+
+      console.log("📝 Sending transaction...");
+      const sig = await forgeProgram.methods
+        .forgeBatch({
+          inputHash,
+          nonce: new anchor.BN(nonce.toString()),
+          count: new anchor.BN(count.toString()),
+        })
+        .accounts({
+          forgeConfig: forgeConfigPDA,
+          recipe: recipePDA,
+          recipeUse: recipeUsePDA,
+          forger: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          ...outputAccounts,
+        })
+        .preInstructions(preInstructions)
+        .rpc();
+
+      console.log("\n✅ forge_batch succeeded");
+      console.log(`   Signature: ${sig}\n`);
+    } catch (err: any) {
+      console.error("❌ Error:", err?.message ?? String(err));
+      if (err?.logs) {
+        console.error("   Logs:");
+        for (const l of err.logs) console.error("   ", l);
+      }
+      process.exit(1);
+    }
+  });
+
+program.parse();
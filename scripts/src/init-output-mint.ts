@@ -0,0 +1,112 @@
+#!/usr/bin/env node
+
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { Command } from "commander";
+import * as fs from "fs";
+import * as path from "path";
+import {
+  loadConfig,
+  deriveForgeConfigPDA,
+  deriveOutputMintPDA,
+  deriveRecipePDA,
+  PRIMARY_OUTPUT_INDEX,
+} from "./utils/config";
+
+// Program IDs (canonical)
+const TOKEN_PROGRAM_ID = new PublicKey(
+  "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+);
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+const program = new Command();
+
+program
+  .name("init-output-mint")
+  .description("Create the shared mint for a recipe's semi-fungible output")
+  .requiredOption("-s, --slug <slug>", "Recipe slug")
+  .requiredOption("-v, --version <number>", "Recipe version")
+  .option("-i, --output-index <number>", "Index into the recipe's extra outputs")
+  .option("--primary", "Create the primary output mint of a semi-fungible recipe", false)
+  .option("-a, --authority <pubkey>", "Forge authority (defaults to wallet)")
+  .action(async (options) => {
+    try {
+      const config = loadConfig();
+      const { connection, programId, wallet } = config;
+
+      // Determine authority
+      const authority = options.authority
+        ? new PublicKey(options.authority)
+        : wallet.publicKey;
+
+      // Derive PDAs
+      const [forgeConfigPDA] = deriveForgeConfigPDA(programId, authority);
+      const version = parseInt(options.version, 10);
+      const [recipePDA] = deriveRecipePDA(
+        programId,
+        forgeConfigPDA,
+        options.slug,
+        version
+      );
+      if (options.primary === (options.outputIndex !== undefined)) {
+        throw new Error("Pass exactly one of --output-index or --primary");
+      }
+      const outputIndex = options.primary
+        ? PRIMARY_OUTPUT_INDEX
+        : parseInt(options.outputIndex, 10);
+      const [outputMintPDA] = deriveOutputMintPDA(programId, recipePDA, outputIndex);
+      const [metadata] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          outputMintPDA.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+
+      console.log(`\n🪙 Initializing output mint...`);
+      console.log(`   Recipe: ${recipePDA.toString()}`);
+      console.log(`   Output Mint: ${outputMintPDA.toString()}\n`);
+
+      // Load program
+      const idlPath = path.resolve(__dirname, "../idl/forge.json");
+      if (!fs.existsSync(idlPath)) {
+        throw new Error(`IDL not found at ${idlPath}. Run 'anchor idl build' first.`);
+      }
+
+      const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+      const provider = new anchor.AnchorProvider(
+        connection,
+        new anchor.Wallet(wallet),
+        anchor.AnchorProvider.defaultOptions()
+      );
+      // @ts-ignore - Anchor Program constructor type inference issue
+      const forgeProgram = new anchor.Program(idl as anchor.Idl, provider);
+
+      console.log("📝 Sending transaction...");
+      const tx = await forgeProgram.methods
+        .initOutputMint({ outputIndex })
+        .accounts({
+          forgeConfig: forgeConfigPDA,
+          recipe: recipePDA,
+          outputMint: outputMintPDA,
+          metadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          authority: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      console.log(`✅ Output mint initialized`);
+      console.log(`   Transaction: ${tx}\n`);
+    } catch (error: any) {
+      console.error("❌ Error:", error.message);
+      process.exit(1);
+    }
+  });
+
+program.parse();
@@ -14,7 +14,8 @@ program
   .description("Toggle recipe status (set recipe status)")
   .requiredOption("-s, --slug <slug>", "Recipe slug")
   .requiredOption("-v, --version <number>", "Recipe version")
-  .requiredOption("--status <status>", "New status: draft, active, paused, retired")
+  .requiredOption("--status <status>", "New status: active, paused, retired")
+  .option("--parent-mint <pubkey>", "Parent mint (required to activate edition recipes)")
   .option("-a, --authority <pubkey>", "Forge authority (defaults to wallet)")
   .action(async (options) => {
     try {
@@ -81,6 +82,11 @@ program
           recipe: recipePDA,
           authority: authority,
         })
+        .remainingAccounts(
+          options.parentMint
+            ? [{ pubkey: new PublicKey(options.parentMint), isSigner: false, isWritable: false }]
+            : []
+        )
         .rpc();
 
       console.log(`✅ Recipe status updated successfully!`);
@@ -0,0 +1,140 @@
+#!/usr/bin/env node
+
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram, TransactionInstruction } from "@solana/web3.js";
+import { Command } from "commander";
+import * as fs from "fs";
+import * as path from "path";
+import {
+  deriveForgeConfigPDA,
+  deriveForgedAssetPDA,
+  deriveForgeEscrowPDA,
+  deriveVaultAuthorityPDA,
+  loadConfig,
+} from "./utils/config";
+
+// Program IDs (canonical)
+const TOKEN_PROGRAM_ID = new PublicKey(
+  "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+);
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+  "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+);
+
+function deriveAta(owner: PublicKey, mint: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  )[0];
+}
+
+function createAtaIdempotentIx(
+  payer: PublicKey,
+  ata: PublicKey,
+  owner: PublicKey,
+  mint: PublicKey
+): TransactionInstruction {
+  // Associated Token program `CreateIdempotent` (instruction index 1).
+  return new TransactionInstruction({
+    programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: ata, isSigner: false, isWritable: true },
+      { pubkey: owner, isSigner: false, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from([1]),
+  });
+}
+
+const program = new Command();
+
+program
+  .name("unforge")
+  .description("Burn a forged asset from a reversible recipe and reclaim its escrowed ingredients.")
+  .requiredOption("-m, --mint <pubkey>", "Mint of the forged asset to unforge")
+  .option(
+    "-a, --authority <pubkey>",
+    "Forge authority (defaults to wallet public key)"
+  )
+  .action(async (options) => {
+    try {
+      const cfg = loadConfig();
+      const { connection, programId, wallet } = cfg;
+
+      const authority = options.authority
+        ? new PublicKey(options.authority)
+        : wallet.publicKey;
+      const mint = new PublicKey(options.mint);
+
+      const [forgeConfigPDA] = deriveForgeConfigPDA(programId, authority);
+      const [forgedAssetPDA] = deriveForgedAssetPDA(programId, mint);
+      const [forgeEscrowPDA] = deriveForgeEscrowPDA(programId, mint);
+      const [vaultAuthority] = deriveVaultAuthorityPDA(programId, forgeConfigPDA);
+
+      // Load IDL/program (Anchor 0.32 signature: (idl, provider, coder?))
+      const idlPath = path.resolve(__dirname, "../idl/forge.json");
+      if (!fs.existsSync(idlPath)) {
+        throw new Error(`IDL not found at ${idlPath}. Run 'anchor idl build' first.`);
+      }
+      const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+      const provider = new anchor.AnchorProvider(
+        connection,
+        new anchor.Wallet(wallet),
+        anchor.AnchorProvider.defaultOptions()
+      );
+      // @ts-ignore - Anchor Program constructor type inference issue
+      const forgeProgram = new anchor.Program(idl as anchor.Idl, provider);
+
+      const forgedAsset = await (forgeProgram.account as any).forgedAsset.fetch(forgedAssetPDA);
+      const escrow = await (forgeProgram.account as any).forgeEscrow.fetch(forgeEscrowPDA);
+
+      // Each escrowed token needs the vault's ATA and the holder's ATA for its mint.
+      const remainingAccounts: anchor.web3.AccountMeta[] = [];
+      const holderAtaIxs: TransactionInstruction[] = [];
+      for (const item of escrow.items as { mint: PublicKey; amount: anchor.BN }[]) {
+        const holderAta = deriveAta(wallet.publicKey, item.mint);
+        remainingAccounts.push(
+          { pubkey: deriveAta(vaultAuthority, item.mint), isSigner: false, isWritable: true },
+          { pubkey: holderAta, isSigner: false, isWritable: true }
+        );
+        holderAtaIxs.push(createAtaIdempotentIx(wallet.publicKey, holderAta, wallet.publicKey, item.mint));
+        console.log(`   Returning ${item.amount.toString()} of ${item.mint.toBase58()}`);
+      }
+
+      console.log(`\n♻️  Unforging ${mint.toBase58()}...`);
+      console.log(`   Recipe: ${forgedAsset.recipe.toBase58()}\n`);
+
+      console.log("📝 Sending transaction...");
+      const sig = await forgeProgram.methods
+        .unforge()
+        .accounts({
+          forgeConfig: forgeConfigPDA,
+          recipe: forgedAsset.recipe,
+          forgedAsset: forgedAssetPDA,
+          forgeEscrow: forgeEscrowPDA,
+          mint,
+          holderTokenAccount: deriveAta(wallet.publicKey, mint),
+          vaultAuthority,
+          holder: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions(holderAtaIxs)
+        .rpc();
+
+      console.log("\n✅ unforge succeeded");
+      console.log(`   Signature: ${sig}\n`);
+    } catch (err: any) {
+      console.error("❌ Error:", err?.message ?? String(err));
+      if (err?.logs) {
+        console.error("   Logs:");
+        for (const l of err.logs) console.error("   ", l);
+      }
+      process.exit(1);
+    }
+  });
+
+program.parse();
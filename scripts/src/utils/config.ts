@@ -1,5 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import * as crypto from "crypto";
 import * as fs from "fs";
 import * as path from "path";
 import dotenv from "dotenv";
@@ -104,3 +105,131 @@ export function deriveRecipeUsePDA(
   );
 }
 
+/**
+ * Derive ForgedAsset PDA (provenance record for a forged mint)
+ */
+export function deriveForgedAssetPDA(
+  programId: PublicKey,
+  mint: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("forged-asset"), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive ForgeCommit PDA (pending trait roll for a forger on a recipe)
+ */
+export function deriveForgeCommitPDA(
+  programId: PublicKey,
+  recipe: PublicKey,
+  forger: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("forge-commit"), recipe.toBuffer(), forger.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Output mint index of a semi-fungible recipe's primary output (minted by forge_batch)
+ */
+export const PRIMARY_OUTPUT_INDEX = 255;
+
+/**
+ * Derive the shared mint PDA of a recipe's semi-fungible output
+ */
+export function deriveOutputMintPDA(
+  programId: PublicKey,
+  recipe: PublicKey,
+  outputIndex: number
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("output-mint"), recipe.toBuffer(), Buffer.from([outputIndex])],
+    programId
+  );
+}
+
+/**
+ * Derive ForgeEscrow PDA (ingredients escrowed for a reversible forge)
+ */
+export function deriveForgeEscrowPDA(
+  programId: PublicKey,
+  mint: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("forge-escrow"), mint.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Derive the forge vault authority PDA (owner of escrowed ingredient tokens)
+ */
+export function deriveVaultAuthorityPDA(
+  programId: PublicKey,
+  forgeConfig: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("forge-vault"), forgeConfig.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Compute the v1 input hash keying a forge's RecipeUse PDA (see
+ * docs/recipes.md, "Input Hash Schema"). `replayPolicy` is the decoded
+ * recipe field, e.g. `{ unlimited: {} }`; the nonce only counts for
+ * unlimited recipes.
+ */
+export function computeInputHashV1(
+  recipe: PublicKey,
+  replayPolicy: Record<string, unknown>,
+  forger: PublicKey,
+  nonce: bigint,
+  chunks: Uint8Array[],
+  inputs: PublicKey[]
+): Uint8Array {
+  const policy = ["oncePerWallet", "oncePerInputSet", "unlimited"].indexOf(
+    Object.keys(replayPolicy)[0]
+  );
+  if (policy < 0) {
+    throw new Error(`Unknown replay policy: ${JSON.stringify(replayPolicy)}`);
+  }
+  const u16 = (value: number) => {
+    const buf = Buffer.alloc(2);
+    buf.writeUInt16LE(value);
+    return buf;
+  };
+  const ingredients = () => [
+    u16(chunks.length),
+    ...chunks.flatMap((chunk) => [u16(chunk.length), Buffer.from(chunk)]),
+    u16(inputs.length),
+    ...inputs.map((input) => input.toBuffer()),
+  ];
+  const parts: Buffer[] = [
+    Buffer.from("forge-input-hash"),
+    Buffer.from([1, policy]),
+    recipe.toBuffer(),
+  ];
+  if (policy === 0) {
+    parts.push(forger.toBuffer());
+  } else if (policy === 1) {
+    parts.push(...ingredients());
+  } else {
+    const nonceBytes = Buffer.alloc(8);
+    nonceBytes.writeBigUInt64LE(nonce);
+    parts.push(forger.toBuffer(), nonceBytes, ...ingredients());
+  }
+  return new Uint8Array(
+    crypto.createHash("sha256").update(Buffer.concat(parts)).digest()
+  );
+}
+
+/**
+ * Random u64 nonce for forges of unlimited recipes.
+ */
+export function randomNonce(): bigint {
+  return crypto.randomBytes(8).readBigUInt64LE();
+}